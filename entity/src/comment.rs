@@ -11,13 +11,15 @@ pub struct Model {
     pub post_id: i32,
     pub text: String,
     pub created_at: DateTime,
+    pub deleted_at: Option<DateTime>,
+    pub edited_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(
         belongs_to = "super::post::Entity",
-        from = "Column::UserId",
+        from = "Column::PostId",
         to = "super::post::Column::Id",
         on_update = "NoAction",
         on_delete = "NoAction"
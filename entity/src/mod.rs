@@ -2,6 +2,29 @@
 
 pub mod prelude;
 
+pub mod audit_event;
+pub mod block;
+pub mod bookmark;
+pub mod bookmark_collection;
 pub mod comment;
+pub mod comment_event;
+pub mod data_export;
+pub mod email_outbox;
+pub mod email_suppression;
+pub mod follow;
+pub mod hashtag;
+pub mod job;
+pub mod job_schedule;
+pub mod mention;
+pub mod mute;
+pub mod notification;
+pub mod notification_preference;
+pub mod outbox;
 pub mod post;
+pub mod reaction;
+pub mod upload;
+pub mod upload_variant;
 pub mod user;
+pub mod webhook;
+pub mod webhook_delivery;
+pub mod webhook_event_type;
@@ -12,12 +12,29 @@ pub struct Model {
     pub image: String,
     pub created_at: String,
     pub user_id: i32,
+    pub deleted_at: Option<DateTime>,
+    pub upload_id: Option<Uuid>,
+    pub format: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub rendered_html: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::bookmark::Entity")]
+    Bookmark,
     #[sea_orm(has_many = "super::comment::Entity")]
     Comment,
+    #[sea_orm(has_many = "super::comment_event::Entity")]
+    CommentEvent,
+    #[sea_orm(
+        belongs_to = "super::upload::Entity",
+        from = "Column::UploadId",
+        to = "super::upload::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Upload,
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::UserId",
@@ -28,12 +45,30 @@ pub enum Relation {
     User,
 }
 
+impl Related<super::bookmark::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Bookmark.def()
+    }
+}
+
 impl Related<super::comment::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Comment.def()
     }
 }
 
+impl Related<super::comment_event::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CommentEvent.def()
+    }
+}
+
+impl Related<super::upload::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Upload.def()
+    }
+}
+
 impl Related<super::user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::User.def()
@@ -1,5 +1,28 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.16
 
+pub use super::audit_event::Entity as AuditEvent;
+pub use super::block::Entity as Block;
+pub use super::bookmark::Entity as Bookmark;
+pub use super::bookmark_collection::Entity as BookmarkCollection;
 pub use super::comment::Entity as Comment;
+pub use super::comment_event::Entity as CommentEvent;
+pub use super::data_export::Entity as DataExport;
+pub use super::email_outbox::Entity as EmailOutbox;
+pub use super::email_suppression::Entity as EmailSuppression;
+pub use super::follow::Entity as Follow;
+pub use super::hashtag::Entity as Hashtag;
+pub use super::job::Entity as Job;
+pub use super::job_schedule::Entity as JobSchedule;
+pub use super::mention::Entity as Mention;
+pub use super::mute::Entity as Mute;
+pub use super::notification::Entity as Notification;
+pub use super::notification_preference::Entity as NotificationPreference;
+pub use super::outbox::Entity as Outbox;
 pub use super::post::Entity as Post;
+pub use super::reaction::Entity as Reaction;
+pub use super::upload::Entity as Upload;
+pub use super::upload_variant::Entity as UploadVariant;
 pub use super::user::Entity as User;
+pub use super::webhook::Entity as Webhook;
+pub use super::webhook_delivery::Entity as WebhookDelivery;
+pub use super::webhook_event_type::Entity as WebhookEventType;
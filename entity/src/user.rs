@@ -14,14 +14,58 @@ pub struct Model {
     #[sea_orm(unique)]
     pub uuid: Uuid,
     pub created_at: DateTime,
+    pub deleted_at: Option<DateTime>,
+    pub is_admin: bool,
+    pub deletion_requested_at: Option<DateTime>,
+    pub tokens_revoked_at: Option<DateTime>,
+    #[sea_orm(unique)]
+    pub handle: Option<String>,
+    pub email_verified_at: Option<DateTime>,
+    pub avatar_upload_id: Option<Uuid>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::audit_event::Entity")]
+    AuditEvent,
+    #[sea_orm(has_many = "super::bookmark_collection::Entity")]
+    BookmarkCollection,
     #[sea_orm(has_many = "super::comment::Entity")]
     Comment,
+    #[sea_orm(has_many = "super::data_export::Entity")]
+    DataExport,
+    #[sea_orm(has_many = "super::mention::Entity")]
+    Mention,
+    #[sea_orm(has_many = "super::notification_preference::Entity")]
+    NotificationPreference,
     #[sea_orm(has_many = "super::post::Entity")]
     Post,
+    #[sea_orm(has_many = "super::reaction::Entity")]
+    Reaction,
+    #[sea_orm(has_many = "super::upload::Entity")]
+    Upload,
+    #[sea_orm(
+        belongs_to = "super::upload::Entity",
+        from = "Column::AvatarUploadId",
+        to = "super::upload::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    AvatarUpload,
+    #[sea_orm(has_many = "super::webhook::Entity")]
+    Webhook,
+}
+
+impl Related<super::audit_event::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AuditEvent.def()
+    }
+}
+
+impl Related<super::bookmark_collection::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BookmarkCollection.def()
+    }
 }
 
 impl Related<super::comment::Entity> for Entity {
@@ -30,10 +74,46 @@ impl Related<super::comment::Entity> for Entity {
     }
 }
 
+impl Related<super::data_export::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DataExport.def()
+    }
+}
+
+impl Related<super::mention::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Mention.def()
+    }
+}
+
+impl Related<super::notification_preference::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::NotificationPreference.def()
+    }
+}
+
 impl Related<super::post::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Post.def()
     }
 }
 
+impl Related<super::reaction::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Reaction.def()
+    }
+}
+
+impl Related<super::upload::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Upload.def()
+    }
+}
+
+impl Related<super::webhook::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Webhook.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -3,6 +3,28 @@ pub use sea_orm_migration::prelude::*;
 mod m20220101_000001_create_table;
 mod m20250916_095631_create_posts_table;
 mod m20250916_095726_create_comments_table;
+mod m20261019_090000_add_soft_delete_columns;
+mod m20261019_091000_add_account_deletion_columns;
+mod m20261019_092000_create_data_exports_table;
+mod m20261019_093000_create_uploads_table;
+mod m20261019_094000_create_upload_variants_table;
+mod m20261019_095000_add_post_format_columns;
+mod m20261019_099000_fix_comments_post_foreign_key;
+mod m20261019_100000_create_reactions_table;
+mod m20261019_101000_create_follows_table;
+mod m20261019_102000_create_blocks_and_mutes_tables;
+mod m20261019_103000_create_bookmarks_tables;
+mod m20261019_104000_create_mentions_and_hashtags_tables;
+mod m20261019_105000_create_notifications_tables;
+mod m20261019_110000_create_comment_events_table;
+mod m20261019_111000_create_webhooks_table;
+mod m20261019_112000_create_jobs_table;
+mod m20261019_113000_create_outbox_table;
+mod m20261019_114000_create_email_tables;
+mod m20261019_115000_add_trace_context_columns;
+mod m20261019_116000_add_avatar_columns;
+mod m20261019_117000_create_audit_events_table;
+mod m20261019_118000_add_mention_notification_key;
 
 pub struct Migrator;
 
@@ -13,6 +35,28 @@ impl MigratorTrait for Migrator {
             Box::new(m20220101_000001_create_table::Migration),
             Box::new(m20250916_095631_create_posts_table::Migration),
             Box::new(m20250916_095726_create_comments_table::Migration),
+            Box::new(m20261019_090000_add_soft_delete_columns::Migration),
+            Box::new(m20261019_091000_add_account_deletion_columns::Migration),
+            Box::new(m20261019_092000_create_data_exports_table::Migration),
+            Box::new(m20261019_093000_create_uploads_table::Migration),
+            Box::new(m20261019_094000_create_upload_variants_table::Migration),
+            Box::new(m20261019_095000_add_post_format_columns::Migration),
+            Box::new(m20261019_099000_fix_comments_post_foreign_key::Migration),
+            Box::new(m20261019_100000_create_reactions_table::Migration),
+            Box::new(m20261019_101000_create_follows_table::Migration),
+            Box::new(m20261019_102000_create_blocks_and_mutes_tables::Migration),
+            Box::new(m20261019_103000_create_bookmarks_tables::Migration),
+            Box::new(m20261019_104000_create_mentions_and_hashtags_tables::Migration),
+            Box::new(m20261019_105000_create_notifications_tables::Migration),
+            Box::new(m20261019_110000_create_comment_events_table::Migration),
+            Box::new(m20261019_111000_create_webhooks_table::Migration),
+            Box::new(m20261019_112000_create_jobs_table::Migration),
+            Box::new(m20261019_113000_create_outbox_table::Migration),
+            Box::new(m20261019_114000_create_email_tables::Migration),
+            Box::new(m20261019_115000_add_trace_context_columns::Migration),
+            Box::new(m20261019_116000_add_avatar_columns::Migration),
+            Box::new(m20261019_117000_create_audit_events_table::Migration),
+            Box::new(m20261019_118000_add_mention_notification_key::Migration),
         ]
     }
 }
@@ -0,0 +1,65 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(User::Table)
+                .add_column(timestamp_null(User::DeletedAt))
+                .add_column(boolean(User::IsAdmin).default(false))
+                .to_owned()
+        ).await?;
+
+        manager.alter_table(
+            Table::alter().table(Post::Table).add_column(timestamp_null(Post::DeletedAt)).to_owned()
+        ).await?;
+
+        manager.alter_table(
+            Table::alter()
+                .table(Comment::Table)
+                .add_column(timestamp_null(Comment::DeletedAt))
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter().table(Comment::Table).drop_column(Comment::DeletedAt).to_owned()
+        ).await?;
+
+        manager.alter_table(
+            Table::alter().table(Post::Table).drop_column(Post::DeletedAt).to_owned()
+        ).await?;
+
+        manager.alter_table(
+            Table::alter()
+                .table(User::Table)
+                .drop_column(User::DeletedAt)
+                .drop_column(User::IsAdmin)
+                .to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    DeletedAt,
+    IsAdmin,
+}
+
+#[derive(DeriveIden)]
+enum Post {
+    Table,
+    DeletedAt,
+}
+
+#[derive(DeriveIden)]
+enum Comment {
+    Table,
+    DeletedAt,
+}
@@ -0,0 +1,34 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(User::Table)
+                .add_column(timestamp_null(User::DeletionRequestedAt))
+                .add_column(timestamp_null(User::TokensRevokedAt))
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(User::Table)
+                .drop_column(User::DeletionRequestedAt)
+                .drop_column(User::TokensRevokedAt)
+                .to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    DeletionRequestedAt,
+    TokensRevokedAt,
+}
@@ -0,0 +1,50 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+use crate::m20220101_000001_create_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(DataExport::Table)
+                .if_not_exists()
+                .col(uuid(DataExport::Id).primary_key())
+                .col(integer(DataExport::UserId).not_null())
+                .col(string(DataExport::Status).not_null())
+                .col(string_null(DataExport::FilePath))
+                .col(string_null(DataExport::Error))
+                .col(timestamp(DataExport::CreatedAt).not_null())
+                .col(timestamp_null(DataExport::CompletedAt))
+                .col(timestamp_null(DataExport::ExpiresAt))
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-data-exports-user-id")
+                        .from(DataExport::Table, DataExport::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(DataExport::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum DataExport {
+    Table,
+    Id,
+    UserId,
+    Status,
+    FilePath,
+    Error,
+    CreatedAt,
+    CompletedAt,
+    ExpiresAt,
+}
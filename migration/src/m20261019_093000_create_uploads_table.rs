@@ -0,0 +1,75 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+use crate::{ m20220101_000001_create_table::User, m20250916_095631_create_posts_table::Post };
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(Upload::Table)
+                .if_not_exists()
+                .col(uuid(Upload::Id).primary_key())
+                .col(integer(Upload::UserId).not_null())
+                .col(string(Upload::StorageKey).not_null())
+                .col(string(Upload::ContentType).not_null())
+                .col(big_integer(Upload::SizeBytes).not_null())
+                .col(string_null(Upload::OriginalFilename))
+                .col(timestamp(Upload::CreatedAt).not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-uploads-user-id")
+                        .from(Upload::Table, Upload::UserId)
+                        .to(User::Table, User::Id)
+                )
+                .to_owned()
+        ).await?;
+
+        manager.alter_table(
+            Table::alter()
+                .table(Post::Table)
+                .add_column(uuid_null(PostUpload::UploadId))
+                .add_foreign_key(
+                    TableForeignKey::new()
+                        .name("fk-posts-upload-id")
+                        .from_tbl(Post::Table)
+                        .from_col(PostUpload::UploadId)
+                        .to_tbl(Upload::Table)
+                        .to_col(Upload::Id)
+                )
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(Post::Table)
+                .drop_foreign_key(Alias::new("fk-posts-upload-id"))
+                .drop_column(PostUpload::UploadId)
+                .to_owned()
+        ).await?;
+
+        manager.drop_table(Table::drop().table(Upload::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Upload {
+    Table,
+    Id,
+    UserId,
+    StorageKey,
+    ContentType,
+    SizeBytes,
+    OriginalFilename,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum PostUpload {
+    UploadId,
+}
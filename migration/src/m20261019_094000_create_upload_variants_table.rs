@@ -0,0 +1,87 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+use crate::m20261019_093000_create_uploads_table::Upload;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(Upload::Table)
+                .add_column(integer_null(UploadMetadata::Width))
+                .add_column(integer_null(UploadMetadata::Height))
+                .add_column(string_null(UploadMetadata::Blurhash))
+                .add_column(timestamp_null(UploadMetadata::ProcessedAt))
+                .to_owned()
+        ).await?;
+
+        manager.create_table(
+            Table::create()
+                .table(UploadVariant::Table)
+                .if_not_exists()
+                .col(pk_auto(UploadVariant::Id))
+                .col(uuid(UploadVariant::UploadId).not_null())
+                .col(string(UploadVariant::Label).not_null())
+                .col(integer(UploadVariant::Width).not_null())
+                .col(integer(UploadVariant::Height).not_null())
+                .col(string(UploadVariant::ContentType).not_null())
+                .col(string(UploadVariant::StorageKey).not_null())
+                .col(big_integer(UploadVariant::SizeBytes).not_null())
+                .col(timestamp(UploadVariant::CreatedAt).not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-upload-variants-upload-id")
+                        .from(UploadVariant::Table, UploadVariant::UploadId)
+                        .to(Upload::Table, Upload::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .index(
+                    Index::create()
+                        .name("idx-upload-variants-upload-id-label")
+                        .col(UploadVariant::UploadId)
+                        .col(UploadVariant::Label)
+                        .unique()
+                )
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(UploadVariant::Table).to_owned()).await?;
+
+        manager.alter_table(
+            Table::alter()
+                .table(Upload::Table)
+                .drop_column(UploadMetadata::Width)
+                .drop_column(UploadMetadata::Height)
+                .drop_column(UploadMetadata::Blurhash)
+                .drop_column(UploadMetadata::ProcessedAt)
+                .to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UploadMetadata {
+    Width,
+    Height,
+    Blurhash,
+    ProcessedAt,
+}
+
+#[derive(DeriveIden)]
+enum UploadVariant {
+    Table,
+    Id,
+    UploadId,
+    Label,
+    Width,
+    Height,
+    ContentType,
+    StorageKey,
+    SizeBytes,
+    CreatedAt,
+}
@@ -0,0 +1,34 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(Post::Table)
+                .add_column(string(Post::Format).default("plain"))
+                .add_column(text_null(Post::RenderedHtml))
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(Post::Table)
+                .drop_column(Post::Format)
+                .drop_column(Post::RenderedHtml)
+                .to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Post {
+    Table,
+    Format,
+    RenderedHtml,
+}
@@ -0,0 +1,46 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20250916_095631_create_posts_table::Post;
+
+const FOREIGN_KEY: &str = "fk-comments-post-id";
+
+/// The original comments migration pointed `fk-comments-post-id` at `user_id`
+/// instead of `post_id`.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        repoint(manager, Comment::PostId).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Fails if some comment's user id is not also a post id, as it did originally
+        repoint(manager, Comment::UserId).await
+    }
+}
+
+async fn repoint(manager: &SchemaManager<'_>, column: Comment) -> Result<(), DbErr> {
+    manager.alter_table(
+        Table::alter()
+            .table(Comment::Table)
+            .drop_foreign_key(Alias::new(FOREIGN_KEY))
+            .add_foreign_key(
+                TableForeignKey::new()
+                    .name(FOREIGN_KEY)
+                    .from_tbl(Comment::Table)
+                    .from_col(column)
+                    .to_tbl(Post::Table)
+                    .to_col(Post::Id)
+            )
+            .to_owned()
+    ).await
+}
+
+#[derive(DeriveIden)]
+enum Comment {
+    Table,
+    UserId,
+    PostId,
+}
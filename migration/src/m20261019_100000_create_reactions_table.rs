@@ -0,0 +1,64 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+use crate::m20220101_000001_create_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(Reaction::Table)
+                .if_not_exists()
+                .col(pk_auto(Reaction::Id))
+                .col(integer(Reaction::UserId).not_null())
+                .col(string(Reaction::TargetType).not_null())
+                .col(integer(Reaction::TargetId).not_null())
+                .col(string(Reaction::Kind).not_null())
+                .col(timestamp(Reaction::CreatedAt).not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-reactions-user-id")
+                        .from(Reaction::Table, Reaction::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .index(
+                    Index::create()
+                        .name("idx-reactions-unique")
+                        .col(Reaction::UserId)
+                        .col(Reaction::TargetType)
+                        .col(Reaction::TargetId)
+                        .col(Reaction::Kind)
+                        .unique()
+                )
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-reactions-target")
+                .table(Reaction::Table)
+                .col(Reaction::TargetType)
+                .col(Reaction::TargetId)
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Reaction::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Reaction {
+    Table,
+    Id,
+    UserId,
+    TargetType,
+    TargetId,
+    Kind,
+    CreatedAt,
+}
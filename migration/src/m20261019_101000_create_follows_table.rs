@@ -0,0 +1,71 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+use crate::{ m20220101_000001_create_table::User, m20250916_095631_create_posts_table::Post };
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(Follow::Table)
+                .if_not_exists()
+                .col(integer(Follow::FollowerId).not_null())
+                .col(integer(Follow::FolloweeId).not_null())
+                .col(timestamp(Follow::CreatedAt).not_null())
+                .primary_key(Index::create().col(Follow::FollowerId).col(Follow::FolloweeId))
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-follows-follower-id")
+                        .from(Follow::Table, Follow::FollowerId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-follows-followee-id")
+                        .from(Follow::Table, Follow::FolloweeId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-follows-followee-id")
+                .table(Follow::Table)
+                .col(Follow::FolloweeId)
+                .col(Follow::FollowerId)
+                .to_owned()
+        ).await?;
+
+        // Lets the feed walk each followed author's posts newest first
+        manager.create_index(
+            Index::create()
+                .name("idx-posts-user-id-id")
+                .table(Post::Table)
+                .col(Post::UserId)
+                .col(Post::Id)
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_index(
+            Index::drop().name("idx-posts-user-id-id").table(Post::Table).to_owned()
+        ).await?;
+
+        manager.drop_table(Table::drop().table(Follow::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Follow {
+    Table,
+    FollowerId,
+    FolloweeId,
+    CreatedAt,
+}
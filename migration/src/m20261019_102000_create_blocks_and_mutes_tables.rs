@@ -0,0 +1,91 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+use crate::m20220101_000001_create_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(Block::Table)
+                .if_not_exists()
+                .col(integer(Block::BlockerId).not_null())
+                .col(integer(Block::BlockedId).not_null())
+                .col(timestamp(Block::CreatedAt).not_null())
+                .primary_key(Index::create().col(Block::BlockerId).col(Block::BlockedId))
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-blocks-blocker-id")
+                        .from(Block::Table, Block::BlockerId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-blocks-blocked-id")
+                        .from(Block::Table, Block::BlockedId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-blocks-blocked-id")
+                .table(Block::Table)
+                .col(Block::BlockedId)
+                .col(Block::BlockerId)
+                .to_owned()
+        ).await?;
+
+        manager.create_table(
+            Table::create()
+                .table(Mute::Table)
+                .if_not_exists()
+                .col(integer(Mute::MuterId).not_null())
+                .col(integer(Mute::MutedId).not_null())
+                .col(timestamp(Mute::CreatedAt).not_null())
+                .primary_key(Index::create().col(Mute::MuterId).col(Mute::MutedId))
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-mutes-muter-id")
+                        .from(Mute::Table, Mute::MuterId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-mutes-muted-id")
+                        .from(Mute::Table, Mute::MutedId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Mute::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(Block::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Block {
+    Table,
+    BlockerId,
+    BlockedId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Mute {
+    Table,
+    MuterId,
+    MutedId,
+    CreatedAt,
+}
@@ -0,0 +1,107 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+use crate::{ m20220101_000001_create_table::User, m20250916_095631_create_posts_table::Post };
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(BookmarkCollection::Table)
+                .if_not_exists()
+                .col(uuid(BookmarkCollection::Id).primary_key())
+                .col(integer(BookmarkCollection::UserId).not_null())
+                .col(string(BookmarkCollection::Name).not_null())
+                .col(uuid_null(BookmarkCollection::ShareToken).unique_key())
+                .col(timestamp(BookmarkCollection::CreatedAt).not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-bookmark-collections-user-id")
+                        .from(BookmarkCollection::Table, BookmarkCollection::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-bookmark-collections-user-id-name")
+                .table(BookmarkCollection::Table)
+                .col(BookmarkCollection::UserId)
+                .col(BookmarkCollection::Name)
+                .unique()
+                .to_owned()
+        ).await?;
+
+        // Bookmarks go with their post when it is hard-deleted
+        manager.create_table(
+            Table::create()
+                .table(Bookmark::Table)
+                .if_not_exists()
+                .col(uuid(Bookmark::CollectionId).not_null())
+                .col(integer(Bookmark::PostId).not_null())
+                .col(timestamp(Bookmark::CreatedAt).not_null())
+                .primary_key(Index::create().col(Bookmark::CollectionId).col(Bookmark::PostId))
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-bookmarks-collection-id")
+                        .from(Bookmark::Table, Bookmark::CollectionId)
+                        .to(BookmarkCollection::Table, BookmarkCollection::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-bookmarks-post-id")
+                        .from(Bookmark::Table, Bookmark::PostId)
+                        .to(Post::Table, Post::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-bookmarks-collection-id-created-at")
+                .table(Bookmark::Table)
+                .col(Bookmark::CollectionId)
+                .col(Bookmark::CreatedAt)
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-bookmarks-post-id")
+                .table(Bookmark::Table)
+                .col(Bookmark::PostId)
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Bookmark::Table).to_owned()).await?;
+
+        manager.drop_table(Table::drop().table(BookmarkCollection::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BookmarkCollection {
+    Table,
+    Id,
+    UserId,
+    Name,
+    ShareToken,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Bookmark {
+    Table,
+    CollectionId,
+    PostId,
+    CreatedAt,
+}
@@ -0,0 +1,127 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Handles are optional so existing accounts keep working until they pick one
+        manager.alter_table(
+            Table::alter()
+                .table(User::Table)
+                .add_column(string_len_null(User::Handle, 30))
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-users-handle")
+                .table(User::Table)
+                .col(User::Handle)
+                .unique()
+                .to_owned()
+        ).await?;
+
+        manager.create_table(
+            Table::create()
+                .table(Mention::Table)
+                .if_not_exists()
+                .col(string(Mention::TargetType).not_null())
+                .col(integer(Mention::TargetId).not_null())
+                .col(integer(Mention::UserId).not_null())
+                .col(timestamp(Mention::CreatedAt).not_null())
+                .primary_key(
+                    Index::create().col(Mention::TargetType).col(Mention::TargetId).col(Mention::UserId)
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-mentions-user-id")
+                        .from(Mention::Table, Mention::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-mentions-user-id-created-at")
+                .table(Mention::Table)
+                .col(Mention::UserId)
+                .col(Mention::CreatedAt)
+                .to_owned()
+        ).await?;
+
+        manager.create_table(
+            Table::create()
+                .table(Hashtag::Table)
+                .if_not_exists()
+                .col(string(Hashtag::Tag).not_null())
+                .col(string(Hashtag::TargetType).not_null())
+                .col(integer(Hashtag::TargetId).not_null())
+                .col(timestamp(Hashtag::CreatedAt).not_null())
+                .primary_key(
+                    Index::create().col(Hashtag::Tag).col(Hashtag::TargetType).col(Hashtag::TargetId)
+                )
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-hashtags-tag-created-at")
+                .table(Hashtag::Table)
+                .col(Hashtag::Tag)
+                .col(Hashtag::CreatedAt)
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-hashtags-target")
+                .table(Hashtag::Table)
+                .col(Hashtag::TargetType)
+                .col(Hashtag::TargetId)
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Hashtag::Table).to_owned()).await?;
+
+        manager.drop_table(Table::drop().table(Mention::Table).to_owned()).await?;
+
+        manager.drop_index(
+            Index::drop().name("idx-users-handle").table(User::Table).to_owned()
+        ).await?;
+
+        manager.alter_table(
+            Table::alter().table(User::Table).drop_column(User::Handle).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+    Handle,
+}
+
+#[derive(DeriveIden)]
+enum Mention {
+    Table,
+    TargetType,
+    TargetId,
+    UserId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Hashtag {
+    Table,
+    Tag,
+    TargetType,
+    TargetId,
+    CreatedAt,
+}
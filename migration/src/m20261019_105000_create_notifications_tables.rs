@@ -0,0 +1,97 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+use crate::m20220101_000001_create_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(Notification::Table)
+                .if_not_exists()
+                .col(pk_auto(Notification::Id))
+                .col(integer(Notification::UserId).not_null())
+                .col(integer_null(Notification::ActorId))
+                .col(string(Notification::Kind).not_null())
+                .col(string_null(Notification::TargetType))
+                .col(integer_null(Notification::TargetId))
+                .col(timestamp_null(Notification::ReadAt))
+                .col(timestamp(Notification::CreatedAt).not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-notifications-user-id")
+                        .from(Notification::Table, Notification::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-notifications-actor-id")
+                        .from(Notification::Table, Notification::ActorId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::SetNull)
+                )
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-notifications-user-id-id")
+                .table(Notification::Table)
+                .col(Notification::UserId)
+                .col(Notification::Id)
+                .to_owned()
+        ).await?;
+
+        // Absent rows mean the kind is enabled
+        manager.create_table(
+            Table::create()
+                .table(NotificationPreference::Table)
+                .if_not_exists()
+                .col(integer(NotificationPreference::UserId).not_null())
+                .col(string(NotificationPreference::Kind).not_null())
+                .col(boolean(NotificationPreference::Enabled).not_null())
+                .primary_key(
+                    Index::create().col(NotificationPreference::UserId).col(NotificationPreference::Kind)
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-notification-preferences-user-id")
+                        .from(NotificationPreference::Table, NotificationPreference::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(NotificationPreference::Table).to_owned()).await?;
+
+        manager.drop_table(Table::drop().table(Notification::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Notification {
+    Table,
+    Id,
+    UserId,
+    ActorId,
+    Kind,
+    TargetType,
+    TargetId,
+    ReadAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum NotificationPreference {
+    Table,
+    UserId,
+    Kind,
+    Enabled,
+}
@@ -0,0 +1,72 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+use crate::m20250916_095631_create_posts_table::Post;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter().table(Comment::Table).add_column(timestamp_null(Comment::EditedAt)).to_owned()
+        ).await?;
+
+        // The id doubles as the SSE event id, so it must only ever increase
+        manager.create_table(
+            Table::create()
+                .table(CommentEvent::Table)
+                .if_not_exists()
+                .col(big_integer(CommentEvent::Id).auto_increment().primary_key())
+                .col(integer(CommentEvent::PostId).not_null())
+                .col(integer(CommentEvent::CommentId).not_null())
+                .col(integer(CommentEvent::AuthorId).not_null())
+                .col(string(CommentEvent::Kind).not_null())
+                .col(text(CommentEvent::Payload).not_null())
+                .col(timestamp(CommentEvent::CreatedAt).not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-comment-events-post-id")
+                        .from(CommentEvent::Table, CommentEvent::PostId)
+                        .to(Post::Table, Post::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-comment-events-post-id-id")
+                .table(CommentEvent::Table)
+                .col(CommentEvent::PostId)
+                .col(CommentEvent::Id)
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(CommentEvent::Table).to_owned()).await?;
+
+        manager.alter_table(
+            Table::alter().table(Comment::Table).drop_column(Comment::EditedAt).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Comment {
+    Table,
+    EditedAt,
+}
+
+#[derive(DeriveIden)]
+enum CommentEvent {
+    Table,
+    Id,
+    PostId,
+    CommentId,
+    AuthorId,
+    Kind,
+    Payload,
+    CreatedAt,
+}
@@ -0,0 +1,139 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+use crate::m20220101_000001_create_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(Webhook::Table)
+                .if_not_exists()
+                .col(pk_auto(Webhook::Id))
+                .col(string(Webhook::Url).not_null())
+                .col(string(Webhook::Secret).not_null())
+                .col(boolean(Webhook::Active).not_null().default(true))
+                .col(integer_null(Webhook::CreatedBy))
+                .col(timestamp(Webhook::CreatedAt).not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-webhooks-created-by")
+                        .from(Webhook::Table, Webhook::CreatedBy)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::SetNull)
+                )
+                .to_owned()
+        ).await?;
+
+        manager.create_table(
+            Table::create()
+                .table(WebhookEventType::Table)
+                .if_not_exists()
+                .col(integer(WebhookEventType::WebhookId).not_null())
+                .col(string(WebhookEventType::EventType).not_null())
+                .primary_key(
+                    Index::create().col(WebhookEventType::WebhookId).col(WebhookEventType::EventType)
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-webhook-event-types-webhook-id")
+                        .from(WebhookEventType::Table, WebhookEventType::WebhookId)
+                        .to(Webhook::Table, Webhook::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .to_owned()
+        ).await?;
+
+        // Deliveries double as the queue: the worker claims rows that are still
+        // pending and whose next attempt is due.
+        manager.create_table(
+            Table::create()
+                .table(WebhookDelivery::Table)
+                .if_not_exists()
+                .col(big_integer(WebhookDelivery::Id).auto_increment().primary_key())
+                .col(integer(WebhookDelivery::WebhookId).not_null())
+                .col(string(WebhookDelivery::EventType).not_null())
+                .col(text(WebhookDelivery::Payload).not_null())
+                .col(string(WebhookDelivery::Status).not_null())
+                .col(integer(WebhookDelivery::Attempts).not_null().default(0))
+                .col(timestamp_null(WebhookDelivery::NextAttemptAt))
+                .col(timestamp_null(WebhookDelivery::LastAttemptAt))
+                .col(integer_null(WebhookDelivery::ResponseStatus))
+                .col(text_null(WebhookDelivery::LastError))
+                .col(timestamp(WebhookDelivery::CreatedAt).not_null())
+                .col(timestamp_null(WebhookDelivery::DeliveredAt))
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-webhook-deliveries-webhook-id")
+                        .from(WebhookDelivery::Table, WebhookDelivery::WebhookId)
+                        .to(Webhook::Table, Webhook::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-webhook-deliveries-status-next-attempt-at")
+                .table(WebhookDelivery::Table)
+                .col(WebhookDelivery::Status)
+                .col(WebhookDelivery::NextAttemptAt)
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-webhook-deliveries-webhook-id-id")
+                .table(WebhookDelivery::Table)
+                .col(WebhookDelivery::WebhookId)
+                .col(WebhookDelivery::Id)
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(WebhookDelivery::Table).to_owned()).await?;
+
+        manager.drop_table(Table::drop().table(WebhookEventType::Table).to_owned()).await?;
+
+        manager.drop_table(Table::drop().table(Webhook::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Webhook {
+    Table,
+    Id,
+    Url,
+    Secret,
+    Active,
+    CreatedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum WebhookEventType {
+    Table,
+    WebhookId,
+    EventType,
+}
+
+#[derive(DeriveIden)]
+enum WebhookDelivery {
+    Table,
+    Id,
+    WebhookId,
+    EventType,
+    Payload,
+    Status,
+    Attempts,
+    NextAttemptAt,
+    LastAttemptAt,
+    ResponseStatus,
+    LastError,
+    CreatedAt,
+    DeliveredAt,
+}
@@ -0,0 +1,79 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(Job::Table)
+                .if_not_exists()
+                .col(big_integer(Job::Id).auto_increment().primary_key())
+                .col(string(Job::Queue).not_null())
+                .col(string(Job::Kind).not_null())
+                .col(text(Job::Payload).not_null())
+                .col(string(Job::Status).not_null())
+                .col(integer(Job::Attempts).not_null().default(0))
+                .col(integer(Job::MaxAttempts).not_null())
+                .col(timestamp(Job::RunAt).not_null())
+                .col(timestamp_null(Job::LockedAt))
+                .col(text_null(Job::LastError))
+                .col(timestamp(Job::CreatedAt).not_null())
+                .col(timestamp_null(Job::FinishedAt))
+                .to_owned()
+        ).await?;
+
+        // Workers claim the oldest due job of their queue
+        manager.create_index(
+            Index::create()
+                .name("idx-jobs-queue-status-run-at")
+                .table(Job::Table)
+                .col(Job::Queue)
+                .col(Job::Status)
+                .col(Job::RunAt)
+                .to_owned()
+        ).await?;
+
+        // One row per cron schedule, so only one instance enqueues each firing
+        manager.create_table(
+            Table::create()
+                .table(JobSchedule::Table)
+                .if_not_exists()
+                .col(string(JobSchedule::Name).not_null().primary_key())
+                .col(timestamp(JobSchedule::NextRunAt).not_null())
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(JobSchedule::Table).to_owned()).await?;
+
+        manager.drop_table(Table::drop().table(Job::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Job {
+    Table,
+    Id,
+    Queue,
+    Kind,
+    Payload,
+    Status,
+    Attempts,
+    MaxAttempts,
+    RunAt,
+    LockedAt,
+    LastError,
+    CreatedAt,
+    FinishedAt,
+}
+
+#[derive(DeriveIden)]
+enum JobSchedule {
+    Table,
+    Name,
+    NextRunAt,
+}
@@ -0,0 +1,53 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(Outbox::Table)
+                .if_not_exists()
+                .col(big_integer(Outbox::Id).auto_increment().primary_key())
+                .col(string(Outbox::EventType).not_null())
+                .col(text(Outbox::Payload).not_null())
+                .col(integer(Outbox::Attempts).not_null().default(0))
+                .col(timestamp(Outbox::AvailableAt).not_null())
+                .col(text_null(Outbox::PendingSubscribers))
+                .col(text_null(Outbox::LastError))
+                .col(timestamp(Outbox::CreatedAt).not_null())
+                .col(timestamp_null(Outbox::DispatchedAt))
+                .to_owned()
+        ).await?;
+
+        // The dispatcher reads undispatched events oldest first
+        manager.create_index(
+            Index::create()
+                .name("idx-outbox-dispatched-at-available-at")
+                .table(Outbox::Table)
+                .col(Outbox::DispatchedAt)
+                .col(Outbox::AvailableAt)
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Outbox::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Outbox {
+    Table,
+    Id,
+    EventType,
+    Payload,
+    Attempts,
+    AvailableAt,
+    PendingSubscribers,
+    LastError,
+    CreatedAt,
+    DispatchedAt,
+}
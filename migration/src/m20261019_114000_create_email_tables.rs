@@ -0,0 +1,89 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter().table(User::Table).add_column(timestamp_null(User::EmailVerifiedAt)).to_owned()
+        ).await?;
+
+        manager.create_table(
+            Table::create()
+                .table(EmailOutbox::Table)
+                .if_not_exists()
+                .col(big_integer(EmailOutbox::Id).auto_increment().primary_key())
+                .col(string(EmailOutbox::Recipient).not_null())
+                .col(string(EmailOutbox::Template).not_null())
+                .col(string(EmailOutbox::Subject).not_null())
+                .col(text(EmailOutbox::Html).not_null())
+                .col(text(EmailOutbox::Text).not_null())
+                .col(string(EmailOutbox::Status).not_null())
+                .col(integer(EmailOutbox::Attempts).not_null().default(0))
+                .col(text_null(EmailOutbox::LastError))
+                .col(timestamp(EmailOutbox::CreatedAt).not_null())
+                .col(timestamp_null(EmailOutbox::SentAt))
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx-email-outbox-recipient")
+                .table(EmailOutbox::Table)
+                .col(EmailOutbox::Recipient)
+                .to_owned()
+        ).await?;
+
+        // Addresses stored lower-cased, so a suppression matches however the
+        // address was typed
+        manager.create_table(
+            Table::create()
+                .table(EmailSuppression::Table)
+                .if_not_exists()
+                .col(string(EmailSuppression::Email).not_null().primary_key())
+                .col(string(EmailSuppression::Reason).not_null())
+                .col(timestamp(EmailSuppression::CreatedAt).not_null())
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(EmailSuppression::Table).to_owned()).await?;
+
+        manager.drop_table(Table::drop().table(EmailOutbox::Table).to_owned()).await?;
+
+        manager.alter_table(Table::alter().table(User::Table).drop_column(User::EmailVerifiedAt).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    EmailVerifiedAt,
+}
+
+#[derive(DeriveIden)]
+enum EmailOutbox {
+    Table,
+    Id,
+    Recipient,
+    Template,
+    Subject,
+    Html,
+    Text,
+    Status,
+    Attempts,
+    LastError,
+    CreatedAt,
+    SentAt,
+}
+
+#[derive(DeriveIden)]
+enum EmailSuppression {
+    Table,
+    Email,
+    Reason,
+    CreatedAt,
+}
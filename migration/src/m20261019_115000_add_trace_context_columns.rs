@@ -0,0 +1,33 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter().table(Job::Table).add_column(string_null(Job::TraceContext)).to_owned()
+        ).await?;
+        manager.alter_table(
+            Table::alter().table(Outbox::Table).add_column(string_null(Outbox::TraceContext)).to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(Table::alter().table(Job::Table).drop_column(Job::TraceContext).to_owned()).await?;
+        manager.alter_table(Table::alter().table(Outbox::Table).drop_column(Outbox::TraceContext).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Job {
+    Table,
+    TraceContext,
+}
+
+#[derive(DeriveIden)]
+enum Outbox {
+    Table,
+    TraceContext,
+}
@@ -0,0 +1,58 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Avatars get square variants, so processing needs to know what an upload is for
+        manager.alter_table(
+            Table::alter()
+                .table(Upload::Table)
+                .add_column(string(Upload::Purpose).default("post"))
+                .to_owned()
+        ).await?;
+
+        manager.alter_table(
+            Table::alter()
+                .table(User::Table)
+                .add_column(uuid_null(User::AvatarUploadId))
+                .add_foreign_key(
+                    TableForeignKey::new()
+                        .name("fk-users-avatar-upload-id")
+                        .from_tbl(User::Table)
+                        .from_col(User::AvatarUploadId)
+                        .to_tbl(Upload::Table)
+                        .to_col(Upload::Id)
+                        .on_delete(ForeignKeyAction::SetNull)
+                )
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(User::Table)
+                .drop_foreign_key(Alias::new("fk-users-avatar-upload-id"))
+                .drop_column(User::AvatarUploadId)
+                .to_owned()
+        ).await?;
+
+        manager.alter_table(Table::alter().table(Upload::Table).drop_column(Upload::Purpose).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Upload {
+    Table,
+    Id,
+    Purpose,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    AvatarUploadId,
+}
@@ -0,0 +1,79 @@
+use sea_orm_migration::{ prelude::*, schema::* };
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(AuditEvent::Table)
+                .if_not_exists()
+                .col(big_integer(AuditEvent::Id).auto_increment().primary_key())
+                .col(integer(AuditEvent::UserId).not_null())
+                .col(string(AuditEvent::EventType).not_null())
+                .col(text(AuditEvent::Payload).not_null())
+                .col(timestamp(AuditEvent::CreatedAt).not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-audit-events-user-id")
+                        .from(AuditEvent::Table, AuditEvent::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                )
+                .to_owned()
+        ).await?;
+
+        // Exports read one user's events in order
+        manager.create_index(
+            Index::create()
+                .name("idx-audit-events-user-id-id")
+                .table(AuditEvent::Table)
+                .col(AuditEvent::UserId)
+                .col(AuditEvent::Id)
+                .to_owned()
+        ).await?;
+
+        // Carry over what the outbox still holds, from the keys events used to name
+        // the acting user by
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"INSERT INTO audit_event (user_id, event_type, payload, created_at)
+                SELECT acted.user_id, outbox.event_type, outbox.payload, outbox.created_at
+                FROM outbox
+                CROSS JOIN LATERAL (
+                    SELECT COALESCE(
+                        (outbox.payload::jsonb ->> 'author_id')::int,
+                        (outbox.payload::jsonb ->> 'follower_id')::int,
+                        (outbox.payload::jsonb ->> 'user_id')::int
+                    ) AS user_id
+                ) acted
+                WHERE acted.user_id IN (SELECT id FROM "user")
+                ORDER BY outbox.id"#
+            ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(AuditEvent::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuditEvent {
+    Table,
+    Id,
+    UserId,
+    EventType,
+    Payload,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
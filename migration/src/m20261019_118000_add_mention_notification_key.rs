@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Outbox retries used to insert the same mention again; keep the first one
+        db.execute_unprepared(
+            r#"DELETE FROM notification duplicate
+            USING notification original
+            WHERE duplicate.kind = 'mention'
+                AND original.kind = 'mention'
+                AND duplicate.user_id = original.user_id
+                AND duplicate.target_type = original.target_type
+                AND duplicate.target_id = original.target_id
+                AND duplicate.actor_id = original.actor_id
+                AND duplicate.id > original.id"#
+        ).await?;
+
+        // Partial, since comments and reactions can legitimately repeat on a target.
+        // The index builder has no WHERE clause, hence the raw statement
+        db.execute_unprepared(
+            r#"CREATE UNIQUE INDEX "idx-notifications-mention-key"
+            ON notification (user_id, target_type, target_id, actor_id)
+            WHERE kind = 'mention'"#
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_index(
+            Index::drop().name("idx-notifications-mention-key").table(Notification::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Notification {
+    Table,
+}
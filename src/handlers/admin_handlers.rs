@@ -0,0 +1,210 @@
+use axum::{ extract::{ Path, State }, http::StatusCode, response::IntoResponse, Json };
+use sea_orm::{
+    sea_query::Expr,
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait,
+    DbErr,
+    EntityTrait,
+    QueryFilter,
+    TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::user_models::AppState,
+    utils::{ api_errors::APIError, soft_delete::SoftDelete },
+};
+
+fn restore_error(e: DbErr) -> APIError {
+    APIError {
+        message: format!("Failed to restore: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    }
+}
+
+fn lookup_error(e: DbErr) -> APIError {
+    APIError {
+        message: format!("Database error while finding deleted row: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(1),
+    }
+}
+
+fn not_deleted(what: &str) -> APIError {
+    APIError {
+        message: format!("No deleted {} found", what),
+        status_code: StatusCode::NOT_FOUND,
+        error_code: Some(2),
+    }
+}
+
+/// Restores a soft-deleted user along with the posts and comments that were
+/// deleted in the same operation.
+pub async fn restore_user(
+    State(state): State<AppState>,
+    Path(uuid): Path<Uuid>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let user = entity::user::Entity
+        ::find_deleted()
+        .filter(entity::user::Column::Uuid.eq(uuid))
+        .one(db).await
+        .map_err(lookup_error)?
+        .ok_or(not_deleted("user"))?;
+
+    let deleted_at = user.deleted_at;
+    let txn = db.begin().await.map_err(restore_error)?;
+
+    entity::post::Entity
+        ::update_many()
+        .col_expr(entity::post::Column::DeletedAt, Expr::value(None::<chrono::NaiveDateTime>))
+        .filter(entity::post::Column::UserId.eq(user.id))
+        .filter(entity::post::Column::DeletedAt.eq(deleted_at))
+        .exec(&txn).await
+        .map_err(restore_error)?;
+
+    entity::comment::Entity
+        ::update_many()
+        .col_expr(entity::comment::Column::DeletedAt, Expr::value(None::<chrono::NaiveDateTime>))
+        .filter(entity::comment::Column::UserId.eq(user.id))
+        .filter(entity::comment::Column::DeletedAt.eq(deleted_at))
+        .exec(&txn).await
+        .map_err(restore_error)?;
+
+    let mut active_user: entity::user::ActiveModel = user.into();
+    active_user.deleted_at = Set(None);
+    active_user.update(&txn).await.map_err(restore_error)?;
+
+    txn.commit().await.map_err(restore_error)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({"message": "User restored successfully", "uuid": uuid})),
+    ))
+}
+
+pub async fn restore_post(
+    State(state): State<AppState>,
+    Path(id): Path<i32>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let post = entity::post::Entity
+        ::find_deleted()
+        .filter(entity::post::Column::Id.eq(id))
+        .one(db).await
+        .map_err(lookup_error)?
+        .ok_or(not_deleted("post"))?;
+
+    ensure_author_active(db, post.user_id).await?;
+
+    let mut active_post: entity::post::ActiveModel = post.into();
+    active_post.deleted_at = Set(None);
+    active_post.update(db).await.map_err(restore_error)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Post restored successfully", "id": id}))))
+}
+
+pub async fn restore_comment(
+    State(state): State<AppState>,
+    Path(id): Path<i32>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let comment = entity::comment::Entity
+        ::find_deleted()
+        .filter(entity::comment::Column::Id.eq(id))
+        .one(db).await
+        .map_err(lookup_error)?
+        .ok_or(not_deleted("comment"))?;
+
+    ensure_author_active(db, comment.user_id).await?;
+
+    let post_active = entity::post::Entity
+        ::find_active()
+        .filter(entity::post::Column::Id.eq(comment.post_id))
+        .one(db).await
+        .map_err(lookup_error)?
+        .is_some();
+    if !post_active {
+        return Err(APIError {
+            message: "Cannot restore a comment on a deleted post".to_string(),
+            status_code: StatusCode::CONFLICT,
+            error_code: Some(5),
+        });
+    }
+
+    let mut active_comment: entity::comment::ActiveModel = comment.into();
+    active_comment.deleted_at = Set(None);
+    active_comment.update(db).await.map_err(restore_error)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({"message": "Comment restored successfully", "id": id})),
+    ))
+}
+
+async fn ensure_author_active(db: &sea_orm::DatabaseConnection, user_id: i32) -> Result<(), APIError> {
+    let author = entity::user::Entity
+        ::find_active()
+        .filter(entity::user::Column::Id.eq(user_id))
+        .one(db).await
+        .map_err(lookup_error)?;
+
+    if author.is_none() {
+        return Err(APIError {
+            message: "Cannot restore content of a deleted user".to_string(),
+            status_code: StatusCode::CONFLICT,
+            error_code: Some(5),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::{
+        handlers::user_handlers::delete_user,
+        testing::{ self, create_comment, create_post, create_user },
+    };
+
+    #[tokio::test]
+    async fn restoring_a_user_brings_back_only_what_was_deleted_with_them() {
+        let Some(db) = testing::database().await else {
+            return;
+        };
+        let state = testing::app_state(&db);
+        let user = create_user(&db).await;
+        let other = create_user(&db).await;
+        let post = create_post(&db, user.id).await;
+        let other_post = create_post(&db, other.id).await;
+        let comment = create_comment(&db, user.id, other_post.id).await;
+        // deleted on its own earlier, so it has to stay deleted
+        let earlier = create_post(&db, user.id).await;
+        let mut active_post: entity::post::ActiveModel = earlier.clone().into();
+        active_post.deleted_at = Set(Some((Utc::now() - chrono::Duration::hours(1)).naive_utc()));
+        active_post.update(&db).await.unwrap();
+
+        assert!(delete_user(State(state.clone()), Path(user.uuid)).await.is_ok());
+        assert!(entity::user::Entity::find_active().filter(entity::user::Column::Id.eq(user.id)).one(&db).await.unwrap().is_none());
+        assert!(entity::post::Entity::find_active().filter(entity::post::Column::Id.eq(post.id)).one(&db).await.unwrap().is_none());
+
+        assert!(restore_user(State(state), Path(user.uuid)).await.is_ok());
+
+        let user = entity::user::Entity::find_by_id(user.id).one(&db).await.unwrap().unwrap();
+        let post = entity::post::Entity::find_by_id(post.id).one(&db).await.unwrap().unwrap();
+        let comment = entity::comment::Entity::find_by_id(comment.id).one(&db).await.unwrap().unwrap();
+        let earlier = entity::post::Entity::find_by_id(earlier.id).one(&db).await.unwrap().unwrap();
+        assert!(user.deleted_at.is_none());
+        assert!(post.deleted_at.is_none());
+        assert!(comment.deleted_at.is_none());
+        assert!(earlier.deleted_at.is_some());
+    }
+}
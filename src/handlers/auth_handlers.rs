@@ -1,12 +1,9 @@
 use crate::{
-    models::user_models::{
-        AppState,
-        CreateUserModel,
-        GetUserModel,
-        GetUserResponseModel,
-        UserModel,
-    },
-    utils::{ api_errors::APIError, jwt::encode_jwt },
+    handlers::user_handlers::available_handle,
+    models::user_models::{ AppState, CreateUserModel, GetUserModel, GetUserResponseModel },
+    services::events::{ self, DomainEvent },
+    telemetry::metrics,
+    utils::{ api_errors::APIError, jwt::encode_jwt, soft_delete::SoftDelete },
 };
 use axum::{ extract::State, response::{ IntoResponse }, Json };
 use chrono::Utc;
@@ -17,8 +14,8 @@ use sea_orm::{
     ActiveValue::Set,
     Condition,
     ColumnTrait,
-    EntityTrait,
     QueryFilter,
+    TransactionTrait,
 };
 use tracing::{ error, info };
 use uuid::Uuid;
@@ -31,8 +28,14 @@ pub async fn create_user(
 
     //check if user with this mail
 
+    let handle = match &user_data.handle {
+        Some(handle) => Some(available_handle(db, handle, None).await?),
+        None => None,
+    };
+
     let user_model = user::ActiveModel {
         name: Set(user_data.name.to_owned()),
+        handle: Set(handle),
         email: Set(user_data.email.to_owned()),
         password: Set(user_data.password.to_owned()),
         uuid: Set(Uuid::new_v4()),
@@ -40,14 +43,19 @@ pub async fn create_user(
         ..Default::default()
     };
 
-    let user = user_model.insert(db).await.map_err(|e| {
+    let insert_error = |e: sea_orm::DbErr| {
         error!("Error creatating user: {:?}", e);
         APIError {
             message: format!("Error creating user: {}", e),
             status_code: StatusCode::INTERNAL_SERVER_ERROR,
             error_code: Some(3),
         }
-    })?;
+    };
+
+    let txn = db.begin().await.map_err(insert_error)?;
+    let user = user_model.insert(&txn).await.map_err(insert_error)?;
+    events::record(&txn, &(DomainEvent::UserCreated { user_id: user.id })).await.map_err(insert_error)?;
+    txn.commit().await.map_err(insert_error)?;
 
     info!("User created successfully with UUID: {}", user.uuid);
 
@@ -71,7 +79,7 @@ pub async fn get_user(
     
 
     let user = user::Entity
-        ::find()
+        ::find_active()
         .filter(
             Condition::all()
                 .add(user::Column::Email.eq(user_data.email))
@@ -87,17 +95,21 @@ pub async fn get_user(
             }
         })
         ? // `?` will return early if Err(APIError)
-        .ok_or(APIError {
-            message: "User not found".to_string(),
-            status_code: StatusCode::NOT_FOUND,
-            error_code: Some(4),
+        .ok_or_else(|| {
+            metrics::record_login(false);
+            APIError {
+                message: "User not found".to_string(),
+                status_code: StatusCode::NOT_FOUND,
+                error_code: Some(4),
+            }
         })?;
 
-    let token = encode_jwt(user.email).map_err(|r| APIError {
+    let token = encode_jwt(user.email).map_err(|_| APIError {
         message: "Failed to login".to_string(),
         status_code: StatusCode::UNAUTHORIZED,
         error_code: Some(1),
     })?;
+    metrics::record_login(true);
 
     Ok(Json(GetUserResponseModel { token }))
 }
@@ -0,0 +1,249 @@
+use axum::{ extract::{ Path, Query, State }, http::StatusCode, response::IntoResponse, Extension, Json };
+use chrono::Utc;
+use sea_orm::{
+    sea_query::OnConflict,
+    ActiveValue::Set,
+    ColumnTrait,
+    Condition,
+    DatabaseConnection,
+    EntityTrait,
+    JoinType,
+    QueryFilter,
+    QueryOrder,
+    QuerySelect,
+    RelationTrait,
+    TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    handlers::follow_handlers::find_active_user,
+    models::{ block_models::{ HiddenUserModel, HiddenUserPageQuery }, user_models::AppState },
+    utils::{ api_errors::APIError, soft_delete::SoftDelete },
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 20;
+const MAX_PAGE_SIZE: u64 = 100;
+
+fn reject_self(target: &entity::user::Model, identity: &entity::user::Model, action: &str) -> Result<(), APIError> {
+    if target.id == identity.id {
+        return Err(APIError {
+            message: format!("You cannot {} yourself", action),
+            status_code: StatusCode::BAD_REQUEST,
+            error_code: Some(6),
+        });
+    }
+    Ok(())
+}
+
+/// Blocking severs any follow between the two users, in either direction, and
+/// hides each user's posts and comments from the other. Blocking twice is a no-op.
+pub async fn block_user(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(uuid): Path<Uuid>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let blocked = find_active_user(db, uuid).await?;
+    reject_self(&blocked, &identity, "block")?;
+
+    let write_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Failed to block user: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let txn = db.begin().await.map_err(write_error)?;
+
+    entity::block::Entity
+        ::insert(entity::block::ActiveModel {
+            blocker_id: Set(identity.id),
+            blocked_id: Set(blocked.id),
+            created_at: Set(Utc::now().naive_utc()),
+        })
+        .on_conflict(
+            OnConflict::columns([entity::block::Column::BlockerId, entity::block::Column::BlockedId])
+                .do_nothing()
+                .to_owned()
+        )
+        .exec_without_returning(&txn).await
+        .map_err(write_error)?;
+
+    entity::follow::Entity
+        ::delete_many()
+        .filter(
+            Condition::any()
+                .add(
+                    entity::follow::Column::FollowerId
+                        .eq(identity.id)
+                        .and(entity::follow::Column::FolloweeId.eq(blocked.id))
+                )
+                .add(
+                    entity::follow::Column::FollowerId
+                        .eq(blocked.id)
+                        .and(entity::follow::Column::FolloweeId.eq(identity.id))
+                )
+        )
+        .exec(&txn).await
+        .map_err(write_error)?;
+
+    txn.commit().await.map_err(write_error)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "User blocked", "uuid": uuid}))))
+}
+
+pub async fn unblock_user(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(uuid): Path<Uuid>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let blocked = find_active_user(db, uuid).await?;
+
+    entity::block::Entity
+        ::delete_many()
+        .filter(entity::block::Column::BlockerId.eq(identity.id))
+        .filter(entity::block::Column::BlockedId.eq(blocked.id))
+        .exec(db).await
+        .map_err(|e| APIError {
+            message: format!("Failed to unblock user: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "User unblocked", "uuid": uuid}))))
+}
+
+/// Muting only hides the muted user's content from the caller; unlike a block it
+/// does not stop them from interacting. Muting twice is a no-op.
+pub async fn mute_user(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(uuid): Path<Uuid>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let muted = find_active_user(db, uuid).await?;
+    reject_self(&muted, &identity, "mute")?;
+
+    entity::mute::Entity
+        ::insert(entity::mute::ActiveModel {
+            muter_id: Set(identity.id),
+            muted_id: Set(muted.id),
+            created_at: Set(Utc::now().naive_utc()),
+        })
+        .on_conflict(
+            OnConflict::columns([entity::mute::Column::MuterId, entity::mute::Column::MutedId])
+                .do_nothing()
+                .to_owned()
+        )
+        .exec_without_returning(db).await
+        .map_err(|e| APIError {
+            message: format!("Failed to mute user: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "User muted", "uuid": uuid}))))
+}
+
+pub async fn unmute_user(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(uuid): Path<Uuid>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let muted = find_active_user(db, uuid).await?;
+
+    entity::mute::Entity
+        ::delete_many()
+        .filter(entity::mute::Column::MuterId.eq(identity.id))
+        .filter(entity::mute::Column::MutedId.eq(muted.id))
+        .exec(db).await
+        .map_err(|e| APIError {
+            message: format!("Failed to unmute user: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "User unmuted", "uuid": uuid}))))
+}
+
+/// Lists the users on the far side of the caller's block or mute rows: `join_on`
+/// is the relation to the listed users, `owner_column` the column holding the caller
+/// and `created_at` the row's creation time.
+async fn list_hidden_users<R: RelationTrait>(
+    db: &DatabaseConnection,
+    identity: &entity::user::Model,
+    join_on: R,
+    owner_column: impl ColumnTrait,
+    created_at: impl ColumnTrait,
+    page: HiddenUserPageQuery
+) -> Result<Vec<HiddenUserModel>, APIError> {
+    let mut query = entity::user::Entity
+        ::find_active()
+        .select_only()
+        .column(entity::user::Column::Uuid)
+        .column(entity::user::Column::Name)
+        .column(created_at)
+        .join_rev(JoinType::InnerJoin, join_on.def())
+        .filter(owner_column.eq(identity.id));
+    if let Some(before) = page.before {
+        query = query.filter(created_at.lt(before));
+    }
+
+    let rows: Vec<(Uuid, String, chrono::NaiveDateTime)> = query
+        .order_by_desc(created_at)
+        .limit(page.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE))
+        .into_tuple()
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while fetching users: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    Ok(
+        rows
+            .into_iter()
+            .map(|(uuid, name, since)| HiddenUserModel { uuid, name, since })
+            .collect()
+    )
+}
+
+pub async fn list_blocks(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Query(page): Query<HiddenUserPageQuery>
+) -> Result<Json<Vec<HiddenUserModel>>, APIError> {
+    let blocks = list_hidden_users(
+        &state.db,
+        &identity,
+        entity::block::Relation::Blocked,
+        entity::block::Column::BlockerId,
+        entity::block::Column::CreatedAt,
+        page
+    ).await?;
+
+    Ok(Json(blocks))
+}
+
+pub async fn list_mutes(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Query(page): Query<HiddenUserPageQuery>
+) -> Result<Json<Vec<HiddenUserModel>>, APIError> {
+    let mutes = list_hidden_users(
+        &state.db,
+        &identity,
+        entity::mute::Relation::Muted,
+        entity::mute::Column::MuterId,
+        entity::mute::Column::CreatedAt,
+        page
+    ).await?;
+
+    Ok(Json(mutes))
+}
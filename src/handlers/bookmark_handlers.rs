@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+
+use axum::{ extract::{ Path, Query, State }, http::StatusCode, response::IntoResponse, Extension, Json };
+use chrono::Utc;
+use sea_orm::{
+    sea_query::OnConflict,
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait,
+    DatabaseConnection,
+    EntityTrait,
+    PaginatorTrait,
+    QueryFilter,
+    QueryOrder,
+    QuerySelect,
+    QueryTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        bookmark_models::{
+            BookmarkModel,
+            BookmarkPageQuery,
+            CollectionModel,
+            CreateCollectionModel,
+            UpdateCollectionModel,
+        },
+        user_models::AppState,
+    },
+    services::posts::post_responses,
+    utils::{ api_errors::APIError, visibility::Visibility },
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 20;
+const MAX_PAGE_SIZE: u64 = 100;
+const MAX_NAME_LENGTH: usize = 100;
+
+fn not_found() -> APIError {
+    APIError {
+        message: "Collection not found".to_string(),
+        status_code: StatusCode::NOT_FOUND,
+        error_code: Some(2),
+    }
+}
+
+fn validate_name(name: &str) -> Result<String, APIError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(APIError {
+            message: format!("Collection names must be 1 to {} characters", MAX_NAME_LENGTH),
+            status_code: StatusCode::BAD_REQUEST,
+            error_code: Some(6),
+        });
+    }
+    Ok(name.to_string())
+}
+
+/// Collection names are unique per user.
+async fn ensure_name_available(db: &DatabaseConnection, user_id: i32, name: &str) -> Result<(), APIError> {
+    let taken = entity::bookmark_collection::Entity
+        ::find()
+        .filter(entity::bookmark_collection::Column::UserId.eq(user_id))
+        .filter(entity::bookmark_collection::Column::Name.eq(name))
+        .count(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while checking collections: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    if taken > 0 {
+        return Err(APIError {
+            message: format!("A collection named \"{}\" already exists", name),
+            status_code: StatusCode::CONFLICT,
+            error_code: Some(5),
+        });
+    }
+    Ok(())
+}
+
+/// Collections are private, so other users' collections are reported as missing.
+async fn find_own_collection(
+    db: &DatabaseConnection,
+    user_id: i32,
+    id: Uuid
+) -> Result<entity::bookmark_collection::Model, APIError> {
+    entity::bookmark_collection::Entity
+        ::find_by_id(id)
+        .filter(entity::bookmark_collection::Column::UserId.eq(user_id))
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding collection: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or_else(not_found)
+}
+
+pub async fn create_collection(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Json(collection_data): Json<CreateCollectionModel>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let name = validate_name(&collection_data.name)?;
+    ensure_name_available(db, identity.id, &name).await?;
+
+    let collection = (entity::bookmark_collection::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(identity.id),
+        name: Set(name),
+        share_token: Set(None),
+        created_at: Set(Utc::now().naive_utc()),
+    })
+        .insert(db).await
+        .map_err(|e| APIError {
+            message: format!("Failed to create collection: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+
+    Ok((StatusCode::CREATED, Json(CollectionModel::from(collection))))
+}
+
+pub async fn list_collections(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>
+) -> Result<Json<Vec<CollectionModel>>, APIError> {
+    let collections = entity::bookmark_collection::Entity
+        ::find()
+        .filter(entity::bookmark_collection::Column::UserId.eq(identity.id))
+        .order_by_asc(entity::bookmark_collection::Column::Name)
+        .all(&state.db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while fetching collections: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    Ok(Json(collections.into_iter().map(Into::into).collect()))
+}
+
+/// Renames the collection and/or turns its share link on or off. Turning sharing
+/// off and on again issues a new link, so old links stop working.
+pub async fn update_collection(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(id): Path<Uuid>,
+    Json(collection_data): Json<UpdateCollectionModel>
+) -> Result<Json<CollectionModel>, APIError> {
+    let db = &state.db;
+
+    let collection = find_own_collection(db, identity.id, id).await?;
+    let share_token = collection.share_token;
+    let current_name = collection.name.clone();
+    let mut active_collection: entity::bookmark_collection::ActiveModel = collection.into();
+
+    if let Some(name) = collection_data.name {
+        let name = validate_name(&name)?;
+        if name != current_name {
+            ensure_name_available(db, identity.id, &name).await?;
+            active_collection.name = Set(name);
+        }
+    }
+    match collection_data.shared {
+        Some(true) if share_token.is_none() => {
+            active_collection.share_token = Set(Some(Uuid::new_v4()));
+        }
+        Some(false) => {
+            active_collection.share_token = Set(None);
+        }
+        _ => {}
+    }
+
+    let collection = active_collection.update(db).await.map_err(|e| APIError {
+        message: format!("Failed to update collection: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    })?;
+
+    Ok(Json(collection.into()))
+}
+
+pub async fn delete_collection(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(id): Path<Uuid>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let collection = find_own_collection(db, identity.id, id).await?;
+
+    entity::bookmark_collection::Entity
+        ::delete_by_id(collection.id)
+        .exec(db).await
+        .map_err(|e| APIError {
+            message: format!("Failed to delete collection: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Collection deleted", "id": id}))))
+}
+
+/// Saving a post that is already in the collection is a no-op.
+pub async fn add_bookmark(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path((id, post_id)): Path<(Uuid, i32)>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let collection = find_own_collection(db, identity.id, id).await?;
+
+    entity::post::Entity
+        ::find_visible_to(identity.id)
+        .filter(entity::post::Column::Id.eq(post_id))
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding post: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or(APIError {
+            message: "Post not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+            error_code: Some(2),
+        })?;
+
+    entity::bookmark::Entity
+        ::insert(entity::bookmark::ActiveModel {
+            collection_id: Set(collection.id),
+            post_id: Set(post_id),
+            created_at: Set(Utc::now().naive_utc()),
+        })
+        .on_conflict(
+            OnConflict::columns([entity::bookmark::Column::CollectionId, entity::bookmark::Column::PostId])
+                .do_nothing()
+                .to_owned()
+        )
+        .exec_without_returning(db).await
+        .map_err(|e| APIError {
+            message: format!("Failed to save post: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Post saved", "post_id": post_id}))))
+}
+
+pub async fn remove_bookmark(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path((id, post_id)): Path<(Uuid, i32)>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let collection = find_own_collection(db, identity.id, id).await?;
+
+    entity::bookmark::Entity
+        ::delete_many()
+        .filter(entity::bookmark::Column::CollectionId.eq(collection.id))
+        .filter(entity::bookmark::Column::PostId.eq(post_id))
+        .exec(db).await
+        .map_err(|e| APIError {
+            message: format!("Failed to remove post: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Post removed", "post_id": post_id}))))
+}
+
+/// Lists a page of the collection's posts as seen by `viewer_id`. Bookmarks of
+/// posts that were deleted, or that the viewer cannot see, are skipped.
+async fn list_bookmarks(
+    db: &DatabaseConnection,
+    collection: &entity::bookmark_collection::Model,
+    viewer_id: i32,
+    page: BookmarkPageQuery
+) -> Result<Vec<BookmarkModel>, APIError> {
+    let lookup_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Database error while fetching bookmarks: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(1),
+    };
+
+    let visible_posts = entity::post::Entity
+        ::find_visible_to(viewer_id)
+        .select_only()
+        .column(entity::post::Column::Id)
+        .into_query();
+
+    let mut query = entity::bookmark::Entity
+        ::find()
+        .filter(entity::bookmark::Column::CollectionId.eq(collection.id))
+        .filter(entity::bookmark::Column::PostId.in_subquery(visible_posts));
+    if let Some(before) = page.before {
+        query = query.filter(entity::bookmark::Column::CreatedAt.lt(before));
+    }
+
+    let bookmarks = query
+        .order_by_desc(entity::bookmark::Column::CreatedAt)
+        .limit(page.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE))
+        .all(db).await
+        .map_err(lookup_error)?;
+
+    let posts = entity::post::Entity
+        ::find()
+        .filter(
+            entity::post::Column::Id.is_in(
+                bookmarks
+                    .iter()
+                    .map(|bookmark| bookmark.post_id)
+                    .collect::<Vec<_>>()
+            )
+        )
+        .all(db).await
+        .map_err(lookup_error)?;
+
+    let mut responses: HashMap<i32, _> = post_responses(db, posts, viewer_id).await
+        .map_err(lookup_error)?
+        .into_iter()
+        .map(|post| (post.id, post))
+        .collect();
+
+    Ok(
+        bookmarks
+            .into_iter()
+            .filter_map(|bookmark| {
+                responses.remove(&bookmark.post_id).map(|post| BookmarkModel {
+                    saved_at: bookmark.created_at,
+                    post,
+                })
+            })
+            .collect()
+    )
+}
+
+pub async fn list_collection_posts(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(id): Path<Uuid>,
+    Query(page): Query<BookmarkPageQuery>
+) -> Result<Json<Vec<BookmarkModel>>, APIError> {
+    let db = &state.db;
+
+    let collection = find_own_collection(db, identity.id, id).await?;
+    let bookmarks = list_bookmarks(db, &collection, identity.id, page).await?;
+
+    Ok(Json(bookmarks))
+}
+
+/// Lists a collection through its share link. Any signed-in user holding the link
+/// can read it; posts they cannot see are still filtered out.
+pub async fn list_shared_collection_posts(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(token): Path<Uuid>,
+    Query(page): Query<BookmarkPageQuery>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let collection = entity::bookmark_collection::Entity
+        ::find()
+        .filter(entity::bookmark_collection::Column::ShareToken.eq(token))
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding collection: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or_else(not_found)?;
+
+    let bookmarks = list_bookmarks(db, &collection, identity.id, page).await?;
+
+    Ok(Json(serde_json::json!({"name": collection.name, "bookmarks": bookmarks})))
+}
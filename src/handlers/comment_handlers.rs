@@ -0,0 +1,239 @@
+use axum::{ extract::{ Path, Query, State }, http::StatusCode, response::IntoResponse, Extension, Json };
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait,
+    ConnectionTrait,
+    DbErr,
+    QueryFilter,
+    QueryOrder,
+    QuerySelect,
+    TransactionTrait,
+};
+
+use crate::{
+    models::{
+        comment_models::{
+            CommentPageQuery,
+            CommentResponseModel,
+            CreateCommentModel,
+            UpdateCommentModel,
+        },
+        user_models::AppState,
+    },
+    services::{
+        comment_events::{ record_comment_event, COMMENT_CREATED, COMMENT_DELETED, COMMENT_UPDATED },
+        comments::comment_responses,
+        events::{ self, DomainEvent },
+        mentions::record_links,
+        reactions::TARGET_COMMENT,
+    },
+    utils::{ api_errors::APIError, soft_delete::SoftDelete, visibility::Visibility },
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 200;
+
+/// Looks up a post the viewer can see; posts hidden by a block are reported as
+/// missing so blocked users can neither read nor comment on them.
+pub(crate) async fn find_visible_post(
+    db: &sea_orm::DatabaseConnection,
+    viewer_id: i32,
+    id: i32
+) -> Result<entity::post::Model, APIError> {
+    entity::post::Entity
+        ::find_visible_to(viewer_id)
+        .filter(entity::post::Column::Id.eq(id))
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding post: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or(APIError {
+            message: "Post not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+            error_code: Some(2),
+        })
+}
+
+pub async fn create_comment(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(post_id): Path<i32>,
+    Json(comment_data): Json<CreateCommentModel>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let post = find_visible_post(db, identity.id, post_id).await?;
+
+    let insert_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Failed to insert comment: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let txn = db.begin().await.map_err(insert_error)?;
+    let comment = (entity::comment::ActiveModel {
+        user_id: Set(identity.id),
+        post_id: Set(post.id),
+        text: Set(comment_data.text),
+        created_at: Set(Utc::now().naive_utc()),
+        ..Default::default()
+    })
+        .insert(&txn).await
+        .map_err(insert_error)?;
+    let links = record_links(&txn, TARGET_COMMENT, comment.id, &comment.text).await.map_err(insert_error)?;
+    let response = comment_response(&txn, comment.clone(), identity.id).await.map_err(insert_error)?;
+    let event = record_comment_event(&txn, COMMENT_CREATED, &comment, &response).await.map_err(insert_error)?;
+    events::record(&txn, &DomainEvent::CommentAdded {
+        comment_id: comment.id,
+        post_id: post.id,
+        author_id: identity.id,
+        post_author_id: post.user_id,
+        mentioned: links.newly_mentioned,
+        event_id: event.id,
+    }).await.map_err(insert_error)?;
+    txn.commit().await.map_err(insert_error)?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+async fn comment_response<C: ConnectionTrait>(
+    db: &C,
+    comment: entity::comment::Model,
+    viewer_id: i32
+) -> Result<CommentResponseModel, DbErr> {
+    Ok(comment_responses(db, vec![comment], viewer_id).await?.pop().expect("one response per comment"))
+}
+
+/// Looks up one of the caller's own comments; other users' comments are reported
+/// as missing.
+async fn find_own_comment(
+    db: &sea_orm::DatabaseConnection,
+    user_id: i32,
+    id: i32
+) -> Result<entity::comment::Model, APIError> {
+    entity::comment::Entity
+        ::find_active()
+        .filter(entity::comment::Column::Id.eq(id))
+        .filter(entity::comment::Column::UserId.eq(user_id))
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding comment: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or(APIError {
+            message: "Comment not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+            error_code: Some(2),
+        })
+}
+
+pub async fn update_comment(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(id): Path<i32>,
+    Json(comment_data): Json<UpdateCommentModel>
+) -> Result<Json<CommentResponseModel>, APIError> {
+    let db = &state.db;
+
+    let comment = find_own_comment(db, identity.id, id).await?;
+
+    let update_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Failed to update comment: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let mut active_comment: entity::comment::ActiveModel = comment.into();
+    active_comment.text = Set(comment_data.text);
+    active_comment.edited_at = Set(Some(Utc::now().naive_utc()));
+
+    let txn = db.begin().await.map_err(update_error)?;
+    let comment = active_comment.update(&txn).await.map_err(update_error)?;
+    let links = record_links(&txn, TARGET_COMMENT, comment.id, &comment.text).await.map_err(update_error)?;
+    let response = comment_response(&txn, comment.clone(), identity.id).await.map_err(update_error)?;
+    let event = record_comment_event(&txn, COMMENT_UPDATED, &comment, &response).await.map_err(update_error)?;
+    events::record(&txn, &DomainEvent::CommentEdited {
+        comment_id: comment.id,
+        post_id: comment.post_id,
+        author_id: identity.id,
+        mentioned: links.newly_mentioned,
+        event_id: event.id,
+    }).await.map_err(update_error)?;
+    txn.commit().await.map_err(update_error)?;
+
+    Ok(Json(response))
+}
+
+pub async fn delete_comment(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(id): Path<i32>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let comment = find_own_comment(db, identity.id, id).await?;
+
+    let delete_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Failed to delete comment: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let mut active_comment: entity::comment::ActiveModel = comment.into();
+    active_comment.deleted_at = Set(Some(Utc::now().naive_utc()));
+
+    let txn = db.begin().await.map_err(delete_error)?;
+    let comment = active_comment.update(&txn).await.map_err(delete_error)?;
+    let payload = serde_json::json!({"id": comment.id, "post_id": comment.post_id});
+    let event = record_comment_event(&txn, COMMENT_DELETED, &comment, &payload).await.map_err(delete_error)?;
+    events::record(&txn, &DomainEvent::CommentDeleted {
+        comment_id: comment.id,
+        post_id: comment.post_id,
+        author_id: identity.id,
+        event_id: event.id,
+    }).await.map_err(delete_error)?;
+    txn.commit().await.map_err(delete_error)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Comment deleted", "id": comment.id}))))
+}
+
+pub async fn list_comments(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(post_id): Path<i32>,
+    Query(page): Query<CommentPageQuery>
+) -> Result<Json<Vec<CommentResponseModel>>, APIError> {
+    let db = &state.db;
+
+    let post = find_visible_post(db, identity.id, post_id).await?;
+
+    let mut query = entity::comment::Entity
+        ::find_visible_to(identity.id)
+        .filter(entity::comment::Column::PostId.eq(post.id));
+    if let Some(after) = page.after {
+        query = query.filter(entity::comment::Column::Id.gt(after));
+    }
+
+    let comments = query
+        .order_by_asc(entity::comment::Column::Id)
+        .limit(page.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE))
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while fetching comments: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    let responses = comment_responses(db, comments, identity.id).await.map_err(|e| APIError {
+        message: format!("Database error while loading comments: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(1),
+    })?;
+
+    Ok(Json(responses))
+}
@@ -0,0 +1,189 @@
+use axum::{ extract::{ Path, Query, State }, http::StatusCode, response::IntoResponse, Extension, Json };
+use chrono::Utc;
+use sea_orm::{ ActiveModelTrait, ActiveValue::Set, ColumnTrait, QueryFilter, TransactionTrait };
+use uuid::Uuid;
+
+use crate::{
+    handlers::follow_handlers::find_active_user,
+    models::{
+        email_models::{ PasswordResetRequestModel, ResetPasswordModel, SignedLinkQuery },
+        user_models::AppState,
+    },
+    services::{
+        account_emails::{
+            password_fingerprint,
+            password_reset_path,
+            queue_password_reset,
+            queue_verification,
+            verification_path,
+        },
+        mailer::{ suppress, unsubscribe_path, unsuppress, SUPPRESSION_UNSUBSCRIBED },
+    },
+    utils::{ api_errors::APIError, signed_url, soft_delete::SoftDelete },
+};
+
+fn invalid_link() -> APIError {
+    APIError {
+        message: "Invalid or expired link".to_string(),
+        status_code: StatusCode::FORBIDDEN,
+        error_code: Some(1),
+    }
+}
+
+/// Follows the link from the verification email.
+pub async fn verify_email(
+    State(state): State<AppState>,
+    Path(uuid): Path<Uuid>,
+    Query(query): Query<SignedLinkQuery>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    if !signed_url::verify(&verification_path(uuid), query.expires, &query.signature) {
+        return Err(invalid_link());
+    }
+    let user = find_active_user(db, uuid).await?;
+
+    if user.email_verified_at.is_none() {
+        let mut active_user: entity::user::ActiveModel = user.into();
+        active_user.email_verified_at = Set(Some(Utc::now().naive_utc()));
+        active_user.update(db).await.map_err(|e| APIError {
+            message: format!("Failed to verify email: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+    }
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Email verified", "uuid": uuid}))))
+}
+
+/// Sends the caller a fresh verification link.
+pub async fn resend_verification(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    if identity.email_verified_at.is_some() {
+        return Err(APIError {
+            message: "Email is already verified".to_string(),
+            status_code: StatusCode::CONFLICT,
+            error_code: Some(5),
+        });
+    }
+
+    let mail_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Failed to queue verification email: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+    let txn = db.begin().await.map_err(mail_error)?;
+    queue_verification(&txn, &state.mailer, &identity).await.map_err(mail_error)?;
+    txn.commit().await.map_err(mail_error)?;
+
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({"message": "Verification email sent"}))))
+}
+
+/// Emails a reset link if the address belongs to an account. The response is the
+/// same either way so it cannot be used to find out who has an account.
+pub async fn request_password_reset(
+    State(state): State<AppState>,
+    Json(request): Json<PasswordResetRequestModel>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let mail_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Failed to queue password reset email: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let user = entity::user::Entity
+        ::find_active()
+        .filter(entity::user::Column::Email.eq(request.email.trim()))
+        .one(db).await
+        .map_err(mail_error)?;
+    if let Some(user) = user {
+        let txn = db.begin().await.map_err(mail_error)?;
+        queue_password_reset(&txn, &state.mailer, &user).await.map_err(mail_error)?;
+        txn.commit().await.map_err(mail_error)?;
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({"message": "If the address has an account, a reset link is on its way"})),
+    ))
+}
+
+/// Sets a new password from a reset link. Existing sessions are revoked, which
+/// also invalidates the link.
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Path((uuid, fingerprint)): Path<(Uuid, String)>,
+    Query(query): Query<SignedLinkQuery>,
+    Json(reset): Json<ResetPasswordModel>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    if !signed_url::verify(&password_reset_path(uuid, &fingerprint), query.expires, &query.signature) {
+        return Err(invalid_link());
+    }
+    let user = find_active_user(db, uuid).await?;
+    if password_fingerprint(&user) != fingerprint {
+        return Err(invalid_link());
+    }
+    if reset.password.is_empty() {
+        return Err(APIError {
+            message: "Password must not be empty".to_string(),
+            status_code: StatusCode::BAD_REQUEST,
+            error_code: Some(6),
+        });
+    }
+
+    let mut active_user: entity::user::ActiveModel = user.into();
+    active_user.password = Set(reset.password);
+    active_user.tokens_revoked_at = Set(Some(Utc::now().naive_utc()));
+    active_user.update(db).await.map_err(|e| APIError {
+        message: format!("Failed to reset password: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    })?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Password updated", "uuid": uuid}))))
+}
+
+/// Follows the unsubscribe link in non-essential emails. Account emails such as
+/// password resets are still sent.
+pub async fn unsubscribe(
+    State(state): State<AppState>,
+    Path(uuid): Path<Uuid>,
+    Query(query): Query<SignedLinkQuery>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    if !signed_url::verify(&unsubscribe_path(uuid), query.expires, &query.signature) {
+        return Err(invalid_link());
+    }
+    let user = find_active_user(db, uuid).await?;
+
+    suppress(db, &user.email, SUPPRESSION_UNSUBSCRIBED).await.map_err(|e| APIError {
+        message: format!("Failed to unsubscribe: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    })?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Unsubscribed", "uuid": uuid}))))
+}
+
+/// Undoes an unsubscribe for the caller's address.
+pub async fn resubscribe(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>
+) -> Result<impl IntoResponse, APIError> {
+    unsuppress(&state.db, &identity.email).await.map_err(|e| APIError {
+        message: format!("Failed to resubscribe: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    })?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Resubscribed"}))))
+}
@@ -0,0 +1,141 @@
+use axum::{
+    extract::{ Path, Query, State },
+    http::{ header, StatusCode },
+    response::IntoResponse,
+    Extension,
+    Json,
+};
+use chrono::Utc;
+use sea_orm::{ ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, QueryFilter, TransactionTrait };
+use uuid::Uuid;
+
+use crate::{
+    jobs::{ data_export::{ download_path, BuildExport, STATUS_PENDING, STATUS_READY }, queue },
+    models::{
+        export_models::{ DataExportResponseModel, SignedDownloadQuery },
+        user_models::AppState,
+    },
+    utils::{ api_errors::APIError, signed_url },
+};
+
+fn to_response(export: entity::data_export::Model) -> DataExportResponseModel {
+    let download_url = match (export.status.as_str(), export.expires_at) {
+        (STATUS_READY, Some(expires_at)) =>
+            Some(signed_url::sign(&download_path(export.id), expires_at.and_utc())),
+        _ => None,
+    };
+
+    DataExportResponseModel {
+        id: export.id,
+        status: export.status,
+        created_at: export.created_at,
+        completed_at: export.completed_at,
+        expires_at: export.expires_at,
+        download_url,
+    }
+}
+
+/// Starts building an archive of everything stored about the caller.
+pub async fn request_export(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let insert_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Failed to create data export: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let txn = db.begin().await.map_err(insert_error)?;
+    let export = (entity::data_export::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(identity.id),
+        status: Set(STATUS_PENDING.to_string()),
+        created_at: Set(Utc::now().naive_utc()),
+        ..Default::default()
+    })
+        .insert(&txn).await
+        .map_err(insert_error)?;
+    queue::enqueue(&txn, &(BuildExport { export_id: export.id })).await.map_err(insert_error)?;
+    txn.commit().await.map_err(insert_error)?;
+
+    Ok((StatusCode::ACCEPTED, Json(to_response(export))))
+}
+
+pub async fn get_export(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(id): Path<Uuid>
+) -> Result<Json<DataExportResponseModel>, APIError> {
+    let db = &state.db;
+
+    let export = entity::data_export::Entity
+        ::find_by_id(id)
+        .filter(entity::data_export::Column::UserId.eq(identity.id))
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding data export: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or(APIError {
+            message: "Data export not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+            error_code: Some(2),
+        })?;
+
+    Ok(Json(to_response(export)))
+}
+
+/// Serves a finished archive. Authorized by the signed URL rather than a token so
+/// the link can be opened directly in a browser.
+pub async fn download_export(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<SignedDownloadQuery>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    if !signed_url::verify(&download_path(id), query.expires, &query.signature) {
+        return Err(APIError {
+            message: "Invalid or expired download link".to_string(),
+            status_code: StatusCode::FORBIDDEN,
+            error_code: Some(1),
+        });
+    }
+
+    let not_found = || APIError {
+        message: "Data export not found".to_string(),
+        status_code: StatusCode::NOT_FOUND,
+        error_code: Some(2),
+    };
+
+    let export = entity::data_export::Entity
+        ::find_by_id(id)
+        .filter(entity::data_export::Column::Status.eq(STATUS_READY))
+        .filter(entity::data_export::Column::ExpiresAt.gt(Utc::now().naive_utc()))
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding data export: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or_else(not_found)?;
+
+    let path = export.file_path.ok_or_else(not_found)?;
+    let bytes = tokio::fs::read(&path).await.map_err(|_| not_found())?;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"export-{}.zip\"", export.created_at.format("%Y%m%d")),
+            ),
+        ],
+        bytes,
+    ))
+}
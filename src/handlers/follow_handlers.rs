@@ -0,0 +1,315 @@
+use axum::{ extract::{ Path, Query, State }, http::StatusCode, response::IntoResponse, Extension, Json };
+use chrono::Utc;
+use sea_orm::{
+    sea_query::{ OnConflict, Query as SeaQuery },
+    ActiveValue::Set,
+    ColumnTrait,
+    Condition,
+    DatabaseConnection,
+    EntityTrait,
+    JoinType,
+    QueryFilter,
+    QueryOrder,
+    QuerySelect,
+    RelationTrait,
+    TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        follow_models::{ FollowPageQuery, FollowUserModel },
+        post_models::{ PostPageQuery, PostResponseModel },
+        user_models::AppState,
+    },
+    services::{
+        blocks::is_blocked_either_way,
+        events::{ self, DomainEvent },
+        posts::post_responses,
+    },
+    utils::{ api_errors::APIError, soft_delete::SoftDelete, visibility::Visibility },
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 20;
+const MAX_PAGE_SIZE: u64 = 100;
+
+pub(crate) async fn find_active_user(db: &DatabaseConnection, uuid: Uuid) -> Result<entity::user::Model, APIError> {
+    entity::user::Entity
+        ::find_active()
+        .filter(entity::user::Column::Uuid.eq(uuid))
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding user: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or(APIError {
+            message: "User not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+            error_code: Some(2),
+        })
+}
+
+/// Following someone already followed is a no-op. Either side of a block may
+/// not follow the other.
+pub async fn follow_user(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(uuid): Path<Uuid>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let followee = find_active_user(db, uuid).await?;
+    if followee.id == identity.id {
+        return Err(APIError {
+            message: "You cannot follow yourself".to_string(),
+            status_code: StatusCode::BAD_REQUEST,
+            error_code: Some(6),
+        });
+    }
+
+    let blocked = is_blocked_either_way(db, identity.id, followee.id).await.map_err(|e| APIError {
+        message: format!("Database error while checking blocks: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(1),
+    })?;
+    if blocked {
+        return Err(APIError {
+            message: "You cannot follow this user".to_string(),
+            status_code: StatusCode::FORBIDDEN,
+            error_code: Some(6),
+        });
+    }
+
+    let insert_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Failed to follow user: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let txn = db.begin().await.map_err(insert_error)?;
+    let inserted = entity::follow::Entity
+        ::insert(entity::follow::ActiveModel {
+            follower_id: Set(identity.id),
+            followee_id: Set(followee.id),
+            created_at: Set(Utc::now().naive_utc()),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                entity::follow::Column::FollowerId,
+                entity::follow::Column::FolloweeId,
+            ])
+                .do_nothing()
+                .to_owned()
+        )
+        .exec_without_returning(&txn).await
+        .map_err(insert_error)?;
+    if inserted > 0 {
+        events::record(&txn, &DomainEvent::UserFollowed {
+            follower_id: identity.id,
+            followee_id: followee.id,
+        }).await.map_err(insert_error)?;
+    }
+    txn.commit().await.map_err(insert_error)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "User followed", "uuid": uuid}))))
+}
+
+pub async fn unfollow_user(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(uuid): Path<Uuid>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let followee = find_active_user(db, uuid).await?;
+
+    entity::follow::Entity
+        ::delete_many()
+        .filter(entity::follow::Column::FollowerId.eq(identity.id))
+        .filter(entity::follow::Column::FolloweeId.eq(followee.id))
+        .exec(db).await
+        .map_err(|e| APIError {
+            message: format!("Failed to unfollow user: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "User unfollowed", "uuid": uuid}))))
+}
+
+/// Lists one side of `user`'s follow edges: `join_on` is the relation from the
+/// follow row to the listed users, `filter_column` the column holding `user`.
+/// Keyset-paginated on (follow time, listed user's uuid), since follow times
+/// are not unique and the uuid is within one list.
+async fn list_follow_edges(
+    db: &DatabaseConnection,
+    user: &entity::user::Model,
+    join_on: entity::follow::Relation,
+    filter_column: entity::follow::Column,
+    page: FollowPageQuery
+) -> Result<Vec<FollowUserModel>, APIError> {
+    let mut query = entity::user::Entity
+        ::find_active()
+        .select_only()
+        .column(entity::user::Column::Uuid)
+        .column(entity::user::Column::Name)
+        .column(entity::follow::Column::CreatedAt)
+        .join_rev(JoinType::InnerJoin, join_on.def())
+        .filter(filter_column.eq(user.id));
+    query = match (page.before, page.before_uuid) {
+        (Some(before), Some(before_uuid)) =>
+            query.filter(
+                Condition::any()
+                    .add(entity::follow::Column::CreatedAt.lt(before))
+                    .add(
+                        Condition::all()
+                            .add(entity::follow::Column::CreatedAt.eq(before))
+                            .add(entity::user::Column::Uuid.lt(before_uuid))
+                    )
+            ),
+        (Some(before), None) => query.filter(entity::follow::Column::CreatedAt.lt(before)),
+        (None, _) => query,
+    };
+
+    let rows: Vec<(Uuid, String, chrono::NaiveDateTime)> = query
+        .order_by_desc(entity::follow::Column::CreatedAt)
+        .order_by_desc(entity::user::Column::Uuid)
+        .limit(page.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE))
+        .into_tuple()
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while fetching follows: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    Ok(
+        rows
+            .into_iter()
+            .map(|(uuid, name, followed_at)| FollowUserModel { uuid, name, followed_at })
+            .collect()
+    )
+}
+
+pub async fn list_followers(
+    State(state): State<AppState>,
+    Path(uuid): Path<Uuid>,
+    Query(page): Query<FollowPageQuery>
+) -> Result<Json<Vec<FollowUserModel>>, APIError> {
+    let db = &state.db;
+
+    let user = find_active_user(db, uuid).await?;
+    let followers = list_follow_edges(
+        db,
+        &user,
+        entity::follow::Relation::Follower,
+        entity::follow::Column::FolloweeId,
+        page
+    ).await?;
+
+    Ok(Json(followers))
+}
+
+pub async fn list_following(
+    State(state): State<AppState>,
+    Path(uuid): Path<Uuid>,
+    Query(page): Query<FollowPageQuery>
+) -> Result<Json<Vec<FollowUserModel>>, APIError> {
+    let db = &state.db;
+
+    let user = find_active_user(db, uuid).await?;
+    let following = list_follow_edges(
+        db,
+        &user,
+        entity::follow::Relation::Followee,
+        entity::follow::Column::FollowerId,
+        page
+    ).await?;
+
+    Ok(Json(following))
+}
+
+/// Posts by the accounts the caller follows, newest first. Keyset-paginated on
+/// the post id, which increases with creation time.
+pub async fn get_feed(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Query(page): Query<PostPageQuery>
+) -> Result<Json<Vec<PostResponseModel>>, APIError> {
+    let db = &state.db;
+
+    let followees = SeaQuery::select()
+        .column(entity::follow::Column::FolloweeId)
+        .from(entity::follow::Entity)
+        .and_where(entity::follow::Column::FollowerId.eq(identity.id))
+        .to_owned();
+
+    let mut query = entity::post::Entity
+        ::find_visible_to(identity.id)
+        .filter(entity::post::Column::UserId.in_subquery(followees));
+    if let Some(before) = page.before {
+        query = query.filter(entity::post::Column::Id.lt(before));
+    }
+
+    let posts = query
+        .order_by_desc(entity::post::Column::Id)
+        .limit(page.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE))
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while fetching feed: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    let responses = post_responses(db, posts, identity.id).await.map_err(|e| APIError {
+        message: format!("Database error while loading feed: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(1),
+    })?;
+
+    Ok(Json(responses))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use sea_orm::{ ActiveValue::Set, EntityTrait };
+
+    use super::list_follow_edges;
+    use crate::{ models::follow_models::{ FollowPageQuery, FollowUserModel }, testing };
+
+    #[tokio::test]
+    async fn paging_followers_followed_at_the_same_instant_skips_and_repeats_nobody() {
+        let Some(db) = testing::database().await else { return };
+        let followee = testing::create_user(&db).await;
+        let followed_at = Utc::now().naive_utc();
+        for _ in 0..3 {
+            let follower = testing::create_user(&db).await;
+            entity::follow::Entity
+                ::insert(entity::follow::ActiveModel {
+                    follower_id: Set(follower.id),
+                    followee_id: Set(followee.id),
+                    created_at: Set(followed_at),
+                })
+                .exec(&db).await
+                .unwrap();
+        }
+
+        let list = |before: Option<&FollowUserModel>| {
+            let page = FollowPageQuery {
+                before: before.map(|last| last.followed_at),
+                before_uuid: before.map(|last| last.uuid),
+                limit: Some(2),
+            };
+            list_follow_edges(&db, &followee, entity::follow::Relation::Follower, entity::follow::Column::FolloweeId, page)
+        };
+        let first = list(None).await.unwrap();
+        let second = list(first.last()).await.unwrap();
+
+        let mut seen: Vec<_> = first.iter().chain(&second).map(|follower| follower.uuid).collect();
+        seen.sort();
+        seen.dedup();
+        assert_eq!((first.len(), second.len(), seen.len()), (2, 1, 3));
+    }
+}
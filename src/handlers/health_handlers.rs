@@ -0,0 +1,96 @@
+use std::{ future::Future, time::{ Duration, Instant } };
+
+use axum::{ extract::State, http::StatusCode, response::IntoResponse, Json };
+use migration::{ Migrator, MigratorTrait };
+use sea_orm::{ ConnectionTrait, DatabaseConnection };
+
+use crate::{ models::user_models::AppState, services::health::Health };
+
+/// A dependency slower than this counts as down, so a probe never hangs on an
+/// exhausted pool.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Liveness: the process is up and serving requests.
+pub async fn healthz() -> impl IntoResponse {
+    (StatusCode::OK, Json(serde_json::json!({"status": "ok"})))
+}
+
+/// Readiness: the database answers, the schema is fully migrated and every
+/// background worker is running. Fails as soon as shutdown begins.
+pub async fn readyz(State(state): State<AppState>) -> impl IntoResponse {
+    if state.health.is_shutting_down() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"status": "unavailable", "shutting_down": true, "checks": {}})),
+        );
+    }
+
+    let (database, migrations) = tokio::join!(check_database(&state.db), check_migrations(&state.db));
+    let workers = check_workers(&state.health);
+
+    let ready = [&database, &migrations, &workers].iter().all(|check| check["status"] == "ok");
+    let status_code = match ready {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    (
+        status_code,
+        Json(
+            serde_json::json!({
+            "status": if ready { "ok" } else { "unavailable" },
+            "shutting_down": false,
+            "checks": {
+                "database": database,
+                "migrations": migrations,
+                "workers": workers,
+            },
+        })
+        ),
+    )
+}
+
+async fn check_database(db: &DatabaseConnection) -> serde_json::Value {
+    let (outcome, latency_ms) = timed(async {
+        db.execute_unprepared("SELECT 1").await.map_err(|e| e.to_string())
+    }).await;
+    match outcome {
+        Ok(_) => serde_json::json!({"status": "ok", "latency_ms": latency_ms}),
+        Err(error) => serde_json::json!({"status": "failing", "latency_ms": latency_ms, "error": error}),
+    }
+}
+
+/// An instance running against an older schema than it was built for is not
+/// ready, e.g. while a deploy is waiting on `migration up`.
+async fn check_migrations(db: &DatabaseConnection) -> serde_json::Value {
+    let (outcome, latency_ms) = timed(async {
+        Migrator::get_pending_migrations(db).await.map_err(|e| e.to_string())
+    }).await;
+    match outcome {
+        Ok(pending) if pending.is_empty() => serde_json::json!({"status": "ok", "latency_ms": latency_ms, "pending": []}),
+        Ok(pending) => {
+            let pending: Vec<&str> = pending.iter().map(|migration| migration.name()).collect();
+            serde_json::json!({"status": "failing", "latency_ms": latency_ms, "pending": pending})
+        }
+        Err(error) => serde_json::json!({"status": "failing", "latency_ms": latency_ms, "error": error}),
+    }
+}
+
+fn check_workers(health: &Health) -> serde_json::Value {
+    let (running, stopped) = health.workers();
+    serde_json::json!({
+        "status": if stopped.is_empty() { "ok" } else { "failing" },
+        "running": running,
+        "stopped": stopped,
+    })
+}
+
+/// Runs `check` under `CHECK_TIMEOUT`, returning its outcome and how long it took
+/// in milliseconds.
+async fn timed<T>(check: impl Future<Output = Result<T, String>>) -> (Result<T, String>, f64) {
+    let started = Instant::now();
+    let outcome = tokio::time
+        ::timeout(CHECK_TIMEOUT, check).await
+        .unwrap_or_else(|_| Err(format!("Timed out after {}s", CHECK_TIMEOUT.as_secs())));
+    let latency_ms = (started.elapsed().as_secs_f64() * 1_000_000.0).round() / 1000.0;
+    (outcome, latency_ms)
+}
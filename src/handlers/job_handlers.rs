@@ -0,0 +1,136 @@
+use axum::{ extract::{ Path, Query, State }, http::StatusCode, Json };
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait,
+    DatabaseConnection,
+    EntityTrait,
+    QueryFilter,
+    QueryOrder,
+    QuerySelect,
+};
+
+use crate::{
+    jobs::queue::{ STATUS_FAILED, STATUS_QUEUED },
+    models::{ job_models::{ JobModel, JobPageQuery, QueueStatusCountModel }, user_models::AppState },
+    utils::api_errors::APIError,
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 200;
+
+async fn find_job(db: &DatabaseConnection, id: i64) -> Result<entity::job::Model, APIError> {
+    entity::job::Entity
+        ::find_by_id(id)
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding job: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or(APIError {
+            message: "Job not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+            error_code: Some(2),
+        })
+}
+
+pub async fn list_jobs(
+    State(state): State<AppState>,
+    Query(page): Query<JobPageQuery>
+) -> Result<Json<Vec<JobModel>>, APIError> {
+    let db = &state.db;
+
+    let mut query = entity::job::Entity::find();
+    if let Some(before) = page.before {
+        query = query.filter(entity::job::Column::Id.lt(before));
+    }
+    if let Some(status) = page.status {
+        query = query.filter(entity::job::Column::Status.eq(status));
+    }
+    if let Some(queue) = page.queue {
+        query = query.filter(entity::job::Column::Queue.eq(queue));
+    }
+    if let Some(kind) = page.kind {
+        query = query.filter(entity::job::Column::Kind.eq(kind));
+    }
+
+    let jobs = query
+        .order_by_desc(entity::job::Column::Id)
+        .limit(page.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE))
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while fetching jobs: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    Ok(Json(jobs.into_iter().map(JobModel::from).collect()))
+}
+
+/// How many jobs each queue holds in each status.
+pub async fn job_summary(State(state): State<AppState>) -> Result<Json<Vec<QueueStatusCountModel>>, APIError> {
+    let db = &state.db;
+
+    let rows: Vec<(String, String, i64)> = entity::job::Entity
+        ::find()
+        .select_only()
+        .column(entity::job::Column::Queue)
+        .column(entity::job::Column::Status)
+        .column_as(entity::job::Column::Id.count(), "count")
+        .group_by(entity::job::Column::Queue)
+        .group_by(entity::job::Column::Status)
+        .order_by_asc(entity::job::Column::Queue)
+        .order_by_asc(entity::job::Column::Status)
+        .into_tuple()
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while counting jobs: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    Ok(
+        Json(
+            rows
+                .into_iter()
+                .map(|(queue, status, count)| QueueStatusCountModel { queue, status, count })
+                .collect()
+        )
+    )
+}
+
+pub async fn get_job(State(state): State<AppState>, Path(id): Path<i64>) -> Result<Json<JobModel>, APIError> {
+    let job = find_job(&state.db, id).await?;
+
+    Ok(Json(JobModel::from(job)))
+}
+
+/// Queues a failed job again with a fresh set of attempts. The last error is
+/// kept until the job next runs.
+pub async fn retry_job(State(state): State<AppState>, Path(id): Path<i64>) -> Result<Json<JobModel>, APIError> {
+    let db = &state.db;
+
+    let job = find_job(db, id).await?;
+    if job.status != STATUS_FAILED {
+        return Err(APIError {
+            message: format!("Only failed jobs can be retried; this one is {}", job.status),
+            status_code: StatusCode::CONFLICT,
+            error_code: Some(5),
+        });
+    }
+
+    let mut active_job: entity::job::ActiveModel = job.into();
+    active_job.status = Set(STATUS_QUEUED.to_string());
+    active_job.attempts = Set(0);
+    active_job.run_at = Set(Utc::now().naive_utc());
+    active_job.finished_at = Set(None);
+    let job = active_job.update(db).await.map_err(|e| APIError {
+        message: format!("Failed to retry job: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    })?;
+
+    Ok(Json(JobModel::from(job)))
+}
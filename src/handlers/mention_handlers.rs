@@ -0,0 +1,103 @@
+use axum::{ extract::{ Path, Query, State }, http::StatusCode, Extension, Json };
+use sea_orm::{ ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect };
+use uuid::Uuid;
+
+use crate::{
+    handlers::follow_handlers::find_active_user,
+    models::{ mention_models::{ LinkPageQuery, LinkedContentModel }, user_models::AppState },
+    services::mentions::{ linked_content, visible_targets },
+    utils::api_errors::APIError,
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 20;
+const MAX_PAGE_SIZE: u64 = 100;
+
+/// Posts and comments mentioning the user, newest first.
+pub async fn list_mentions(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(uuid): Path<Uuid>,
+    Query(page): Query<LinkPageQuery>
+) -> Result<Json<Vec<LinkedContentModel>>, APIError> {
+    use entity::mention;
+
+    let db = &state.db;
+
+    let user = find_active_user(db, uuid).await?;
+
+    let mut query = mention::Entity
+        ::find()
+        .select_only()
+        .column(mention::Column::TargetType)
+        .column(mention::Column::TargetId)
+        .column(mention::Column::CreatedAt)
+        .filter(mention::Column::UserId.eq(user.id))
+        .filter(visible_targets(mention::Column::TargetType, mention::Column::TargetId, identity.id));
+    if let Some(before) = page.before {
+        query = query.filter(mention::Column::CreatedAt.lt(before));
+    }
+
+    let rows = query
+        .order_by_desc(mention::Column::CreatedAt)
+        .limit(page.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE))
+        .into_tuple()
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while fetching mentions: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    let mentions = linked_content(db, rows, identity.id).await.map_err(|e| APIError {
+        message: format!("Database error while loading mentions: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(1),
+    })?;
+
+    Ok(Json(mentions))
+}
+
+/// Posts and comments carrying the hashtag, newest first. The tag is matched
+/// case-insensitively and may be given with or without its leading `#`.
+pub async fn list_hashtag(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(tag): Path<String>,
+    Query(page): Query<LinkPageQuery>
+) -> Result<Json<Vec<LinkedContentModel>>, APIError> {
+    use entity::hashtag;
+
+    let db = &state.db;
+    let tag = tag.trim_start_matches('#').to_ascii_lowercase();
+
+    let mut query = hashtag::Entity
+        ::find()
+        .select_only()
+        .column(hashtag::Column::TargetType)
+        .column(hashtag::Column::TargetId)
+        .column(hashtag::Column::CreatedAt)
+        .filter(hashtag::Column::Tag.eq(tag))
+        .filter(visible_targets(hashtag::Column::TargetType, hashtag::Column::TargetId, identity.id));
+    if let Some(before) = page.before {
+        query = query.filter(hashtag::Column::CreatedAt.lt(before));
+    }
+
+    let rows = query
+        .order_by_desc(hashtag::Column::CreatedAt)
+        .limit(page.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE))
+        .into_tuple()
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while fetching hashtag: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    let tagged = linked_content(db, rows, identity.id).await.map_err(|e| APIError {
+        message: format!("Database error while loading hashtag: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(1),
+    })?;
+
+    Ok(Json(tagged))
+}
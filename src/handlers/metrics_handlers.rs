@@ -0,0 +1,38 @@
+use axum::{ extract::State, http::{ header, StatusCode }, response::IntoResponse };
+use sea_orm::{ ColumnTrait, EntityTrait, QueryFilter, QuerySelect };
+use tracing::error;
+
+use crate::{
+    jobs::queue::{ STATUS_QUEUED, STATUS_RUNNING },
+    models::user_models::AppState,
+    telemetry::metrics,
+};
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Prometheus scrape endpoint. Pool and queue gauges are sampled here, so they
+/// are as fresh as the scrape.
+pub async fn get_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    metrics::record_pool(&state.db);
+
+    let depths = entity::job::Entity
+        ::find()
+        .select_only()
+        .column(entity::job::Column::Queue)
+        .column(entity::job::Column::Status)
+        .column_as(entity::job::Column::Id.count(), "count")
+        .filter(entity::job::Column::Status.is_in([STATUS_QUEUED, STATUS_RUNNING]))
+        .group_by(entity::job::Column::Queue)
+        .group_by(entity::job::Column::Status)
+        .into_tuple::<(String, String, i64)>()
+        .all(&state.db).await;
+    match depths {
+        Ok(depths) => metrics::record_job_queue_depth(depths),
+        Err(err) => error!("Failed to sample job queue depth: {:?}", err),
+    }
+
+    match metrics::render() {
+        Some(body) => (StatusCode::OK, [(header::CONTENT_TYPE, CONTENT_TYPE)], body),
+        None => (StatusCode::NOT_FOUND, [(header::CONTENT_TYPE, CONTENT_TYPE)], String::new()),
+    }
+}
@@ -1,3 +1,20 @@
 pub mod auth_handlers;
 pub mod user_handlers;
 pub mod post_handlers;
+pub mod admin_handlers;
+pub mod export_handlers;
+pub mod upload_handlers;
+pub mod comment_handlers;
+pub mod reaction_handlers;
+pub mod follow_handlers;
+pub mod block_handlers;
+pub mod bookmark_handlers;
+pub mod mention_handlers;
+pub mod notification_handlers;
+pub mod ws_handlers;
+pub mod sse_handlers;
+pub mod webhook_handlers;
+pub mod job_handlers;
+pub mod email_handlers;
+pub mod health_handlers;
+pub mod metrics_handlers;
@@ -0,0 +1,220 @@
+use std::collections::{ BTreeMap, HashMap };
+
+use axum::{ extract::{ Path, Query, State }, http::StatusCode, response::IntoResponse, Extension, Json };
+use chrono::Utc;
+use sea_orm::{
+    sea_query::{ Expr, OnConflict },
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait,
+    DatabaseConnection,
+    EntityTrait,
+    PaginatorTrait,
+    QueryFilter,
+    QueryOrder,
+    QuerySelect,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::{ notification_models::{ NotificationModel, NotificationPageQuery }, user_models::AppState },
+    services::notifier::{ is_enabled, NotificationKind },
+    utils::api_errors::APIError,
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 20;
+const MAX_PAGE_SIZE: u64 = 100;
+
+pub async fn list_notifications(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Query(page): Query<NotificationPageQuery>
+) -> Result<Json<Vec<NotificationModel>>, APIError> {
+    let db = &state.db;
+
+    let mut query = entity::notification::Entity
+        ::find()
+        .filter(entity::notification::Column::UserId.eq(identity.id));
+    if page.unread {
+        query = query.filter(entity::notification::Column::ReadAt.is_null());
+    }
+    if let Some(before) = page.before {
+        query = query.filter(entity::notification::Column::Id.lt(before));
+    }
+
+    let notifications = query
+        .order_by_desc(entity::notification::Column::Id)
+        .limit(page.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE))
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while fetching notifications: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    let actor_ids: Vec<i32> = notifications
+        .iter()
+        .filter_map(|notification| notification.actor_id)
+        .collect();
+    let actors: HashMap<i32, Uuid> = entity::user::Entity
+        ::find()
+        .select_only()
+        .column(entity::user::Column::Id)
+        .column(entity::user::Column::Uuid)
+        .filter(entity::user::Column::Id.is_in(actor_ids))
+        .into_tuple()
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while loading notifications: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .into_iter()
+        .collect();
+
+    Ok(
+        Json(
+            notifications
+                .into_iter()
+                .map(|notification| {
+                    let actor_uuid = notification.actor_id.and_then(|id| actors.get(&id).copied());
+                    NotificationModel::new(notification, actor_uuid)
+                })
+                .collect()
+        )
+    )
+}
+
+pub async fn unread_count(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>
+) -> Result<impl IntoResponse, APIError> {
+    let unread = entity::notification::Entity
+        ::find()
+        .filter(entity::notification::Column::UserId.eq(identity.id))
+        .filter(entity::notification::Column::ReadAt.is_null())
+        .count(&state.db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while counting notifications: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    Ok(Json(serde_json::json!({"unread": unread})))
+}
+
+/// Marking an already read notification keeps its original `read_at`.
+pub async fn mark_read(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(id): Path<i32>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let notification = entity::notification::Entity
+        ::find_by_id(id)
+        .filter(entity::notification::Column::UserId.eq(identity.id))
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding notification: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or(APIError {
+            message: "Notification not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+            error_code: Some(2),
+        })?;
+
+    if notification.read_at.is_none() {
+        let mut active_notification: entity::notification::ActiveModel = notification.into();
+        active_notification.read_at = Set(Some(Utc::now().naive_utc()));
+        active_notification.update(db).await.map_err(|e| APIError {
+            message: format!("Failed to mark notification read: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+    }
+
+    Ok(Json(serde_json::json!({"message": "Notification marked read", "id": id})))
+}
+
+pub async fn mark_all_read(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>
+) -> Result<impl IntoResponse, APIError> {
+    let result = entity::notification::Entity
+        ::update_many()
+        .col_expr(entity::notification::Column::ReadAt, Expr::value(Utc::now().naive_utc()))
+        .filter(entity::notification::Column::UserId.eq(identity.id))
+        .filter(entity::notification::Column::ReadAt.is_null())
+        .exec(&state.db).await
+        .map_err(|e| APIError {
+            message: format!("Failed to mark notifications read: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+
+    Ok(Json(serde_json::json!({"message": "Notifications marked read", "updated": result.rows_affected})))
+}
+
+async fn preferences(db: &DatabaseConnection, user_id: i32) -> Result<BTreeMap<&'static str, bool>, APIError> {
+    let mut preferences = BTreeMap::new();
+    for kind in NotificationKind::ALL {
+        let enabled = is_enabled(db, user_id, kind).await.map_err(|e| APIError {
+            message: format!("Database error while loading preferences: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+        preferences.insert(kind.as_str(), enabled);
+    }
+    Ok(preferences)
+}
+
+/// Which notification kinds are recorded for the caller, keyed by kind.
+pub async fn get_preferences(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>
+) -> Result<impl IntoResponse, APIError> {
+    Ok(Json(preferences(&state.db, identity.id).await?))
+}
+
+/// Accepts a partial map of kind to enabled; kinds left out keep their setting.
+pub async fn update_preferences(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Json(changes): Json<BTreeMap<String, bool>>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    for (kind, enabled) in changes {
+        let kind = NotificationKind::parse(&kind).ok_or(APIError {
+            message: format!("Unknown notification kind: {}", kind),
+            status_code: StatusCode::BAD_REQUEST,
+            error_code: Some(6),
+        })?;
+
+        entity::notification_preference::Entity
+            ::insert(entity::notification_preference::ActiveModel {
+                user_id: Set(identity.id),
+                kind: Set(kind.as_str().to_string()),
+                enabled: Set(enabled),
+            })
+            .on_conflict(
+                OnConflict::columns([
+                    entity::notification_preference::Column::UserId,
+                    entity::notification_preference::Column::Kind,
+                ])
+                    .update_column(entity::notification_preference::Column::Enabled)
+                    .to_owned()
+            )
+            .exec_without_returning(db).await
+            .map_err(|e| APIError {
+                message: format!("Failed to update preferences: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                error_code: Some(3),
+            })?;
+    }
+
+    Ok(Json(preferences(db, identity.id).await?))
+}
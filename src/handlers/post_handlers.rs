@@ -1,35 +1,234 @@
-use axum::{ extract::State, Extension, Json };
+use axum::{ extract::{ Path, Query, State }, Extension, Json };
 use chrono::Utc;
 use hyper::StatusCode;
-use sea_orm::{ ActiveValue::Set, EntityTrait };
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait,
+    EntityTrait,
+    QueryFilter,
+    QueryOrder,
+    QuerySelect,
+    TransactionTrait,
+};
 
 use crate::{
-    models::{ post_models::CreatePostModel, user_models::AppState },
-    utils::api_errors::APIError,
+    models::{
+        post_models::{ CreatePostModel, PostFormat, PostPageQuery, PostResponseModel, UpdatePostModel },
+        upload_models::upload_url,
+        user_models::AppState,
+    },
+    services::{
+        events::{ self, DomainEvent },
+        mentions::{ mention_maps, record_links },
+        posts::post_responses,
+        reactions::TARGET_POST,
+    },
+    utils::{ api_errors::APIError, markdown, soft_delete::SoftDelete, visibility::Visibility },
 };
 
+const DEFAULT_PAGE_SIZE: u64 = 20;
+const MAX_PAGE_SIZE: u64 = 100;
+
 pub async fn create_post(
     State(state): State<AppState>,
     Extension(identity): Extension<entity::user::Model>,
     Json(post_data): Json<CreatePostModel>
 ) -> Result<(), APIError> {
     let db = &state.db;
+
+    // Posts may only reference images the author uploaded themselves
+    if let Some(upload_id) = post_data.upload_id {
+        entity::upload::Entity
+            ::find_by_id(upload_id)
+            .filter(entity::upload::Column::UserId.eq(identity.id))
+            .one(db).await
+            .map_err(|e| APIError {
+                message: format!("Database error while finding upload: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                error_code: Some(1),
+            })?
+            .ok_or(APIError {
+                message: "Upload not found".to_string(),
+                status_code: StatusCode::BAD_REQUEST,
+                error_code: Some(2),
+            })?;
+    }
+
+    let insert_error = |_| APIError {
+        message: "Failed to insert Post".to_string(),
+        error_code: Some(1),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+    };
+
     let post_entity = entity::post::ActiveModel {
         title: Set(post_data.title),
+        format: Set(post_data.format.as_str().to_string()),
         text: Set(post_data.text),
-        image: Set(post_data.image),
+        image: Set(post_data.upload_id.map(upload_url).unwrap_or_default()),
+        upload_id: Set(post_data.upload_id),
         created_at: Set(Utc::now().naive_local().to_string()),
         user_id: Set(identity.id),
         ..Default::default()
     };
 
-    entity::post::Entity
-        ::insert(post_entity)
-        .exec(db).await
+    let txn = db.begin().await.map_err(insert_error)?;
+    let post = post_entity.insert(&txn).await.map_err(insert_error)?;
+
+    // Rendering needs the mentions resolved, which needs the post's id
+    let links = record_links(&txn, TARGET_POST, post.id, &post.text).await.map_err(insert_error)?;
+    let html = markdown::render(&post.text, post_data.format, &links.handles);
+    let mut active_post: entity::post::ActiveModel = post.into();
+    active_post.rendered_html = Set(Some(html));
+    let post = active_post.update(&txn).await.map_err(insert_error)?;
+    events::record(&txn, &DomainEvent::PostPublished {
+        post_id: post.id,
+        author_id: identity.id,
+        mentioned: links.newly_mentioned,
+    }).await.map_err(insert_error)?;
+
+    txn.commit().await.map_err(insert_error)?;
+
+    Ok(())
+}
+
+pub async fn get_post(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(id): Path<i32>
+) -> Result<Json<PostResponseModel>, APIError> {
+    let db = &state.db;
+
+    let post = entity::post::Entity
+        ::find_visible_to(identity.id)
+        .filter(entity::post::Column::Id.eq(id))
+        .one(db).await
         .map_err(|e| APIError {
-            message: "Failed to insert Post".to_string(),
+            message: format!("Database error while finding post: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
             error_code: Some(1),
+        })?
+        .ok_or(APIError {
+            message: "Post not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+            error_code: Some(2),
+        })?;
+
+    let response = post_responses(db, vec![post], identity.id).await
+        .map_err(|e| APIError {
+            message: format!("Database error while loading post: {}", e),
             status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .pop()
+        .expect("one response per post");
+
+    Ok(Json(response))
+}
+
+/// Lists posts newest first.
+pub async fn list_posts(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Query(page): Query<PostPageQuery>
+) -> Result<Json<Vec<PostResponseModel>>, APIError> {
+    let db = &state.db;
+
+    let mut query = entity::post::Entity::find_visible_to(identity.id);
+    if let Some(before) = page.before {
+        query = query.filter(entity::post::Column::Id.lt(before));
+    }
+
+    let posts = query
+        .order_by_desc(entity::post::Column::Id)
+        .limit(page.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE))
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while fetching posts: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
         })?;
-    Ok(())
+
+    let responses = post_responses(db, posts, identity.id).await.map_err(|e| APIError {
+        message: format!("Database error while loading posts: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(1),
+    })?;
+
+    Ok(Json(responses))
+}
+
+/// Updates the caller's own post, rendering its HTML again from the new text and
+/// format.
+pub async fn update_post(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path(id): Path<i32>,
+    Json(post_data): Json<UpdatePostModel>
+) -> Result<Json<PostResponseModel>, APIError> {
+    let db = &state.db;
+
+    let post = entity::post::Entity
+        ::find_active()
+        .filter(entity::post::Column::Id.eq(id))
+        .filter(entity::post::Column::UserId.eq(identity.id))
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding post: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or(APIError {
+            message: "Post not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+            error_code: Some(2),
+        })?;
+
+    let update_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Failed to update post: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let text = post_data.text.clone().unwrap_or_else(|| post.text.clone());
+    let format = post_data.format.unwrap_or_else(|| PostFormat::from_column(&post.format));
+
+    let txn = db.begin().await.map_err(update_error)?;
+    let (mentions, newly_mentioned) = match post_data.text {
+        Some(_) => {
+            let links = record_links(&txn, TARGET_POST, post.id, &text).await.map_err(update_error)?;
+            (links.handles, links.newly_mentioned)
+        }
+        None => {
+            let mut mentions = mention_maps(&txn, TARGET_POST, vec![post.id]).await.map_err(update_error)?;
+            (mentions.remove(&post.id).unwrap_or_default(), Vec::new())
+        }
+    };
+
+    let mut active_post: entity::post::ActiveModel = post.into();
+    if let Some(title) = post_data.title {
+        active_post.title = Set(title);
+    }
+    active_post.rendered_html = Set(Some(markdown::render(&text, format, &mentions)));
+    active_post.text = Set(text);
+    active_post.format = Set(format.as_str().to_string());
+
+    let post = active_post.update(&txn).await.map_err(update_error)?;
+    events::record(&txn, &DomainEvent::PostUpdated {
+        post_id: post.id,
+        author_id: identity.id,
+        mentioned: newly_mentioned,
+    }).await.map_err(update_error)?;
+    txn.commit().await.map_err(update_error)?;
+
+    let response = post_responses(db, vec![post], identity.id).await
+        .map_err(|e| APIError {
+            message: format!("Database error while loading post: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .pop()
+        .expect("one response per post");
+
+    Ok(Json(response))
 }
@@ -0,0 +1,186 @@
+use axum::{ extract::{ Path, State }, http::StatusCode, response::IntoResponse, Extension, Json };
+use chrono::Utc;
+use sea_orm::{
+    sea_query::OnConflict,
+    ActiveValue::Set,
+    ColumnTrait,
+    DatabaseConnection,
+    EntityTrait,
+    QueryFilter,
+    QuerySelect,
+    TransactionTrait,
+};
+
+use crate::{
+    models::{ reaction_models::REACTION_KINDS, user_models::AppState },
+    services::{
+        events::{ self, DomainEvent },
+        reactions::{ TARGET_COMMENT, TARGET_POST },
+    },
+    utils::{ api_errors::APIError, visibility::Visibility },
+};
+
+fn validate_kind(kind: &str) -> Result<(), APIError> {
+    if !REACTION_KINDS.contains(&kind) {
+        return Err(APIError {
+            message: format!("Unknown reaction kind: {}", kind),
+            status_code: StatusCode::BAD_REQUEST,
+            error_code: Some(6),
+        });
+    }
+    Ok(())
+}
+
+/// Returns the author of a target the viewer can see. Targets hidden from the
+/// viewer, including content by users who blocked them, are reported as missing.
+async fn find_visible_target_author(
+    db: &DatabaseConnection,
+    viewer_id: i32,
+    target_type: &str,
+    id: i32
+) -> Result<i32, APIError> {
+    let author: Result<Option<i32>, _> = match target_type {
+        TARGET_POST =>
+            entity::post::Entity
+                ::find_visible_to(viewer_id)
+                .select_only()
+                .column(entity::post::Column::UserId)
+                .filter(entity::post::Column::Id.eq(id))
+                .into_tuple()
+                .one(db).await,
+        _ =>
+            entity::comment::Entity
+                ::find_visible_to(viewer_id)
+                .select_only()
+                .column(entity::comment::Column::UserId)
+                .filter(entity::comment::Column::Id.eq(id))
+                .into_tuple()
+                .one(db).await,
+    };
+
+    author
+        .map_err(|e| APIError {
+            message: format!("Database error while finding {}: {}", target_type, e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or(APIError {
+            message: format!("{} not found", target_type),
+            status_code: StatusCode::NOT_FOUND,
+            error_code: Some(2),
+        })
+}
+
+/// Adding a reaction that already exists is a no-op.
+async fn add_reaction(
+    state: AppState,
+    identity: entity::user::Model,
+    target_type: &'static str,
+    target_id: i32,
+    kind: String
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    validate_kind(&kind)?;
+    let author_id = find_visible_target_author(db, identity.id, target_type, target_id).await?;
+
+    let insert_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Failed to add reaction: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let txn = db.begin().await.map_err(insert_error)?;
+    let inserted = entity::reaction::Entity
+        ::insert(entity::reaction::ActiveModel {
+            user_id: Set(identity.id),
+            target_type: Set(target_type.to_string()),
+            target_id: Set(target_id),
+            kind: Set(kind.clone()),
+            created_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([
+                entity::reaction::Column::UserId,
+                entity::reaction::Column::TargetType,
+                entity::reaction::Column::TargetId,
+                entity::reaction::Column::Kind,
+            ])
+                .do_nothing()
+                .to_owned()
+        )
+        .exec_without_returning(&txn).await
+        .map_err(insert_error)?;
+    if inserted > 0 {
+        events::record(&txn, &DomainEvent::ReactionAdded {
+            user_id: identity.id,
+            target_type: target_type.to_string(),
+            target_id,
+            target_author_id: author_id,
+        }).await.map_err(insert_error)?;
+    }
+    txn.commit().await.map_err(insert_error)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Reaction added", "kind": kind}))))
+}
+
+/// Removing a reaction that does not exist is a no-op.
+async fn remove_reaction(
+    state: AppState,
+    identity: entity::user::Model,
+    target_type: &str,
+    target_id: i32,
+    kind: String
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    validate_kind(&kind)?;
+
+    entity::reaction::Entity
+        ::delete_many()
+        .filter(entity::reaction::Column::UserId.eq(identity.id))
+        .filter(entity::reaction::Column::TargetType.eq(target_type))
+        .filter(entity::reaction::Column::TargetId.eq(target_id))
+        .filter(entity::reaction::Column::Kind.eq(&kind))
+        .exec(db).await
+        .map_err(|e| APIError {
+            message: format!("Failed to remove reaction: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Reaction removed", "kind": kind}))))
+}
+
+pub async fn add_post_reaction(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path((id, kind)): Path<(i32, String)>
+) -> Result<impl IntoResponse, APIError> {
+    add_reaction(state, identity, TARGET_POST, id, kind).await
+}
+
+pub async fn remove_post_reaction(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path((id, kind)): Path<(i32, String)>
+) -> Result<impl IntoResponse, APIError> {
+    remove_reaction(state, identity, TARGET_POST, id, kind).await
+}
+
+pub async fn add_comment_reaction(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path((id, kind)): Path<(i32, String)>
+) -> Result<impl IntoResponse, APIError> {
+    add_reaction(state, identity, TARGET_COMMENT, id, kind).await
+}
+
+pub async fn remove_comment_reaction(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Path((id, kind)): Path<(i32, String)>
+) -> Result<impl IntoResponse, APIError> {
+    remove_reaction(state, identity, TARGET_COMMENT, id, kind).await
+}
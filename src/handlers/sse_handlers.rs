@@ -0,0 +1,179 @@
+use std::{ collections::HashSet, convert::Infallible, time::Duration };
+
+use axum::{
+    extract::{ Path, Query, State },
+    http::{ HeaderMap, StatusCode },
+    response::{ sse::{ Event, KeepAlive }, Sse },
+};
+use sea_orm::{ ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect };
+use serde::Deserialize;
+use tokio::sync::{ broadcast::error::RecvError, mpsc };
+use tokio_stream::{ wrappers::ReceiverStream, Stream };
+use tracing::debug;
+
+use crate::{
+    handlers::comment_handlers::find_visible_post,
+    models::user_models::AppState,
+    services::{ blocks::hidden_author_ids, bus::post_topic },
+    utils::{ api_errors::APIError, guard::{ authenticate, bearer_token } },
+};
+
+const LAST_EVENT_ID: &str = "last-event-id";
+const REPLAY_PAGE_SIZE: u64 = 200;
+const STREAM_BUFFER: usize = 64;
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// `EventSource` cannot set headers, so the token may also be passed as `?token=`.
+#[derive(Deserialize)]
+pub struct StreamAuthQuery {
+    token: Option<String>,
+}
+
+/// Streams `comment.created`, `comment.updated` and `comment.deleted` events for a
+/// post. Each event carries its `comment_event` id, so a client reconnecting with
+/// `Last-Event-ID` is first sent everything it missed from the log.
+///
+/// Events are always read from the log, in id order; the bus only signals that
+/// there is something new. A post's events commit in id order, so the last id sent
+/// is all a stream needs to know it has missed nothing, however late the signals
+/// arrive.
+pub async fn stream_comments(
+    State(state): State<AppState>,
+    Path(post_id): Path<i32>,
+    Query(auth): Query<StreamAuthQuery>,
+    headers: HeaderMap
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, APIError> {
+    let token = bearer_token(&headers)
+        .or(auth.token.as_deref())
+        .ok_or(APIError {
+            message: "Token Not Found".to_string(),
+            status_code: StatusCode::UNAUTHORIZED,
+            error_code: Some(1),
+        })?;
+    let identity = authenticate(&state.db, token).await?;
+    let post = find_visible_post(&state.db, identity.id, post_id).await?;
+
+    let last_event_id = headers
+        .get(LAST_EVENT_ID)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<i64>().ok());
+    let hidden = hidden_author_ids(&state.db, identity.id).await.map_err(|e| APIError {
+        message: format!("Database error while loading blocks: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(1),
+    })?;
+
+    // Subscribe before reading the log so nothing committed in between is missed
+    let mut live = state.bus.subscribe();
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+    let topic = post_topic(post.id);
+    let db = state.db.clone();
+    let shutdown = state.health.shutdown_token();
+
+    tokio::spawn(async move {
+        // A new stream starts after whatever is already in the log
+        let mut sent_up_to = match last_event_id {
+            Some(after) => after,
+            None =>
+                match latest_event_id(&db, post.id).await {
+                    Ok(latest) => latest,
+                    Err(err) => {
+                        debug!("Failed to start comment stream for post {}: {:?}", post.id, err);
+                        return;
+                    }
+                }
+        };
+
+        loop {
+            match replay(&db, post.id, sent_up_to, &hidden, &tx).await {
+                Some(last) => {
+                    sent_up_to = last;
+                }
+                None => {
+                    return;
+                }
+            }
+
+            // Wait for news of an event not sent yet. Missed signals are harmless,
+            // since the log has every event they were about.
+            loop {
+                // Ending the stream on shutdown lets the connection drain; clients
+                // reconnect elsewhere with `Last-Event-ID`
+                let received = tokio::select! {
+                    received = live.recv() => received,
+                    _ = shutdown.cancelled() => return,
+                };
+                match received {
+                    Ok(event) if event.topic == topic && event.id.is_some_and(|id| id > sent_up_to) => {
+                        break;
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(_)) => {
+                        break;
+                    }
+                    Err(RecvError::Closed) => {
+                        return;
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(Sse::new(ReceiverStream::new(rx)).keep_alive(KeepAlive::new().interval(KEEP_ALIVE_INTERVAL)))
+}
+
+/// The newest event in the post's log, or 0 if it has none.
+async fn latest_event_id(db: &DatabaseConnection, post_id: i32) -> Result<i64, sea_orm::DbErr> {
+    let latest: Option<i64> = entity::comment_event::Entity
+        ::find()
+        .select_only()
+        .column_as(entity::comment_event::Column::Id.max(), "id")
+        .filter(entity::comment_event::Column::PostId.eq(post_id))
+        .into_tuple()
+        .one(db).await?
+        .flatten();
+    Ok(latest.unwrap_or(0))
+}
+
+/// Sends every logged event for the post after `after`, oldest first. Returns the
+/// last id covered, or `None` once the client has gone away.
+async fn replay(
+    db: &DatabaseConnection,
+    post_id: i32,
+    after: i64,
+    hidden: &HashSet<i32>,
+    tx: &mpsc::Sender<Result<Event, Infallible>>
+) -> Option<i64> {
+    let mut last = after;
+    loop {
+        let events = entity::comment_event::Entity
+            ::find()
+            .filter(entity::comment_event::Column::PostId.eq(post_id))
+            .filter(entity::comment_event::Column::Id.gt(last))
+            .order_by_asc(entity::comment_event::Column::Id)
+            .limit(REPLAY_PAGE_SIZE)
+            .all(db).await;
+        let events = match events {
+            Ok(events) => events,
+            Err(err) => {
+                debug!("Failed to replay comment events for post {}: {:?}", post_id, err);
+                return Some(last);
+            }
+        };
+
+        let page_len = events.len() as u64;
+        for event in events {
+            last = event.id;
+            if hidden.contains(&event.author_id) {
+                continue;
+            }
+            let sse_event = Event::default().id(event.id.to_string()).event(&event.kind).data(event.payload);
+            if tx.send(Ok(sse_event)).await.is_err() {
+                return None;
+            }
+        }
+        if page_len < REPLAY_PAGE_SIZE {
+            return Some(last);
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use axum::{
+    extract::{ Multipart, Path, State },
+    http::{ header, StatusCode },
+    response::IntoResponse,
+    Extension,
+    Json,
+};
+use bytes::BytesMut;
+use chrono::Utc;
+use sea_orm::{ ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, QueryFilter };
+use tracing::error;
+use uuid::Uuid;
+
+use crate::{
+    config,
+    jobs::{ image_processing::{ original_key, ProcessUpload, PURPOSE_AVATAR, PURPOSE_POST }, queue },
+    models::{ upload_models::UploadModel, user_models::AppState },
+    utils::{ api_errors::APIError, mime::sniff_image_type },
+};
+
+/// Largest accepted upload, from `uploads.max_bytes`.
+pub fn max_upload_bytes() -> usize {
+    config::get().uploads.max_bytes
+}
+
+fn bad_request(message: impl Into<String>) -> APIError {
+    APIError {
+        message: message.into(),
+        status_code: StatusCode::BAD_REQUEST,
+        error_code: Some(6),
+    }
+}
+
+/// Accepts a multipart form with a single `file` field containing an image, and an
+/// optional `purpose` field of `post` (the default) or `avatar`.
+pub async fn create_upload(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    mut multipart: Multipart
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+    let max_bytes = max_upload_bytes();
+
+    let mut file = None;
+    let mut purpose = PURPOSE_POST;
+    while
+        let Some(mut field) = multipart
+            .next_field().await
+            .map_err(|e| bad_request(format!("Invalid multipart body: {}", e)))?
+    {
+        if field.name() == Some("purpose") {
+            let value = field.text().await.map_err(|e| bad_request(format!("Invalid purpose: {}", e)))?;
+            purpose = match value.as_str() {
+                PURPOSE_POST => PURPOSE_POST,
+                PURPOSE_AVATAR => PURPOSE_AVATAR,
+                _ => {
+                    return Err(bad_request("`purpose` must be `post` or `avatar`"));
+                }
+            };
+            continue;
+        }
+        if field.name() != Some("file") || file.is_some() {
+            continue;
+        }
+        let filename = field.file_name().map(str::to_string);
+        let mut buffer = BytesMut::new();
+        // Read chunk by chunk so oversized uploads are rejected without buffering them
+        while
+            let Some(chunk) = field
+                .chunk().await
+                .map_err(|e| bad_request(format!("Failed to read upload: {}", e)))?
+        {
+            if buffer.len() + chunk.len() > max_bytes {
+                return Err(APIError {
+                    message: format!("Upload exceeds the {} byte limit", max_bytes),
+                    status_code: StatusCode::PAYLOAD_TOO_LARGE,
+                    error_code: Some(6),
+                });
+            }
+            buffer.extend_from_slice(&chunk);
+        }
+        file = Some((filename, buffer.freeze()));
+    }
+
+    let (filename, bytes) = file.ok_or_else(|| bad_request("Missing `file` field"))?;
+
+    if bytes.is_empty() {
+        return Err(bad_request("Upload is empty"));
+    }
+
+    let content_type = sniff_image_type(&bytes).ok_or(APIError {
+        message: "Unsupported file type".to_string(),
+        status_code: StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        error_code: Some(6),
+    })?;
+
+    let id = Uuid::new_v4();
+    let storage_key = original_key(id);
+    let size_bytes = bytes.len() as i64;
+
+    state.storage.put(&storage_key, bytes, content_type).await.map_err(|e| {
+        error!("Failed to store upload {}: {}", id, e);
+        APIError {
+            message: "Failed to store upload".to_string(),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        }
+    })?;
+
+    let upload = (entity::upload::ActiveModel {
+        id: Set(id),
+        user_id: Set(identity.id),
+        storage_key: Set(storage_key),
+        content_type: Set(content_type.to_string()),
+        size_bytes: Set(size_bytes),
+        original_filename: Set(filename),
+        purpose: Set(purpose.to_string()),
+        created_at: Set(Utc::now().naive_utc()),
+        ..Default::default()
+    })
+        .insert(db).await
+        .map_err(|e| APIError {
+            message: format!("Failed to record upload: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+
+    queue::enqueue(db, &(ProcessUpload { upload_id: upload.id })).await.map_err(|e| APIError {
+        message: format!("Failed to queue upload processing: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    })?;
+
+    Ok((StatusCode::CREATED, Json(UploadModel::from(upload))))
+}
+
+/// Serves the stripped image. Nothing is served until processing succeeds, since
+/// the original may still carry EXIF/GPS metadata; after that the stored objects
+/// never change, so they can be cached for good.
+pub async fn get_upload(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let not_found = || APIError {
+        message: "Upload not found".to_string(),
+        status_code: StatusCode::NOT_FOUND,
+        error_code: Some(2),
+    };
+
+    let upload = entity::upload::Entity
+        ::find_by_id(id)
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding upload: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or_else(not_found)?;
+
+    if upload.processed_at.is_none() {
+        return Err(APIError {
+            message: "Upload is still being processed".to_string(),
+            status_code: StatusCode::CONFLICT,
+            error_code: Some(5),
+        });
+    }
+
+    let bytes = state.storage
+        .get(&upload.storage_key).await
+        .map_err(|e| {
+            error!("Failed to read upload {}: {}", id, e);
+            APIError {
+                message: "Failed to read upload".to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                error_code: Some(3),
+            }
+        })?
+        .ok_or_else(not_found)?;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, upload.content_type),
+            (header::CACHE_CONTROL, "public, max-age=31536000, immutable".to_string()),
+        ],
+        bytes,
+    ))
+}
+
+pub async fn get_upload_variant(
+    State(state): State<AppState>,
+    Path((id, label)): Path<(Uuid, String)>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let not_found = || APIError {
+        message: "Upload variant not found".to_string(),
+        status_code: StatusCode::NOT_FOUND,
+        error_code: Some(2),
+    };
+
+    // A failed attempt can leave variants behind that a retry will overwrite
+    let variant = entity::upload_variant::Entity
+        ::find()
+        .inner_join(entity::upload::Entity)
+        .filter(entity::upload::Column::ProcessedAt.is_not_null())
+        .filter(entity::upload_variant::Column::UploadId.eq(id))
+        .filter(entity::upload_variant::Column::Label.eq(label))
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding upload variant: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or_else(not_found)?;
+
+    let bytes = state.storage
+        .get(&variant.storage_key).await
+        .map_err(|e| {
+            error!("Failed to read variant {} of upload {}: {}", variant.label, id, e);
+            APIError {
+                message: "Failed to read upload".to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                error_code: Some(3),
+            }
+        })?
+        .ok_or_else(not_found)?;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, variant.content_type),
+            (header::CACHE_CONTROL, "public, max-age=31536000, immutable".to_string()),
+        ],
+        bytes,
+    ))
+}
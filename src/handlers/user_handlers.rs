@@ -1,22 +1,91 @@
-use axum::{ extract::{ Path, State }, http::StatusCode, response::{ IntoResponse }, Json };
-use sea_orm::{ ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, QueryFilter };
+use axum::{ extract::{ Path, State }, http::StatusCode, response::{ IntoResponse }, Extension, Json };
+use chrono::Utc;
+use sea_orm::{
+    sea_query::{ Expr, Query },
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait,
+    DatabaseConnection,
+    EntityTrait,
+    PaginatorTrait,
+    QueryFilter,
+    SqlErr,
+    TransactionTrait,
+};
 use uuid::Uuid;
 
 use crate::{
-    models::user_models::{ AppState, UpdateUserModel, UserModel },
-    utils::api_errors::APIError,
+    jobs::account_deletion::{ grace_period, TOMBSTONE_UUID },
+    jobs::image_processing::PURPOSE_AVATAR,
+    models::user_models::{ AppState, SetAvatarModel, UpdateUserModel, UserModel },
+    services::{ events::{ self, DomainEvent }, posts::{ load_images, posts_mentioning, render_posts } },
+    utils::{
+        api_errors::APIError,
+        mentions::{ normalize_handle, MAX_HANDLE_LENGTH, MIN_HANDLE_LENGTH },
+        soft_delete::SoftDelete,
+    },
 };
 
+/// Validates a requested `@handle` and checks no other account holds it. Handles
+/// are stored lowercased, so uniqueness is case-insensitive. This only gives an
+/// early, friendly error; the unique index on `users.handle` settles races.
+pub(crate) async fn available_handle(
+    db: &DatabaseConnection,
+    handle: &str,
+    current_user_id: Option<i32>
+) -> Result<String, APIError> {
+    let handle = normalize_handle(handle).ok_or(APIError {
+        message: format!(
+            "Handles must be {} to {} letters, digits or underscores",
+            MIN_HANDLE_LENGTH,
+            MAX_HANDLE_LENGTH
+        ),
+        status_code: StatusCode::BAD_REQUEST,
+        error_code: Some(6),
+    })?;
+
+    let mut query = entity::user::Entity::find().filter(entity::user::Column::Handle.eq(handle.clone()));
+    if let Some(user_id) = current_user_id {
+        query = query.filter(entity::user::Column::Id.ne(user_id));
+    }
+    let taken = query
+        .count(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while checking handle: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    if taken > 0 {
+        return Err(APIError {
+            message: format!("The handle @{} is taken", handle),
+            status_code: StatusCode::CONFLICT,
+            error_code: Some(5),
+        });
+    }
+    Ok(handle)
+}
+
+/// Updates a profile. Only the account itself or an administrator may do so.
 pub async fn update_user(
     State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
     Path(uuid): Path<Uuid>,
     Json(user_data): Json<UpdateUserModel>
 ) -> Result<impl IntoResponse, APIError> {
     let db = &state.db;
 
+    if identity.uuid != uuid && !identity.is_admin {
+        return Err(APIError {
+            message: "FORBIDDEN".to_string(),
+            status_code: StatusCode::FORBIDDEN,
+            error_code: Some(1),
+        });
+    }
+
     let user = entity::user::Entity
-        ::find()
-        .filter(entity::user::Column::Email.eq(uuid))
+        ::find_active()
+        .filter(entity::user::Column::Uuid.eq(uuid))
         .one(db).await
         .map_err(|e| {
             // turn DB error into APIError
@@ -34,15 +103,45 @@ pub async fn update_user(
             error_code: Some(2),
         })?;
 
+    let handle = match user_data.handle {
+        Some(handle) => Some(available_handle(db, &handle, Some(user.id)).await?),
+        None => None,
+    };
+
+    let update_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Failed to update user: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let handle_changed = handle.is_some() && handle != user.handle;
     let mut active_user: entity::user::ActiveModel = user.into();
     active_user.name = Set(user_data.name);
-    active_user.update(db).await.map_err(|e| {
-        APIError {
-            message: format!("Failed to update user: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-            error_code: Some(3),
-        }
+    if let Some(handle) = handle {
+        active_user.handle = Set(Some(handle));
+    }
+    let txn = db.begin().await.map_err(update_error)?;
+    let user = active_user.update(&txn).await.map_err(|e| match e.sql_err() {
+        // a concurrent request claimed the handle after `available_handle` checked it
+        Some(SqlErr::UniqueConstraintViolation(_)) => APIError {
+            message: "That handle is taken".to_string(),
+            status_code: StatusCode::CONFLICT,
+            error_code: Some(5),
+        },
+        _ => update_error(e),
     })?;
+    // Posts link mentions by handle, so the old one stops linking to this user
+    if handle_changed {
+        let this_user = Query::select()
+            .column(entity::user::Column::Id)
+            .from(entity::user::Entity)
+            .and_where(entity::user::Column::Id.eq(user.id))
+            .to_owned();
+        let stale_posts = posts_mentioning(&txn, this_user).await.map_err(update_error)?;
+        render_posts(&txn, stale_posts).await.map_err(update_error)?;
+    }
+    events::record(&txn, &(DomainEvent::UserUpdated { user_id: user.id })).await.map_err(update_error)?;
+    txn.commit().await.map_err(update_error)?;
 
     Ok((
         StatusCode::OK,
@@ -55,6 +154,8 @@ pub async fn update_user(
     ))
 }
 
+/// Soft deletes an account immediately, skipping the grace period users get from
+/// `DELETE /me`. Mounted under the admin routes.
 pub async fn delete_user(
     State(state): State<AppState>,
     Path(uuid): Path<Uuid>
@@ -62,7 +163,7 @@ pub async fn delete_user(
     let db = &state.db;
 
     let user = entity::user::Entity
-        ::find()
+        ::find_active()
         .filter(entity::user::Column::Uuid.eq(uuid))
         .one(db).await
         .map_err(|e| APIError {
@@ -77,14 +178,40 @@ pub async fn delete_user(
             error_code: Some(2),
         })?;
 
-    entity::user::Entity
-        ::delete_by_id(user.id)
-        .exec(db).await
-        .map_err(|e| APIError {
-            message: format!("Failed to delete user: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-            error_code: Some(3),
-        })?;
+    // Soft delete the user together with their content, stamping every row with the
+    // same timestamp so a restore can bring back exactly what was removed here.
+    let deleted_at = Utc::now().naive_utc();
+    let delete_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Failed to delete user: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let txn = db.begin().await.map_err(delete_error)?;
+
+    entity::comment::Entity
+        ::update_many()
+        .col_expr(entity::comment::Column::DeletedAt, Expr::value(deleted_at))
+        .filter(entity::comment::Column::UserId.eq(user.id))
+        .filter(entity::comment::Column::DeletedAt.is_null())
+        .exec(&txn).await
+        .map_err(delete_error)?;
+
+    entity::post::Entity
+        ::update_many()
+        .col_expr(entity::post::Column::DeletedAt, Expr::value(deleted_at))
+        .filter(entity::post::Column::UserId.eq(user.id))
+        .filter(entity::post::Column::DeletedAt.is_null())
+        .exec(&txn).await
+        .map_err(delete_error)?;
+
+    let mut active_user: entity::user::ActiveModel = user.into();
+    active_user.deleted_at = Set(Some(deleted_at));
+    let user = active_user.update(&txn).await.map_err(delete_error)?;
+
+    events::record(&txn, &(DomainEvent::UserDeleted { user_id: user.id, uuid })).await.map_err(delete_error)?;
+
+    txn.commit().await.map_err(delete_error)?;
 
     Ok((
         StatusCode::OK,
@@ -96,7 +223,8 @@ pub async fn get_all_users(State(state): State<AppState>) -> Result<impl IntoRes
     let db = &state.db;
 
     let users = entity::user::Entity
-        ::find()
+        ::find_active()
+        .filter(entity::user::Column::Uuid.ne(TOMBSTONE_UUID))
         .all(db).await
         .map_err(|e| APIError {
             message: format!("Database error while fetching all users: {}", e),
@@ -104,11 +232,23 @@ pub async fn get_all_users(State(state): State<AppState>) -> Result<impl IntoRes
             error_code: Some(1),
         })?;
 
+    let avatar_ids = users
+        .iter()
+        .filter_map(|user| user.avatar_upload_id)
+        .collect();
+    let avatars = load_images(db, avatar_ids).await.map_err(|e| APIError {
+        message: format!("Database error while loading avatars: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(1),
+    })?;
+
     let user_models: Vec<UserModel> = users
         .into_iter()
         .map(|user| UserModel {
+            avatar: user.avatar_upload_id.and_then(|id| avatars.get(&id).cloned()),
             name: user.name,
             email: user.email,
+            handle: user.handle,
             password: user.password,
             uuid: user.uuid,
             created_at: user.created_at,
@@ -117,3 +257,145 @@ pub async fn get_all_users(State(state): State<AppState>) -> Result<impl IntoRes
 
     Ok((StatusCode::OK, Json(user_models)))
 }
+
+/// Schedules deletion of the caller's account. The account stays usable until the
+/// grace period ends so the request can still be cancelled.
+pub async fn request_account_deletion(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let requested_at = match identity.deletion_requested_at {
+        Some(requested_at) => requested_at,
+        None => {
+            let requested_at = Utc::now().naive_utc();
+            let mut active_user: entity::user::ActiveModel = identity.clone().into();
+            active_user.deletion_requested_at = Set(Some(requested_at));
+            active_user.update(db).await.map_err(|e| APIError {
+                message: format!("Failed to schedule account deletion: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                error_code: Some(3),
+            })?;
+            requested_at
+        }
+    };
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(
+            serde_json::json!({
+            "message": "Account deletion scheduled",
+            "uuid": identity.uuid,
+            "scheduled_for": requested_at + grace_period()
+        })
+        ),
+    ))
+}
+
+pub async fn cancel_account_deletion(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    // Conditional so it cannot race the finalize job: if that job holds the row,
+    // this waits for it and then matches nothing because the account is gone
+    let cancelled = entity::user::Entity
+        ::update_many()
+        .col_expr(entity::user::Column::DeletionRequestedAt, Expr::value(None::<chrono::NaiveDateTime>))
+        .filter(entity::user::Column::Id.eq(identity.id))
+        .filter(entity::user::Column::DeletionRequestedAt.is_not_null())
+        .filter(entity::user::Column::DeletedAt.is_null())
+        .exec(db).await
+        .map_err(|e| APIError {
+            message: format!("Failed to cancel account deletion: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+
+    if cancelled.rows_affected == 0 {
+        return Err(APIError {
+            message: "No account deletion is pending".to_string(),
+            status_code: StatusCode::CONFLICT,
+            error_code: Some(5),
+        });
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({"message": "Account deletion cancelled", "uuid": identity.uuid})),
+    ))
+}
+
+/// Points the caller's avatar at one of their uploads made with the `avatar`
+/// purpose, which is what gets the square variants.
+pub async fn set_avatar(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Json(avatar_data): Json<SetAvatarModel>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let upload = entity::upload::Entity
+        ::find_by_id(avatar_data.upload_id)
+        .filter(entity::upload::Column::UserId.eq(identity.id))
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding upload: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or(APIError {
+            message: "Upload not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+            error_code: Some(2),
+        })?;
+
+    if upload.purpose != PURPOSE_AVATAR {
+        return Err(APIError {
+            message: "Upload was not made with the `avatar` purpose".to_string(),
+            status_code: StatusCode::BAD_REQUEST,
+            error_code: Some(6),
+        });
+    }
+
+    update_avatar(db, identity, Some(upload.id)).await?;
+    let avatar = load_images(db, vec![upload.id]).await
+        .map_err(|e| APIError {
+            message: format!("Database error while loading avatar: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .remove(&upload.id);
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Avatar updated", "avatar": avatar}))))
+}
+
+pub async fn remove_avatar(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>
+) -> Result<impl IntoResponse, APIError> {
+    update_avatar(&state.db, identity, None).await?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Avatar removed"}))))
+}
+
+async fn update_avatar(
+    db: &DatabaseConnection,
+    user: entity::user::Model,
+    upload_id: Option<Uuid>
+) -> Result<(), APIError> {
+    let update_error = |e: sea_orm::DbErr| APIError {
+        message: format!("Failed to update avatar: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let mut active_user: entity::user::ActiveModel = user.into();
+    active_user.avatar_upload_id = Set(upload_id);
+    let txn = db.begin().await.map_err(update_error)?;
+    let user = active_user.update(&txn).await.map_err(update_error)?;
+    events::record(&txn, &(DomainEvent::UserUpdated { user_id: user.id })).await.map_err(update_error)?;
+    txn.commit().await.map_err(update_error)
+}
@@ -0,0 +1,362 @@
+use std::collections::{ BTreeSet, HashMap };
+
+use axum::{ extract::{ Path, Query, State }, http::StatusCode, response::IntoResponse, Extension, Json };
+use chrono::Utc;
+use reqwest::Url;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait,
+    ConnectionTrait,
+    DatabaseConnection,
+    DbErr,
+    EntityTrait,
+    QueryFilter,
+    QueryOrder,
+    QuerySelect,
+    TransactionTrait,
+};
+
+use crate::{
+    jobs::{ queue, webhook_delivery::{ DeliverWebhook, STATUS_PENDING } },
+    models::{
+        user_models::AppState,
+        webhook_models::{
+            CreateWebhookModel,
+            DeliveryPageQuery,
+            UpdateWebhookModel,
+            WebhookDeliveryModel,
+            WebhookModel,
+        },
+    },
+    services::webhooks::{ enqueue_for, generate_secret, PING, WEBHOOK_EVENT_TYPES },
+    utils::api_errors::APIError,
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 200;
+
+fn validate_url(url: &str) -> Result<String, APIError> {
+    match Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(parsed.to_string()),
+        _ =>
+            Err(APIError {
+                message: "Webhook URL must be an absolute http or https URL".to_string(),
+                status_code: StatusCode::BAD_REQUEST,
+                error_code: Some(6),
+            }),
+    }
+}
+
+/// Deduplicates and checks the requested event types against `WEBHOOK_EVENT_TYPES`.
+fn validate_event_types(event_types: Vec<String>) -> Result<Vec<String>, APIError> {
+    let event_types: BTreeSet<String> = event_types.into_iter().collect();
+    if event_types.is_empty() {
+        return Err(APIError {
+            message: "Subscribe to at least one event type".to_string(),
+            status_code: StatusCode::BAD_REQUEST,
+            error_code: Some(6),
+        });
+    }
+    if let Some(unknown) = event_types.iter().find(|event_type| !WEBHOOK_EVENT_TYPES.contains(&event_type.as_str())) {
+        return Err(APIError {
+            message: format!("Unknown event type: {}", unknown),
+            status_code: StatusCode::BAD_REQUEST,
+            error_code: Some(6),
+        });
+    }
+    Ok(event_types.into_iter().collect())
+}
+
+async fn replace_event_types<C: ConnectionTrait>(db: &C, webhook_id: i32, event_types: &[String]) -> Result<(), DbErr> {
+    entity::webhook_event_type::Entity
+        ::delete_many()
+        .filter(entity::webhook_event_type::Column::WebhookId.eq(webhook_id))
+        .exec(db).await?;
+
+    entity::webhook_event_type::Entity
+        ::insert_many(
+            event_types.iter().map(|event_type| entity::webhook_event_type::ActiveModel {
+                webhook_id: Set(webhook_id),
+                event_type: Set(event_type.clone()),
+            })
+        )
+        .exec_without_returning(db).await?;
+
+    Ok(())
+}
+
+async fn event_types_by_webhook(
+    db: &DatabaseConnection,
+    webhook_ids: Vec<i32>
+) -> Result<HashMap<i32, Vec<String>>, APIError> {
+    let rows = entity::webhook_event_type::Entity
+        ::find()
+        .filter(entity::webhook_event_type::Column::WebhookId.is_in(webhook_ids))
+        .order_by_asc(entity::webhook_event_type::Column::EventType)
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while fetching webhook events: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    let mut event_types: HashMap<i32, Vec<String>> = HashMap::new();
+    for row in rows {
+        event_types.entry(row.webhook_id).or_default().push(row.event_type);
+    }
+    Ok(event_types)
+}
+
+async fn find_webhook(db: &DatabaseConnection, id: i32) -> Result<entity::webhook::Model, APIError> {
+    entity::webhook::Entity
+        ::find_by_id(id)
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding webhook: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or(APIError {
+            message: "Webhook not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+            error_code: Some(2),
+        })
+}
+
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Extension(identity): Extension<entity::user::Model>,
+    Json(webhook_data): Json<CreateWebhookModel>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let url = validate_url(&webhook_data.url)?;
+    let event_types = validate_event_types(webhook_data.event_types)?;
+    let secret = match webhook_data.secret {
+        Some(secret) if !secret.trim().is_empty() => secret,
+        _ => generate_secret(),
+    };
+
+    let insert_error = |e: DbErr| APIError {
+        message: format!("Failed to create webhook: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let txn = db.begin().await.map_err(insert_error)?;
+    let webhook = (entity::webhook::ActiveModel {
+        url: Set(url),
+        secret: Set(secret),
+        active: Set(true),
+        created_by: Set(Some(identity.id)),
+        created_at: Set(Utc::now().naive_utc()),
+        ..Default::default()
+    })
+        .insert(&txn).await
+        .map_err(insert_error)?;
+    replace_event_types(&txn, webhook.id, &event_types).await.map_err(insert_error)?;
+    txn.commit().await.map_err(insert_error)?;
+
+    let secret = webhook.secret.clone();
+    let mut response = WebhookModel::new(webhook, event_types);
+    response.secret = Some(secret);
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+pub async fn list_webhooks(State(state): State<AppState>) -> Result<Json<Vec<WebhookModel>>, APIError> {
+    let db = &state.db;
+
+    let webhooks = entity::webhook::Entity
+        ::find()
+        .order_by_asc(entity::webhook::Column::Id)
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while fetching webhooks: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    let mut event_types = event_types_by_webhook(
+        db,
+        webhooks
+            .iter()
+            .map(|webhook| webhook.id)
+            .collect()
+    ).await?;
+
+    Ok(
+        Json(
+            webhooks
+                .into_iter()
+                .map(|webhook| {
+                    let types = event_types.remove(&webhook.id).unwrap_or_default();
+                    WebhookModel::new(webhook, types)
+                })
+                .collect()
+        )
+    )
+}
+
+pub async fn update_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Json(webhook_data): Json<UpdateWebhookModel>
+) -> Result<Json<WebhookModel>, APIError> {
+    let db = &state.db;
+
+    let webhook = find_webhook(db, id).await?;
+    let url = webhook_data.url.as_deref().map(validate_url).transpose()?;
+    let event_types = webhook_data.event_types.map(validate_event_types).transpose()?;
+
+    let update_error = |e: DbErr| APIError {
+        message: format!("Failed to update webhook: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let mut active_webhook: entity::webhook::ActiveModel = webhook.into();
+    if let Some(url) = url {
+        active_webhook.url = Set(url);
+    }
+    if let Some(active) = webhook_data.active {
+        active_webhook.active = Set(active);
+    }
+
+    let txn = db.begin().await.map_err(update_error)?;
+    let webhook = active_webhook.update(&txn).await.map_err(update_error)?;
+    if let Some(event_types) = &event_types {
+        replace_event_types(&txn, webhook.id, event_types).await.map_err(update_error)?;
+    }
+    txn.commit().await.map_err(update_error)?;
+
+    let event_types = event_types_by_webhook(db, vec![webhook.id]).await?.remove(&webhook.id).unwrap_or_default();
+
+    Ok(Json(WebhookModel::new(webhook, event_types)))
+}
+
+/// Deleting a webhook drops its pending deliveries and delivery log with it.
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<i32>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let webhook = find_webhook(db, id).await?;
+    entity::webhook::Entity
+        ::delete_by_id(webhook.id)
+        .exec(db).await
+        .map_err(|e| APIError {
+            message: format!("Failed to delete webhook: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(3),
+        })?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"message": "Webhook deleted", "id": id}))))
+}
+
+/// Queues a `ping` event so a receiver can be checked without waiting for real
+/// traffic.
+pub async fn ping_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<i32>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let webhook = find_webhook(db, id).await?;
+
+    let queue_error = |e: DbErr| APIError {
+        message: format!("Failed to queue ping: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let txn = db.begin().await.map_err(queue_error)?;
+    enqueue_for(&txn, vec![webhook.id], PING, serde_json::json!({"webhook_id": webhook.id})).await.map_err(
+        queue_error
+    )?;
+    txn.commit().await.map_err(queue_error)?;
+
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({"message": "Ping queued", "id": id}))))
+}
+
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(page): Query<DeliveryPageQuery>
+) -> Result<Json<Vec<WebhookDeliveryModel>>, APIError> {
+    let db = &state.db;
+
+    let webhook = find_webhook(db, id).await?;
+    let mut query = entity::webhook_delivery::Entity
+        ::find()
+        .filter(entity::webhook_delivery::Column::WebhookId.eq(webhook.id));
+    if let Some(before) = page.before {
+        query = query.filter(entity::webhook_delivery::Column::Id.lt(before));
+    }
+    if let Some(status) = page.status {
+        query = query.filter(entity::webhook_delivery::Column::Status.eq(status));
+    }
+
+    let deliveries = query
+        .order_by_desc(entity::webhook_delivery::Column::Id)
+        .limit(page.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE))
+        .all(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while fetching deliveries: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?;
+
+    Ok(Json(deliveries.into_iter().map(WebhookDeliveryModel::from).collect()))
+}
+
+/// Sends a past delivery again as a new delivery with a fresh retry schedule; the
+/// original stays in the log as it was. The payload keeps its event id.
+pub async fn redeliver(
+    State(state): State<AppState>,
+    Path((id, delivery_id)): Path<(i32, i64)>
+) -> Result<impl IntoResponse, APIError> {
+    let db = &state.db;
+
+    let delivery = entity::webhook_delivery::Entity
+        ::find_by_id(delivery_id)
+        .filter(entity::webhook_delivery::Column::WebhookId.eq(id))
+        .one(db).await
+        .map_err(|e| APIError {
+            message: format!("Database error while finding delivery: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: Some(1),
+        })?
+        .ok_or(APIError {
+            message: "Delivery not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+            error_code: Some(2),
+        })?;
+
+    let queue_error = |e: DbErr| APIError {
+        message: format!("Failed to queue redelivery: {}", e),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        error_code: Some(3),
+    };
+
+    let now = Utc::now().naive_utc();
+    let txn = db.begin().await.map_err(queue_error)?;
+    let redelivery = (entity::webhook_delivery::ActiveModel {
+        webhook_id: Set(delivery.webhook_id),
+        event_type: Set(delivery.event_type),
+        payload: Set(delivery.payload),
+        status: Set(STATUS_PENDING.to_string()),
+        attempts: Set(0),
+        next_attempt_at: Set(Some(now)),
+        created_at: Set(now),
+        ..Default::default()
+    })
+        .insert(&txn).await
+        .map_err(queue_error)?;
+    queue::enqueue(&txn, &(DeliverWebhook { delivery_id: redelivery.id })).await.map_err(queue_error)?;
+    txn.commit().await.map_err(queue_error)?;
+
+    Ok((StatusCode::ACCEPTED, Json(WebhookDeliveryModel::from(redelivery))))
+}
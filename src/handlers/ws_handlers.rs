@@ -0,0 +1,273 @@
+use std::{ collections::{ HashMap, HashSet }, time::Duration };
+
+use axum::{
+    extract::{ ws::{ close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade }, Query, State },
+    http::{ HeaderMap, StatusCode },
+    response::IntoResponse,
+};
+use bytes::Bytes;
+use sea_orm::{ ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QuerySelect };
+use serde::{ Deserialize, Serialize };
+use tokio::{ sync::broadcast::error::RecvError, time::{ timeout, Instant } };
+use tracing::debug;
+
+use crate::{
+    models::user_models::AppState,
+    services::{
+        blocks::hidden_author_ids,
+        bus::{ notifications_topic, post_topic, BusEvent, FEED_TOPIC },
+    },
+    telemetry::metrics::WebSocketConnection,
+    utils::{
+        api_errors::APIError,
+        guard::{ authenticate, bearer_token },
+        visibility::Visibility,
+    },
+};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+/// A client that cannot take a message within this long is disconnected rather
+/// than letting its events queue up.
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+const NOTIFICATIONS: &str = "notifications";
+
+/// Browsers cannot set headers on a WebSocket handshake, so the token may also be
+/// passed as `?token=`.
+#[derive(Deserialize)]
+pub struct WsAuthQuery {
+    token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ClientMessage {
+    Subscribe {
+        topic: String,
+    },
+    Unsubscribe {
+        topic: String,
+    },
+    Ping,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ServerMessage<'a> {
+    Event {
+        topic: &'a str,
+        event: &'a str,
+        data: &'a serde_json::Value,
+    },
+    Subscribed {
+        topic: &'a str,
+    },
+    Unsubscribed {
+        topic: &'a str,
+    },
+    /// The connection fell behind and `missed` events were dropped; clients
+    /// should refetch whatever they display.
+    Lagged {
+        missed: u64,
+    },
+    Error {
+        message: &'a str,
+    },
+    Pong,
+}
+
+pub async fn ws_handler(
+    State(state): State<AppState>,
+    Query(auth): Query<WsAuthQuery>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade
+) -> Result<impl IntoResponse, APIError> {
+    let token = bearer_token(&headers)
+        .or(auth.token.as_deref())
+        .ok_or(APIError {
+            message: "Token Not Found".to_string(),
+            status_code: StatusCode::UNAUTHORIZED,
+            error_code: Some(1),
+        })?;
+    let identity = authenticate(&state.db, token).await?;
+
+    Ok(ws.on_upgrade(move |socket| serve_connection(socket, state, identity)))
+}
+
+/// Per-connection subscriptions, keyed by bus topic with the topic name the client
+/// used, plus the viewer's block/mute and follow sets used to filter events.
+struct Subscriptions {
+    topics: HashMap<String, String>,
+    hidden: HashSet<i32>,
+    followees: HashSet<i32>,
+}
+
+impl Subscriptions {
+    fn client_topic(&self, event: &BusEvent) -> Option<&str> {
+        let topic = self.topics.get(&event.topic)?;
+        if let Some(author_id) = event.author_id {
+            if self.hidden.contains(&author_id) {
+                return None;
+            }
+            if event.topic == FEED_TOPIC && !self.followees.contains(&author_id) {
+                return None;
+            }
+        }
+        Some(topic)
+    }
+}
+
+async fn serve_connection(mut socket: WebSocket, state: AppState, identity: entity::user::Model) {
+    let _connection = WebSocketConnection::open();
+    let mut events = state.bus.subscribe();
+    let mut subscriptions = Subscriptions {
+        topics: HashMap::from([(notifications_topic(identity.id), NOTIFICATIONS.to_string())]),
+        hidden: HashSet::new(),
+        followees: HashSet::new(),
+    };
+    match hidden_author_ids(&state.db, identity.id).await {
+        Ok(hidden) => {
+            subscriptions.hidden = hidden;
+        }
+        Err(err) => debug!("Failed to load hidden users for {}: {:?}", identity.uuid, err),
+    }
+
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    let mut last_seen = Instant::now();
+    let shutdown = state.health.shutdown_token();
+
+    loop {
+        let reply = tokio::select! {
+            incoming = socket.recv() => {
+                let message = match incoming {
+                    Some(Ok(message)) => message,
+                    _ => break,
+                };
+                last_seen = Instant::now();
+                match message {
+                    Message::Text(text) =>
+                        handle_client_message(&state, &identity, &mut subscriptions, text.as_str()).await,
+                    Message::Close(_) => break,
+                    // Pings are answered by axum; any frame counts as a sign of life
+                    _ => continue,
+                }
+            }
+            event = events.recv() => {
+                match event {
+                    Ok(event) => match subscriptions.client_topic(&event) {
+                        Some(topic) => encode(&ServerMessage::Event {
+                            topic,
+                            event: &event.event,
+                            data: &event.data,
+                        }),
+                        None => continue,
+                    },
+                    Err(RecvError::Lagged(missed)) => encode(&ServerMessage::Lagged { missed }),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            _ = heartbeat.tick() => {
+                if last_seen.elapsed() > HEARTBEAT_INTERVAL * 2 {
+                    debug!("WebSocket for {} timed out", identity.uuid);
+                    break;
+                }
+                Message::Ping(Bytes::new())
+            }
+            _ = shutdown.cancelled() => {
+                let close = CloseFrame { code: close_code::AWAY, reason: "Server shutting down".into() };
+                let _ = timeout(SEND_TIMEOUT, socket.send(Message::Close(Some(close)))).await;
+                break;
+            }
+        };
+
+        match timeout(SEND_TIMEOUT, socket.send(reply)).await {
+            Ok(Ok(())) => {}
+            _ => break,
+        }
+    }
+}
+
+fn encode(message: &ServerMessage) -> Message {
+    Message::Text(serde_json::to_string(message).unwrap_or_default().into())
+}
+
+async fn handle_client_message(
+    state: &AppState,
+    identity: &entity::user::Model,
+    subscriptions: &mut Subscriptions,
+    text: &str
+) -> Message {
+    let message = match serde_json::from_str::<ClientMessage>(text) {
+        Ok(message) => message,
+        Err(_) => {
+            return encode(&ServerMessage::Error { message: "Unrecognised message" });
+        }
+    };
+
+    match message {
+        ClientMessage::Ping => encode(&ServerMessage::Pong),
+        ClientMessage::Subscribe { topic } => {
+            match resolve_topic(&state.db, identity, &topic).await {
+                Ok(bus_topic) => {
+                    // Refresh the filters so blocks and follows made since connecting apply
+                    if let Ok(hidden) = hidden_author_ids(&state.db, identity.id).await {
+                        subscriptions.hidden = hidden;
+                    }
+                    if
+                        bus_topic == FEED_TOPIC &&
+                        let Ok(followees) = load_followees(&state.db, identity.id).await
+                    {
+                        subscriptions.followees = followees;
+                    }
+                    subscriptions.topics.insert(bus_topic, topic.clone());
+                    encode(&ServerMessage::Subscribed { topic: &topic })
+                }
+                Err(message) => encode(&ServerMessage::Error { message }),
+            }
+        }
+        ClientMessage::Unsubscribe { topic } => {
+            subscriptions.topics.retain(|_, client_topic| *client_topic != topic);
+            encode(&ServerMessage::Unsubscribed { topic: &topic })
+        }
+    }
+}
+
+/// Maps a client topic to its bus topic: `notifications`, `feed` or `post:{id}`
+/// for a post the viewer can see.
+async fn resolve_topic(
+    db: &DatabaseConnection,
+    identity: &entity::user::Model,
+    topic: &str
+) -> Result<String, &'static str> {
+    if topic == NOTIFICATIONS {
+        return Ok(notifications_topic(identity.id));
+    }
+    if topic == FEED_TOPIC {
+        return Ok(FEED_TOPIC.to_string());
+    }
+
+    let post_id: i32 = topic
+        .strip_prefix("post:")
+        .and_then(|id| id.parse().ok())
+        .ok_or("Unknown topic")?;
+    let visible = entity::post::Entity
+        ::find_visible_to(identity.id)
+        .filter(entity::post::Column::Id.eq(post_id))
+        .count(db).await
+        .map_err(|_| "Failed to look up post")?;
+    if visible == 0 {
+        return Err("Post not found");
+    }
+    Ok(post_topic(post_id))
+}
+
+async fn load_followees(db: &DatabaseConnection, follower_id: i32) -> Result<HashSet<i32>, sea_orm::DbErr> {
+    let followees: Vec<i32> = entity::follow::Entity
+        ::find()
+        .select_only()
+        .column(entity::follow::Column::FolloweeId)
+        .filter(entity::follow::Column::FollowerId.eq(follower_id))
+        .into_tuple()
+        .all(db).await?;
+
+    Ok(followees.into_iter().collect())
+}
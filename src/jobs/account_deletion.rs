@@ -0,0 +1,372 @@
+use async_trait::async_trait;
+use chrono::{ NaiveDateTime, Utc };
+use sea_orm::{
+    sea_query::{ Condition, Expr, Query },
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait,
+    DatabaseConnection,
+    DatabaseTransaction,
+    DbErr,
+    EntityTrait,
+    QueryFilter,
+    QuerySelect,
+    TransactionTrait,
+};
+use serde::{ Deserialize, Serialize };
+use tracing::{ error, info };
+use uuid::Uuid;
+
+use crate::{
+    config,
+    jobs::{ purge::upload_object_keys, queue::Job },
+    models::user_models::AppState,
+    services::{
+        events::{ self, DomainEvent },
+        mentions::delete_orphaned_links,
+        posts::{ posts_mentioning, render_posts },
+        reactions::delete_orphaned_reactions,
+    },
+    storage::{ self, StorageBackend },
+};
+
+/// Owner of content that is kept after its author deleted their account.
+pub const TOMBSTONE_UUID: Uuid = Uuid::nil();
+
+pub const FINALIZE_SCHEDULE: &str = "0 */5 * * * *";
+
+/// What happens to a deleted account's posts and comments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeletionMode {
+    /// Delete the content along with the account.
+    Cascade,
+    /// Keep the content and attribute it to the tombstone user.
+    Reassign,
+}
+
+/// How long a user has to cancel a deletion request, from `accounts.deletion_grace_hours`.
+pub fn grace_period() -> chrono::Duration {
+    chrono::Duration::hours(config::get().accounts.deletion_grace_hours)
+}
+
+/// Finalizes account deletions whose grace period has elapsed. Runs on
+/// `FINALIZE_SCHEDULE`.
+#[derive(Serialize, Deserialize)]
+pub struct FinalizeAccountDeletions;
+
+#[async_trait]
+impl Job for FinalizeAccountDeletions {
+    const KIND: &'static str = "account_deletion.finalize";
+
+    async fn run(self, state: &AppState) -> Result<(), String> {
+        finalize_due_deletions(&state.db, state.storage.as_ref(), config::get().accounts.deletion_mode, grace_period()).await.map_err(
+            |e| format!("Finalizing account deletions failed: {}", e)
+        )
+    }
+}
+
+/// Finalizes every account whose grace period ended before now. A failure is
+/// logged and leaves that account for the next run without holding up the rest.
+pub async fn finalize_due_deletions(
+    db: &DatabaseConnection,
+    storage: &dyn StorageBackend,
+    mode: DeletionMode,
+    grace: chrono::Duration
+) -> Result<(), DbErr> {
+    let cutoff = (Utc::now() - grace).naive_utc();
+
+    let due: Vec<i32> = entity::user::Entity
+        ::find()
+        .select_only()
+        .column(entity::user::Column::Id)
+        .filter(entity::user::Column::DeletionRequestedAt.lt(cutoff))
+        .filter(entity::user::Column::DeletedAt.is_null())
+        .into_tuple()
+        .all(db).await?;
+
+    for user_id in due {
+        match finalize_deletion(db, storage, user_id, mode, cutoff).await {
+            Ok(Some(uuid)) => info!("Finalized deletion of account {}", uuid),
+            Ok(None) => info!("Deletion of account {} was cancelled before it was finalized", user_id),
+            Err(err) => error!("Failed to finalize deletion of account {}: {}", user_id, err),
+        }
+    }
+
+    Ok(())
+}
+
+/// Deletes one account if its deletion is still due. The row is locked and
+/// re-checked first, so a cancellation that commits before this wins, and one
+/// that arrives later waits and then finds the account gone.
+pub async fn finalize_deletion(
+    db: &DatabaseConnection,
+    storage: &dyn StorageBackend,
+    user_id: i32,
+    mode: DeletionMode,
+    cutoff: NaiveDateTime
+) -> Result<Option<Uuid>, DbErr> {
+    let txn = db.begin().await?;
+
+    let Some(user) = entity::user::Entity
+        ::find_by_id(user_id)
+        .filter(entity::user::Column::DeletionRequestedAt.lt(cutoff))
+        .filter(entity::user::Column::DeletedAt.is_null())
+        .lock_exclusive()
+        .one(&txn).await? else {
+        return Ok(None);
+    };
+
+    let uuid = user.uuid;
+    let orphaned_objects = delete_account(&txn, user, mode).await?;
+    events::record(&txn, &(DomainEvent::UserDeleted { user_id, uuid })).await?;
+    txn.commit().await?;
+    storage::delete_objects(storage, orphaned_objects).await;
+
+    Ok(Some(uuid))
+}
+
+/// Removes or reassigns the user's content, scrubs their personal data and
+/// revokes their tokens. Callers own the transaction so this runs atomically, and
+/// should delete the returned storage keys once it has committed.
+pub async fn delete_account(
+    txn: &DatabaseTransaction,
+    user: entity::user::Model,
+    mode: DeletionMode
+) -> Result<Vec<String>, DbErr> {
+    use entity::{ bookmark_collection, comment, email_outbox, post, upload, user as user_entity };
+
+    let mut orphaned_objects = Vec::new();
+
+    match mode {
+        DeletionMode::Cascade => {
+            let own_posts = Query::select()
+                .column(post::Column::Id)
+                .from(post::Entity)
+                .and_where(post::Column::UserId.eq(user.id))
+                .to_owned();
+
+            comment::Entity
+                ::delete_many()
+                .filter(
+                    Condition::any()
+                        .add(comment::Column::UserId.eq(user.id))
+                        .add(comment::Column::PostId.in_subquery(own_posts))
+                )
+                .exec(txn).await?;
+
+            post::Entity::delete_many().filter(post::Column::UserId.eq(user.id)).exec(txn).await?;
+
+            let own_uploads = Query::select()
+                .column(upload::Column::Id)
+                .from(upload::Entity)
+                .and_where(upload::Column::UserId.eq(user.id))
+                .to_owned();
+            orphaned_objects = upload_object_keys(txn, own_uploads).await?;
+
+            upload::Entity::delete_many().filter(upload::Column::UserId.eq(user.id)).exec(txn).await?;
+
+            delete_orphaned_reactions(txn).await?;
+            delete_orphaned_links(txn).await?;
+        }
+        DeletionMode::Reassign => {
+            let tombstone = tombstone_user(txn).await?;
+
+            comment::Entity
+                ::update_many()
+                .col_expr(comment::Column::UserId, Expr::value(tombstone.id))
+                .filter(comment::Column::UserId.eq(user.id))
+                .exec(txn).await?;
+
+            post::Entity
+                ::update_many()
+                .col_expr(post::Column::UserId, Expr::value(tombstone.id))
+                .filter(post::Column::UserId.eq(user.id))
+                .exec(txn).await?;
+
+            upload::Entity
+                ::update_many()
+                .col_expr(upload::Column::UserId, Expr::value(tombstone.id))
+                .filter(upload::Column::UserId.eq(user.id))
+                .exec(txn).await?;
+        }
+    }
+
+    // Bookmarks are private to the user, so they never outlive the account
+    bookmark_collection::Entity
+        ::delete_many()
+        .filter(bookmark_collection::Column::UserId.eq(user.id))
+        .exec(txn).await?;
+
+    // Queued and past mail carries the address being scrubbed below
+    email_outbox::Entity::delete_many().filter(email_outbox::Column::Recipient.eq(&user.email)).exec(txn).await?;
+
+    let now = Utc::now().naive_utc();
+    let mut active_user: user_entity::ActiveModel = user.clone().into();
+    active_user.name = Set("Deleted user".to_string());
+    active_user.email = Set(format!("deleted-{}@deleted.invalid", user.uuid));
+    active_user.handle = Set(None);
+    active_user.avatar_upload_id = Set(None);
+    active_user.password = Set(Uuid::new_v4().to_string());
+    active_user.tokens_revoked_at = Set(Some(now));
+    active_user.deletion_requested_at = Set(None);
+    active_user.deleted_at = Set(Some(now));
+    active_user.update(txn).await?;
+
+    // Without a handle, mentions of the user no longer link anywhere
+    let this_user = Query::select()
+        .column(user_entity::Column::Id)
+        .from(user_entity::Entity)
+        .and_where(user_entity::Column::Id.eq(user.id))
+        .to_owned();
+    render_posts(txn, posts_mentioning(txn, this_user).await?).await?;
+
+    Ok(orphaned_objects)
+}
+
+/// Finds or creates the placeholder account that reassigned content belongs to.
+async fn tombstone_user(txn: &DatabaseTransaction) -> Result<entity::user::Model, DbErr> {
+    let existing = entity::user::Entity
+        ::find()
+        .filter(entity::user::Column::Uuid.eq(TOMBSTONE_UUID))
+        .one(txn).await?;
+
+    if let Some(user) = existing {
+        return Ok(user);
+    }
+
+    entity::user::ActiveModel {
+        name: Set("Deleted user".to_string()),
+        email: Set("deleted-user@deleted.invalid".to_string()),
+        password: Set(Uuid::new_v4().to_string()),
+        uuid: Set(TOMBSTONE_UUID),
+        created_at: Set(Utc::now().naive_utc()),
+        ..Default::default()
+    }.insert(txn).await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use chrono::Duration;
+
+    use super::*;
+    use crate::{
+        storage::local::LocalStorage,
+        testing::{ self, create_comment, create_post, create_user },
+        utils::{ guard::authenticate, jwt::encode_jwt },
+    };
+
+    fn storage() -> LocalStorage {
+        LocalStorage::new(std::env::temp_dir().join("account-deletion-tests"))
+    }
+
+    fn cutoff() -> NaiveDateTime {
+        (Utc::now() - Duration::days(1)).naive_utc()
+    }
+
+    /// A user whose deletion request is older than `cutoff`.
+    async fn due_user(db: &DatabaseConnection) -> entity::user::Model {
+        let mut user: entity::user::ActiveModel = create_user(db).await.into();
+        user.deletion_requested_at = Set(Some((Utc::now() - Duration::days(30)).naive_utc()));
+        user.update(db).await.unwrap()
+    }
+
+    async fn find_user(db: &DatabaseConnection, id: i32) -> entity::user::Model {
+        entity::user::Entity::find_by_id(id).one(db).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn cascade_deletes_content_and_scrubs_the_account() {
+        let Some(db) = testing::database().await else {
+            return;
+        };
+        let user = due_user(&db).await;
+        let other = create_user(&db).await;
+        let post = create_post(&db, user.id).await;
+        let reply = create_comment(&db, other.id, post.id).await;
+        let other_post = create_post(&db, other.id).await;
+        let own_comment = create_comment(&db, user.id, other_post.id).await;
+
+        let finalized = finalize_deletion(&db, &storage(), user.id, DeletionMode::Cascade, cutoff()).await.unwrap();
+        assert_eq!(finalized, Some(user.uuid));
+
+        assert!(entity::post::Entity::find_by_id(post.id).one(&db).await.unwrap().is_none());
+        // replies go with the post, and the user's comments elsewhere go too
+        assert!(entity::comment::Entity::find_by_id(reply.id).one(&db).await.unwrap().is_none());
+        assert!(entity::comment::Entity::find_by_id(own_comment.id).one(&db).await.unwrap().is_none());
+        assert!(entity::post::Entity::find_by_id(other_post.id).one(&db).await.unwrap().is_some());
+
+        let scrubbed = find_user(&db, user.id).await;
+        assert_eq!(scrubbed.name, "Deleted user");
+        assert_ne!(scrubbed.email, user.email);
+        assert!(scrubbed.deleted_at.is_some());
+        assert!(scrubbed.deletion_requested_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn reassign_keeps_content_under_the_tombstone() {
+        let Some(db) = testing::database().await else {
+            return;
+        };
+        let user = due_user(&db).await;
+        let other = create_user(&db).await;
+        let post = create_post(&db, user.id).await;
+        let other_post = create_post(&db, other.id).await;
+        let comment = create_comment(&db, user.id, other_post.id).await;
+
+        finalize_deletion(&db, &storage(), user.id, DeletionMode::Reassign, cutoff()).await.unwrap();
+
+        let tombstone = entity::user::Entity
+            ::find()
+            .filter(entity::user::Column::Uuid.eq(TOMBSTONE_UUID))
+            .one(&db).await
+            .unwrap()
+            .unwrap();
+        let post = entity::post::Entity::find_by_id(post.id).one(&db).await.unwrap().unwrap();
+        let comment = entity::comment::Entity::find_by_id(comment.id).one(&db).await.unwrap().unwrap();
+        assert_eq!(post.user_id, tombstone.id);
+        assert_eq!(comment.user_id, tombstone.id);
+        assert!(find_user(&db, user.id).await.deleted_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn cancelled_or_pending_deletions_are_left_alone() {
+        let Some(db) = testing::database().await else {
+            return;
+        };
+        let cancelled = due_user(&db).await;
+        let mut active: entity::user::ActiveModel = cancelled.clone().into();
+        active.deletion_requested_at = Set(None);
+        active.update(&db).await.unwrap();
+
+        let pending = due_user(&db).await;
+        // requested after the cutoff, so still inside its grace period
+        let early_cutoff = (Utc::now() - Duration::days(60)).naive_utc();
+
+        let storage = storage();
+        let finalized = finalize_deletion(&db, &storage, cancelled.id, DeletionMode::Cascade, cutoff()).await.unwrap();
+        assert_eq!(finalized, None);
+        let finalized = finalize_deletion(&db, &storage, pending.id, DeletionMode::Cascade, early_cutoff).await.unwrap();
+        assert_eq!(finalized, None);
+
+        assert_eq!(find_user(&db, cancelled.id).await.email, cancelled.email);
+        assert!(find_user(&db, pending.id).await.deleted_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn tokens_issued_before_deletion_stop_working() {
+        let Some(db) = testing::database().await else {
+            return;
+        };
+        let user = due_user(&db).await;
+        let token = encode_jwt(user.email.clone()).unwrap();
+        assert!(authenticate(&db, &token).await.is_ok());
+
+        finalize_deletion(&db, &storage(), user.id, DeletionMode::Cascade, cutoff()).await.unwrap();
+
+        assert!(find_user(&db, user.id).await.tokens_revoked_at.is_some());
+        let rejected = authenticate(&db, &token).await.unwrap_err();
+        assert_eq!(rejected.status_code, StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::{ ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter };
+use serde::{ Deserialize, Serialize };
+use tracing::info;
+
+use crate::{ config, jobs::queue::Job, models::user_models::AppState };
+
+pub const PRUNE_SCHEDULE: &str = "0 10 * * * *";
+
+/// Deletes comment events older than `realtime.comment_event_retention_days`.
+/// The log only has to cover clients reconnecting to a stream. Runs on
+/// `PRUNE_SCHEDULE`.
+#[derive(Serialize, Deserialize)]
+pub struct PruneCommentEvents;
+
+#[async_trait]
+impl Job for PruneCommentEvents {
+    const KIND: &'static str = "comment_event.prune";
+
+    async fn run(self, state: &AppState) -> Result<(), String> {
+        let retention = chrono::Duration::days(config::get().realtime.comment_event_retention_days);
+        let pruned = prune_comment_events(&state.db, retention).await.map_err(|e|
+            format!("Pruning comment events failed: {}", e)
+        )?;
+        info!("Pruned {} comment events", pruned);
+        Ok(())
+    }
+}
+
+pub async fn prune_comment_events(db: &DatabaseConnection, retention: chrono::Duration) -> Result<u64, DbErr> {
+    let cutoff = (Utc::now() - retention).naive_utc();
+
+    let result = entity::comment_event::Entity
+        ::delete_many()
+        .filter(entity::comment_event::Column::CreatedAt.lt(cutoff))
+        .exec(db).await?;
+    Ok(result.rows_affected)
+}
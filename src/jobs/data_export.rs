@@ -0,0 +1,254 @@
+use std::{ io::Write, path::PathBuf };
+
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait,
+    DatabaseConnection,
+    EntityTrait,
+    QueryFilter,
+    QueryOrder,
+    TransactionTrait,
+};
+use serde::{ Deserialize, Serialize };
+use tracing::{ error, info };
+use uuid::Uuid;
+use zip::{ write::SimpleFileOptions, ZipWriter };
+
+use crate::{
+    config,
+    jobs::queue::Job,
+    mail::templates::EmailTemplate,
+    models::{
+        export_models::{ ExportAuditEventModel, ExportCommentModel, ExportPostModel, ExportUserModel },
+        upload_models::UploadModel,
+        user_models::AppState,
+    },
+    utils::signed_url,
+};
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_READY: &str = "ready";
+pub const STATUS_FAILED: &str = "failed";
+
+pub const CLEANUP_SCHEDULE: &str = "0 30 * * * *";
+
+/// Directory archives are written to, from `exports.dir`.
+pub fn export_dir() -> PathBuf {
+    PathBuf::from(&config::get().exports.dir)
+}
+
+pub fn download_path(id: Uuid) -> String {
+    format!("/exports/{}/download", id)
+}
+
+/// How long a finished archive can be downloaded, from `exports.link_ttl_hours`.
+pub fn link_ttl() -> chrono::Duration {
+    chrono::Duration::hours(config::get().exports.link_ttl_hours)
+}
+
+/// Builds the archive for a pending export and records the outcome on the export
+/// row, emailing the user a download link once it is ready. Not retried: a failed
+/// export is reported to the user, who can ask again.
+#[derive(Serialize, Deserialize)]
+pub struct BuildExport {
+    pub export_id: Uuid,
+}
+
+#[async_trait]
+impl Job for BuildExport {
+    const KIND: &'static str = "data_export.build";
+    const QUEUE: &'static str = "exports";
+    const MAX_ATTEMPTS: i32 = 1;
+
+    async fn run(self, state: &AppState) -> Result<(), String> {
+        let db = &state.db;
+        let Some(export) = entity::data_export::Entity
+            ::find_by_id(self.export_id)
+            .one(db).await
+            .map_err(|e| format!("Failed to load data export: {}", e))? else {
+            return Ok(());
+        };
+        let user = entity::user::Entity
+            ::find_by_id(export.user_id)
+            .one(db).await
+            .map_err(|e| format!("Failed to load user: {}", e))?
+            .ok_or_else(|| format!("User of data export {} is gone", export.id))?;
+
+        let id = export.id;
+        let result = build_export(db, &export, &user).await;
+
+        let mut active_export: entity::data_export::ActiveModel = export.into();
+        let now = Utc::now().naive_utc();
+        active_export.completed_at = Set(Some(now));
+        let path = match result {
+            Ok(path) => path,
+            Err(err) => {
+                active_export.status = Set(STATUS_FAILED.to_string());
+                active_export.error = Set(Some(err.clone()));
+                active_export.update(db).await.map_err(|e| format!("Failed to record data export {}: {}", id, e))?;
+                return Err(err);
+            }
+        };
+
+        let expires_at = now + link_ttl();
+        active_export.status = Set(STATUS_READY.to_string());
+        active_export.file_path = Set(Some(path.to_string_lossy().into_owned()));
+        active_export.expires_at = Set(Some(expires_at));
+
+        // The email goes out only if the export is recorded as ready, and vice versa
+        let record_error = |e: sea_orm::DbErr| format!("Failed to record data export {}: {}", id, e);
+        let txn = db.begin().await.map_err(record_error)?;
+        active_export.update(&txn).await.map_err(record_error)?;
+        let download_url = state.mailer.url(&signed_url::sign(&download_path(id), expires_at.and_utc()));
+        let context =
+            serde_json::json!({
+            "name": user.name,
+            "download_url": download_url,
+            "expires_in_hours": link_ttl().num_hours(),
+        });
+        state.mailer.queue(&txn, &user, EmailTemplate::ExportReady, context).await.map_err(record_error)?;
+        txn.commit().await.map_err(record_error)?;
+
+        info!("Data export {} is ready for user {}", id, user.uuid);
+        Ok(())
+    }
+}
+
+async fn build_export(
+    db: &DatabaseConnection,
+    export: &entity::data_export::Model,
+    user: &entity::user::Model
+) -> Result<PathBuf, String> {
+    let posts: Vec<ExportPostModel> = entity::post::Entity
+        ::find()
+        .filter(entity::post::Column::UserId.eq(user.id))
+        .all(db).await
+        .map_err(|e| format!("Failed to load posts: {}", e))?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    let comments: Vec<ExportCommentModel> = entity::comment::Entity
+        ::find()
+        .filter(entity::comment::Column::UserId.eq(user.id))
+        .all(db).await
+        .map_err(|e| format!("Failed to load comments: {}", e))?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    let uploads: Vec<UploadModel> = entity::upload::Entity
+        ::find()
+        .filter(entity::upload::Column::UserId.eq(user.id))
+        .all(db).await
+        .map_err(|e| format!("Failed to load uploads: {}", e))?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    let audit_events: Vec<ExportAuditEventModel> = entity::audit_event::Entity
+        ::find()
+        .filter(entity::audit_event::Column::UserId.eq(user.id))
+        .order_by_asc(entity::audit_event::Column::Id)
+        .all(db).await
+        .map_err(|e| format!("Failed to load audit events: {}", e))?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    let profile = ExportUserModel::from(user.clone());
+
+    let files = vec![
+        ("user.json".to_string(), to_json(&profile)?),
+        ("user.csv".to_string(), to_csv(std::slice::from_ref(&profile))?),
+        ("posts.json".to_string(), to_json(&posts)?),
+        ("posts.csv".to_string(), to_csv(&posts)?),
+        ("comments.json".to_string(), to_json(&comments)?),
+        ("comments.csv".to_string(), to_csv(&comments)?),
+        ("uploads.json".to_string(), to_json(&uploads)?),
+        ("uploads.csv".to_string(), to_csv(&uploads)?),
+        ("audit_events.json".to_string(), to_json(&audit_events)?),
+        ("audit_events.csv".to_string(), to_csv(&audit_events)?)
+    ];
+
+    let dir = export_dir();
+    let path = dir.join(format!("{}.zip", export.id));
+    tokio::task
+        ::spawn_blocking(move || write_archive(&dir, &path, files).map(|_| path))
+        .await
+        .map_err(|e| format!("Archive task failed: {}", e))?
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(value).map_err(|e| format!("Failed to serialize JSON: {}", e))
+}
+
+fn to_csv<T: Serialize>(rows: &[T]) -> Result<Vec<u8>, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for row in rows {
+        writer.serialize(row).map_err(|e| format!("Failed to serialize CSV: {}", e))?;
+    }
+    writer.into_inner().map_err(|e| format!("Failed to serialize CSV: {}", e))
+}
+
+fn write_archive(
+    dir: &std::path::Path,
+    path: &std::path::Path,
+    files: Vec<(String, Vec<u8>)>
+) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create export dir: {}", e))?;
+    let file = std::fs::File::create(path).map_err(|e| format!("Failed to create archive: {}", e))?;
+
+    let mut zip = ZipWriter::new(file);
+    for (name, contents) in files {
+        zip
+            .start_file(name, SimpleFileOptions::default())
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+        zip.write_all(&contents).map_err(|e| format!("Failed to write archive: {}", e))?;
+    }
+    zip.finish().map_err(|e| format!("Failed to write archive: {}", e))?;
+
+    Ok(())
+}
+
+/// Removes archives, and their rows, once their link has expired. Runs on
+/// `CLEANUP_SCHEDULE`.
+#[derive(Serialize, Deserialize)]
+pub struct CleanUpExports;
+
+#[async_trait]
+impl Job for CleanUpExports {
+    const KIND: &'static str = "data_export.clean_up";
+
+    async fn run(self, state: &AppState) -> Result<(), String> {
+        remove_expired_exports(&state.db).await.map_err(|e| format!("Cleaning up expired data exports failed: {}", e))
+    }
+}
+
+async fn remove_expired_exports(db: &DatabaseConnection) -> Result<(), String> {
+    let expired = entity::data_export::Entity
+        ::find()
+        .filter(entity::data_export::Column::ExpiresAt.lt(Utc::now().naive_utc()))
+        .all(db).await
+        .map_err(|e| e.to_string())?;
+
+    for export in expired {
+        if let Some(path) = &export.file_path
+            && let Err(err) = tokio::fs::remove_file(path).await
+            && err.kind() != std::io::ErrorKind::NotFound
+        {
+            error!("Failed to remove export archive {}: {:?}", path, err);
+            continue;
+        }
+
+        entity::data_export::Entity
+            ::delete_by_id(export.id)
+            .exec(db).await
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
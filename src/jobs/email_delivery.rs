@@ -0,0 +1,149 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::{
+    sea_query::Expr,
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait,
+    DatabaseConnection,
+    DbErr,
+    EntityTrait,
+    QueryFilter,
+};
+use serde::{ Deserialize, Serialize };
+use tracing::{ info, warn };
+
+use crate::{
+    config,
+    jobs::queue::Job,
+    mail::templates::EmailTemplate,
+    models::user_models::AppState,
+    services::mailer::is_suppressed,
+};
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_SENT: &str = "sent";
+/// The recipient unsubscribed between queueing and sending.
+pub const STATUS_SUPPRESSED: &str = "suppressed";
+/// Gave up after `MAX_ATTEMPTS`.
+pub const STATUS_FAILED: &str = "failed";
+
+const MAX_ATTEMPTS: i32 = 6;
+
+pub const PRUNE_SCHEDULE: &str = "0 50 * * * *";
+
+/// Sends one email from `email_outbox`, retried with the queue's backoff. The row
+/// keeps the attempt count and last error, and loses the body of an email with a
+/// secret link once it is sent or given up on.
+#[derive(Serialize, Deserialize)]
+pub struct SendEmail {
+    pub email_id: i64,
+}
+
+#[async_trait]
+impl Job for SendEmail {
+    const KIND: &'static str = "email.send";
+    const QUEUE: &'static str = "mail";
+    const MAX_ATTEMPTS: i32 = MAX_ATTEMPTS;
+
+    async fn run(self, state: &AppState) -> Result<(), String> {
+        let db = &state.db;
+        let email = entity::email_outbox::Entity
+            ::find_by_id(self.email_id)
+            .one(db).await
+            .map_err(|e| format!("Failed to load email: {}", e))?;
+        let Some(email) = email.filter(|email| email.status == STATUS_PENDING) else {
+            return Ok(());
+        };
+
+        let template = EmailTemplate::parse(&email.template);
+        let transactional = template.is_some_and(EmailTemplate::is_transactional);
+        let has_secret_link = template.is_some_and(EmailTemplate::has_secret_link);
+        let suppressed =
+            !transactional &&
+            is_suppressed(db, &email.recipient).await.map_err(|e| format!("Failed to check suppression: {}", e))?;
+
+        let id = email.id;
+        let attempts = email.attempts + 1;
+        let outcome = match suppressed {
+            true => Ok(()),
+            false => state.mailer.deliver(&email).await,
+        };
+
+        let now = Utc::now().naive_utc();
+        let mut active_email: entity::email_outbox::ActiveModel = email.into();
+        active_email.attempts = Set(attempts);
+        match &outcome {
+            Ok(()) if suppressed => {
+                active_email.status = Set(STATUS_SUPPRESSED.to_string());
+            }
+            Ok(()) => {
+                active_email.status = Set(STATUS_SENT.to_string());
+                active_email.last_error = Set(None);
+                active_email.sent_at = Set(Some(now));
+            }
+            Err(message) => {
+                active_email.last_error = Set(Some(message.clone()));
+                if attempts >= MAX_ATTEMPTS {
+                    warn!("Email {} failed for good after {} attempts", id, attempts);
+                    active_email.status = Set(STATUS_FAILED.to_string());
+                }
+            }
+        }
+        // Anyone reading the table could otherwise use the link while it is live
+        if has_secret_link && active_email.status.as_ref() != STATUS_PENDING {
+            active_email.html = Set(String::new());
+            active_email.text = Set(String::new());
+        }
+        let email = active_email.update(db).await.map_err(|e| format!("Failed to record email: {}", e))?;
+        if email.status == STATUS_SENT {
+            info!("Sent email {} ({})", id, email.template);
+        }
+
+        outcome
+    }
+}
+
+/// Deletes sent and suppressed mail older than `mail.retention_days`, since it
+/// holds addresses; failed mail stays for inspection. Runs on `PRUNE_SCHEDULE`.
+#[derive(Serialize, Deserialize)]
+pub struct PruneEmails;
+
+#[async_trait]
+impl Job for PruneEmails {
+    const KIND: &'static str = "email.prune";
+
+    async fn run(self, state: &AppState) -> Result<(), String> {
+        let retention = chrono::Duration::days(config::get().mail.retention_days);
+        let pruned = prune_emails(&state.db, retention).await.map_err(|e| format!("Pruning sent mail failed: {}", e))?;
+        info!("Pruned {} sent emails", pruned);
+        Ok(())
+    }
+}
+
+pub async fn prune_emails(db: &DatabaseConnection, retention: chrono::Duration) -> Result<u64, DbErr> {
+    use entity::email_outbox;
+
+    let cutoff = (Utc::now() - retention).naive_utc();
+
+    // Sending clears bodies with secret links; this catches any kept from before
+    let secret_link_templates = EmailTemplate::ALL
+        .into_iter()
+        .filter(|template| template.has_secret_link())
+        .map(EmailTemplate::name);
+    email_outbox::Entity
+        ::update_many()
+        .col_expr(email_outbox::Column::Html, Expr::value(""))
+        .col_expr(email_outbox::Column::Text, Expr::value(""))
+        .filter(email_outbox::Column::Template.is_in(secret_link_templates))
+        .filter(email_outbox::Column::Status.ne(STATUS_PENDING))
+        .filter(email_outbox::Column::Text.ne(""))
+        .exec(db).await?;
+
+    let result = email_outbox::Entity
+        ::delete_many()
+        .filter(email_outbox::Column::CreatedAt.lt(cutoff))
+        .filter(email_outbox::Column::Status.is_in([STATUS_SENT, STATUS_SUPPRESSED]))
+        .exec(db).await?;
+    Ok(result.rows_affected)
+}
@@ -0,0 +1,296 @@
+use std::io::Cursor;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::Utc;
+use image::{
+    codecs::jpeg::JpegEncoder,
+    imageops::FilterType,
+    DynamicImage,
+    ImageDecoder,
+    ImageFormat,
+    ImageReader,
+};
+use sea_orm::{ ActiveModelTrait, ActiveValue::Set, DatabaseConnection, EntityTrait };
+use serde::{ Deserialize, Serialize };
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{ config, jobs::queue::Job, models::user_models::AppState, storage::StorageBackend };
+
+const JPEG_QUALITY: u8 = 85;
+
+/// Where `create_upload` stores the file exactly as it was received. It may carry
+/// EXIF/GPS metadata, so it is never served and is removed once processed.
+pub fn original_key(id: Uuid) -> String {
+    format!("uploads/{}/original", id)
+}
+
+/// Upload purposes accepted by `create_upload`.
+pub const PURPOSE_POST: &str = "post";
+pub const PURPOSE_AVATAR: &str = "avatar";
+
+/// Widths thumbnails are generated at, from `uploads.thumbnail_widths`.
+pub fn thumbnail_widths() -> Vec<u32> {
+    config::get().uploads.thumbnail_widths.clone()
+}
+
+/// Square sizes avatars are cropped to, from `uploads.avatar_sizes`.
+pub fn avatar_sizes() -> Vec<u32> {
+    config::get().uploads.avatar_sizes.clone()
+}
+
+struct EncodedVariant {
+    label: String,
+    width: u32,
+    height: u32,
+    content_type: &'static str,
+    bytes: Vec<u8>,
+}
+
+struct ProcessedImage {
+    /// Re-encoded original without EXIF/GPS metadata, when the format carries any.
+    stripped: Option<Vec<u8>>,
+    width: u32,
+    height: u32,
+    blurhash: String,
+    variants: Vec<EncodedVariant>,
+}
+
+/// Processes an upload in the background; failures leave the original untouched
+/// and are retried.
+#[derive(Serialize, Deserialize)]
+pub struct ProcessUpload {
+    pub upload_id: Uuid,
+}
+
+#[async_trait]
+impl Job for ProcessUpload {
+    const KIND: &'static str = "upload.process";
+    const QUEUE: &'static str = "media";
+    const MAX_ATTEMPTS: i32 = 3;
+
+    async fn run(self, state: &AppState) -> Result<(), String> {
+        let Some(upload) = entity::upload::Entity
+            ::find_by_id(self.upload_id)
+            .one(&state.db).await
+            .map_err(|e| format!("Failed to load upload: {}", e))? else {
+            return Ok(());
+        };
+
+        process_upload(&state.db, state.storage.as_ref(), upload).await?;
+        info!("Processed upload {}", self.upload_id);
+        Ok(())
+    }
+}
+
+/// Stores a metadata-free copy of the original under a new key, stores resized
+/// and WebP variants and records the dimensions and blurhash on the upload row.
+/// Always reads from the original, so a retry never re-encodes its own output.
+pub async fn process_upload(
+    db: &DatabaseConnection,
+    storage: &dyn StorageBackend,
+    upload: entity::upload::Model
+) -> Result<(), String> {
+    if upload.processed_at.is_some() {
+        // Only removing the original can be left over from an earlier attempt
+        return remove_original(storage, &upload).await;
+    }
+
+    let original = storage
+        .get(&original_key(upload.id)).await?
+        .ok_or_else(|| format!("Original of upload {} is missing", upload.id))?;
+
+    let content_type = upload.content_type.clone();
+    let widths = thumbnail_widths();
+    let squares = match upload.purpose.as_str() {
+        PURPOSE_AVATAR => avatar_sizes(),
+        _ => Vec::new(),
+    };
+    let processed = tokio::task
+        ::spawn_blocking(move || process_image(&original, &content_type, &widths, &squares))
+        .await
+        .map_err(|e| format!("Image task failed: {}", e))??;
+
+    let mut size_bytes = upload.size_bytes;
+    let mut storage_key = upload.storage_key.clone();
+    if let Some(stripped) = processed.stripped {
+        size_bytes = stripped.len() as i64;
+        storage_key = format!("uploads/{}/stripped", upload.id);
+        storage.put(&storage_key, Bytes::from(stripped), &upload.content_type).await?;
+    }
+
+    let now = Utc::now().naive_utc();
+    for variant in processed.variants {
+        let storage_key = format!("uploads/{}/{}", upload.id, variant.label);
+        let variant_size = variant.bytes.len() as i64;
+        storage.put(&storage_key, Bytes::from(variant.bytes), variant.content_type).await?;
+
+        entity::upload_variant::Entity
+            ::insert(entity::upload_variant::ActiveModel {
+                upload_id: Set(upload.id),
+                label: Set(variant.label),
+                width: Set(variant.width as i32),
+                height: Set(variant.height as i32),
+                content_type: Set(variant.content_type.to_string()),
+                storage_key: Set(storage_key),
+                size_bytes: Set(variant_size),
+                created_at: Set(now),
+                ..Default::default()
+            })
+            .on_conflict(
+                sea_orm::sea_query::OnConflict
+                    ::columns([
+                        entity::upload_variant::Column::UploadId,
+                        entity::upload_variant::Column::Label,
+                    ])
+                    .update_columns([
+                        entity::upload_variant::Column::Width,
+                        entity::upload_variant::Column::Height,
+                        entity::upload_variant::Column::SizeBytes,
+                    ])
+                    .to_owned()
+            )
+            .exec(db).await
+            .map_err(|e| format!("Failed to record variant: {}", e))?;
+    }
+
+    let mut active_upload: entity::upload::ActiveModel = upload.into();
+    active_upload.storage_key = Set(storage_key);
+    active_upload.size_bytes = Set(size_bytes);
+    active_upload.width = Set(Some(processed.width as i32));
+    active_upload.height = Set(Some(processed.height as i32));
+    active_upload.blurhash = Set(Some(processed.blurhash));
+    active_upload.processed_at = Set(Some(now));
+    let upload = active_upload.update(db).await.map_err(|e| format!("Failed to record upload metadata: {}", e))?;
+
+    remove_original(storage, &upload).await
+}
+
+/// Deletes the received file once a stripped copy has replaced it. GIFs are
+/// served as uploaded, so their original stays.
+async fn remove_original(storage: &dyn StorageBackend, upload: &entity::upload::Model) -> Result<(), String> {
+    let key = original_key(upload.id);
+    if upload.storage_key == key {
+        return Ok(());
+    }
+    storage.delete(&key).await
+}
+
+fn process_image(
+    bytes: &[u8],
+    content_type: &str,
+    widths: &[u32],
+    squares: &[u32]
+) -> Result<ProcessedImage, String> {
+    let mut decoder = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to read image: {}", e))?
+        .into_decoder()
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    let orientation = decoder.orientation().map_err(|e| format!("Failed to read orientation: {}", e))?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|e|
+        format!("Failed to decode image: {}", e)
+    )?;
+    // Bake the EXIF orientation into the pixels since the metadata is being dropped
+    image.apply_orientation(orientation);
+
+    // GIF carries no EXIF and re-encoding would drop its animation frames
+    let stripped = match content_type {
+        "image/gif" => None,
+        _ => Some(encode(&image, content_type)?),
+    };
+
+    let thumbnail_type = match content_type {
+        "image/jpeg" => Some("image/jpeg"),
+        "image/png" | "image/gif" => Some("image/png"),
+        _ => None,
+    };
+
+    let mut variants = vec![EncodedVariant {
+        label: "webp".to_string(),
+        width: image.width(),
+        height: image.height(),
+        content_type: "image/webp",
+        bytes: encode(&image, "image/webp")?,
+    }];
+
+    for &width in widths {
+        if width == 0 || width >= image.width() {
+            continue;
+        }
+        let resized = image.resize(width, u32::MAX, FilterType::Lanczos3);
+
+        if let Some(thumbnail_type) = thumbnail_type {
+            variants.push(EncodedVariant {
+                label: format!("w{}", width),
+                width: resized.width(),
+                height: resized.height(),
+                content_type: thumbnail_type,
+                bytes: encode(&resized, thumbnail_type)?,
+            });
+        }
+        variants.push(EncodedVariant {
+            label: format!("w{}-webp", width),
+            width: resized.width(),
+            height: resized.height(),
+            content_type: "image/webp",
+            bytes: encode(&resized, "image/webp")?,
+        });
+    }
+
+    // Avatars are shown in fixed square slots, so crop rather than scale to a width
+    for &size in squares {
+        if size == 0 || size > image.width().min(image.height()) {
+            continue;
+        }
+        let cropped = image.resize_to_fill(size, size, FilterType::Lanczos3);
+
+        if let Some(thumbnail_type) = thumbnail_type {
+            variants.push(EncodedVariant {
+                label: format!("sq{}", size),
+                width: size,
+                height: size,
+                content_type: thumbnail_type,
+                bytes: encode(&cropped, thumbnail_type)?,
+            });
+        }
+        variants.push(EncodedVariant {
+            label: format!("sq{}-webp", size),
+            width: size,
+            height: size,
+            content_type: "image/webp",
+            bytes: encode(&cropped, "image/webp")?,
+        });
+    }
+
+    let preview = image.thumbnail(32, 32).to_rgba8();
+    let blurhash = blurhash
+        ::encode(4, 3, preview.width(), preview.height(), preview.as_raw())
+        .map_err(|e| format!("Failed to compute blurhash: {:?}", e))?;
+
+    Ok(ProcessedImage {
+        stripped,
+        width: image.width(),
+        height: image.height(),
+        blurhash,
+        variants,
+    })
+}
+
+fn encode(image: &DynamicImage, content_type: &str) -> Result<Vec<u8>, String> {
+    let mut buffer = Cursor::new(Vec::new());
+    let result = match content_type {
+        "image/jpeg" => {
+            let encoder = JpegEncoder::new_with_quality(&mut buffer, JPEG_QUALITY);
+            DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(encoder)
+        }
+        "image/png" => image.write_to(&mut buffer, ImageFormat::Png),
+        "image/webp" => DynamicImage::ImageRgba8(image.to_rgba8()).write_to(&mut buffer, ImageFormat::WebP),
+        other => {
+            return Err(format!("Cannot encode {}", other));
+        }
+    };
+    result.map_err(|e| format!("Failed to encode {}: {}", content_type, e))?;
+    Ok(buffer.into_inner())
+}
@@ -0,0 +1,62 @@
+pub mod account_deletion;
+pub mod comment_events;
+pub mod data_export;
+pub mod email_delivery;
+pub mod image_processing;
+pub mod notification_digest;
+pub mod outbox;
+pub mod purge;
+pub mod queue;
+pub mod webhook_delivery;
+
+use crate::services::subscribers::{
+    MailSubscriber,
+    NotificationSubscriber,
+    RealtimeSubscriber,
+    WebhookSubscriber,
+};
+
+use self::{
+    account_deletion::FinalizeAccountDeletions,
+    comment_events::PruneCommentEvents,
+    data_export::{ BuildExport, CleanUpExports },
+    email_delivery::{ PruneEmails, SendEmail },
+    image_processing::ProcessUpload,
+    notification_digest::SendNotificationDigests,
+    outbox::{ OutboxDispatcher, PruneOutbox },
+    purge::PurgeSoftDeleted,
+    queue::{ Job, JobRunner, PruneJobs },
+    webhook_delivery::{ DeliverWebhook, PruneWebhookDeliveries },
+};
+
+/// Every job kind this server runs and every recurring job. A kind has to be
+/// registered here before anything enqueues it.
+pub fn job_runner() -> JobRunner {
+    JobRunner::default()
+        .register::<ProcessUpload>()
+        .register::<BuildExport>()
+        .register::<DeliverWebhook>()
+        .register::<SendEmail>()
+        .concurrency(ProcessUpload::QUEUE, 2)
+        .concurrency(BuildExport::QUEUE, 1)
+        .concurrency(DeliverWebhook::QUEUE, 8)
+        .concurrency(SendEmail::QUEUE, 4)
+        .schedule("purge", &purge::schedule(), PurgeSoftDeleted)
+        .schedule("account_deletion", account_deletion::FINALIZE_SCHEDULE, FinalizeAccountDeletions)
+        .schedule("export_cleanup", data_export::CLEANUP_SCHEDULE, CleanUpExports)
+        .schedule("notification_digest", notification_digest::DIGEST_SCHEDULE, SendNotificationDigests)
+        .schedule("comment_event_prune", comment_events::PRUNE_SCHEDULE, PruneCommentEvents)
+        .schedule("webhook_delivery_prune", webhook_delivery::PRUNE_SCHEDULE, PruneWebhookDeliveries)
+        .schedule("job_prune", queue::PRUNE_SCHEDULE, PruneJobs)
+        .schedule("outbox_prune", outbox::PRUNE_SCHEDULE, PruneOutbox)
+        .schedule("email_prune", email_delivery::PRUNE_SCHEDULE, PruneEmails)
+}
+
+/// Every in-process subscriber to domain events recorded in the outbox.
+pub fn outbox_dispatcher() -> OutboxDispatcher {
+    OutboxDispatcher::default()
+        .subscribe(NotificationSubscriber)
+        .subscribe(RealtimeSubscriber)
+        .subscribe(WebhookSubscriber)
+        .subscribe(MailSubscriber)
+}
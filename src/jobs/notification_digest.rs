@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{ Duration, Utc };
+use sea_orm::{
+    ColumnTrait,
+    DatabaseConnection,
+    DbErr,
+    EntityTrait,
+    QueryFilter,
+    QueryOrder,
+    QuerySelect,
+    TransactionTrait,
+};
+use serde::{ Deserialize, Serialize };
+use tracing::info;
+
+use crate::{
+    jobs::queue::Job,
+    mail::templates::EmailTemplate,
+    models::user_models::AppState,
+    services::{ mailer::Mailer, notifier::NotificationKind },
+    utils::soft_delete::SoftDelete,
+};
+
+/// Daily at 08:00 UTC; each digest covers the day before it.
+pub const DIGEST_SCHEDULE: &str = "0 0 8 * * *";
+/// Notifications listed by name; the rest are only counted.
+const MAX_DIGEST_ITEMS: usize = 10;
+
+/// Emails every user with unread notifications from the last day a summary of
+/// them. Runs on `DIGEST_SCHEDULE`.
+#[derive(Serialize, Deserialize)]
+pub struct SendNotificationDigests;
+
+#[async_trait]
+impl Job for SendNotificationDigests {
+    const KIND: &'static str = "notification.digest";
+
+    async fn run(self, state: &AppState) -> Result<(), String> {
+        send_digests(&state.db, &state.mailer).await.map_err(|e| format!("Sending digests failed: {}", e))
+    }
+}
+
+pub async fn send_digests(db: &DatabaseConnection, mailer: &Mailer) -> Result<(), DbErr> {
+    let since = (Utc::now() - Duration::days(1)).naive_utc();
+
+    let recipients: Vec<i32> = entity::notification::Entity
+        ::find()
+        .select_only()
+        .column(entity::notification::Column::UserId)
+        .distinct()
+        .filter(entity::notification::Column::ReadAt.is_null())
+        .filter(entity::notification::Column::CreatedAt.gte(since))
+        .into_tuple()
+        .all(db).await?;
+
+    let mut sent = 0;
+    for user_id in recipients {
+        let Some(user) = entity::user::Entity
+            ::find_active()
+            .filter(entity::user::Column::Id.eq(user_id))
+            .one(db).await? else {
+            continue;
+        };
+
+        let unread = entity::notification::Entity
+            ::find()
+            .filter(entity::notification::Column::UserId.eq(user.id))
+            .filter(entity::notification::Column::ReadAt.is_null())
+            .filter(entity::notification::Column::CreatedAt.gte(since))
+            .order_by_desc(entity::notification::Column::Id)
+            .all(db).await?;
+
+        let actor_ids: Vec<i32> = unread
+            .iter()
+            .take(MAX_DIGEST_ITEMS)
+            .filter_map(|notification| notification.actor_id)
+            .collect();
+        let actors: HashMap<i32, String> = entity::user::Entity
+            ::find()
+            .filter(entity::user::Column::Id.is_in(actor_ids))
+            .all(db).await?
+            .into_iter()
+            .map(|actor| (actor.id, actor.name))
+            .collect();
+
+        let items: Vec<serde_json::Value> = unread
+            .iter()
+            .take(MAX_DIGEST_ITEMS)
+            .map(|notification| {
+                let actor = notification.actor_id
+                    .and_then(|actor_id| actors.get(&actor_id))
+                    .map(String::as_str)
+                    .unwrap_or("Someone");
+                serde_json::json!({"summary": summary(notification, actor)})
+            })
+            .collect();
+
+        let context =
+            serde_json::json!({
+            "name": user.name,
+            "unread": unread.len(),
+            "items": items,
+        });
+        let txn = db.begin().await?;
+        if mailer.queue(&txn, &user, EmailTemplate::NotificationDigest, context).await?.is_some() {
+            sent += 1;
+        }
+        txn.commit().await?;
+    }
+
+    if sent > 0 {
+        info!("Queued {} notification digests", sent);
+    }
+    Ok(())
+}
+
+fn summary(notification: &entity::notification::Model, actor: &str) -> String {
+    let target = notification.target_type.as_deref().unwrap_or("post");
+    match NotificationKind::parse(&notification.kind) {
+        Some(NotificationKind::Comment) => format!("{} commented on your post", actor),
+        Some(NotificationKind::Reaction) => format!("{} reacted to your {}", actor, target),
+        Some(NotificationKind::Follow) => format!("{} started following you", actor),
+        Some(NotificationKind::Mention) => format!("{} mentioned you in a {}", actor, target),
+        None => format!("{} did something", actor),
+    }
+}
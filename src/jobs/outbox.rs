@@ -0,0 +1,232 @@
+use std::{ collections::HashSet, sync::Arc, time::Duration };
+
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::{
+    sea_query::{ Expr, LockBehavior, LockType },
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait,
+    DatabaseConnection,
+    DbErr,
+    EntityTrait,
+    QueryFilter,
+    QueryOrder,
+    QuerySelect,
+    TransactionTrait,
+};
+use serde::{ Deserialize, Serialize };
+use tracing::{ error, field, info, info_span, warn, Instrument, Span };
+
+use crate::{
+    config,
+    jobs::queue::{ backoff, Job },
+    models::user_models::AppState,
+    services::events::{ DomainEvent, EventSubscriber },
+    telemetry::traces,
+};
+
+const BATCH_SIZE: u64 = 100;
+const MAX_ATTEMPTS: i32 = 10;
+/// How long a claimed batch stays reserved; an instance that dies mid-batch leaves
+/// its events to be picked up again once this runs out.
+const LEASE: chrono::Duration = chrono::Duration::minutes(5);
+
+pub const PRUNE_SCHEDULE: &str = "0 40 * * * *";
+
+/// Reads committed events from the `outbox` and hands each to every registered
+/// subscriber. An event is marked dispatched once all subscribers have handled it;
+/// only the subscribers that failed see it again, after the job queue's backoff.
+/// After `MAX_ATTEMPTS` it is marked dispatched anyway, keeping the subscribers
+/// still owed it and the last error.
+#[derive(Default)]
+pub struct OutboxDispatcher {
+    subscribers: Vec<Arc<dyn EventSubscriber>>,
+}
+
+impl OutboxDispatcher {
+    pub fn subscribe(mut self, subscriber: impl EventSubscriber) -> Self {
+        self.subscribers.push(Arc::new(subscriber));
+        self
+    }
+
+    /// Spawns the dispatcher, polling every `jobs.outbox_poll_interval_ms`. On
+    /// shutdown it finishes the batch in hand and stops.
+    pub fn start(self, state: AppState) {
+        let poll_interval = Duration::from_millis(config::get().jobs.outbox_poll_interval_ms);
+        info!("Starting outbox dispatcher with {} subscribers", self.subscribers.len());
+        let health = state.health.clone();
+        health.spawn_worker("outbox_dispatcher", run(state, self.subscribers, poll_interval));
+    }
+}
+
+async fn run(state: AppState, subscribers: Vec<Arc<dyn EventSubscriber>>, poll_interval: Duration) {
+    let shutdown = state.health.shutdown_token();
+    let mut ticker = tokio::time::interval(poll_interval);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = shutdown.cancelled() => break,
+        }
+        // Drain the backlog before waiting for the next tick
+        while !shutdown.is_cancelled() {
+            let claimed = match claim(&state.db).await {
+                Ok(claimed) => claimed,
+                Err(err) => {
+                    error!("Claiming outbox events failed: {:?}", err);
+                    break;
+                }
+            };
+            let exhausted = (claimed.len() as u64) < BATCH_SIZE;
+
+            for entry in claimed {
+                let id = entry.id;
+                let span = dispatch_span(&entry);
+                if let Err(err) = dispatch(&state, &subscribers, entry).instrument(span).await {
+                    error!("Failed to record dispatch of outbox event {}: {:?}", id, err);
+                }
+            }
+            if exhausted {
+                break;
+            }
+        }
+    }
+    info!("Outbox dispatcher stopped");
+}
+
+/// Reserves the oldest due events for `LEASE`. `SKIP LOCKED` lets every instance
+/// poll the outbox without dispatching an event twice.
+async fn claim(db: &DatabaseConnection) -> Result<Vec<entity::outbox::Model>, DbErr> {
+    let now = Utc::now().naive_utc();
+    let txn = db.begin().await?;
+
+    let due = entity::outbox::Entity
+        ::find()
+        .filter(entity::outbox::Column::DispatchedAt.is_null())
+        .filter(entity::outbox::Column::AvailableAt.lte(now))
+        .order_by_asc(entity::outbox::Column::Id)
+        .limit(BATCH_SIZE)
+        .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+        .all(&txn).await?;
+    if due.is_empty() {
+        return Ok(due);
+    }
+
+    let ids: Vec<i64> = due
+        .iter()
+        .map(|entry| entry.id)
+        .collect();
+    entity::outbox::Entity
+        ::update_many()
+        .col_expr(entity::outbox::Column::AvailableAt, Expr::value(now + LEASE))
+        .filter(entity::outbox::Column::Id.is_in(ids))
+        .exec(&txn).await?;
+    txn.commit().await?;
+
+    Ok(due)
+}
+
+/// Each attempt gets its own span, in the trace of the request that recorded the
+/// event, so the jobs subscribers queue join that trace too.
+fn dispatch_span(entry: &entity::outbox::Model) -> Span {
+    let span =
+        info_span!(
+        "outbox.dispatch",
+        event.id = entry.id,
+        event.kind = %entry.event_type,
+        otel.name = %format!("dispatch {}", entry.event_type),
+        otel.kind = "consumer",
+        otel.status_code = field::Empty,
+    );
+    traces::continue_trace(&span, entry.trace_context.as_deref());
+    span
+}
+
+async fn dispatch(
+    state: &AppState,
+    subscribers: &[Arc<dyn EventSubscriber>],
+    entry: entity::outbox::Model
+) -> Result<(), DbErr> {
+    let now = Utc::now().naive_utc();
+    let id = entry.id;
+    let attempts = entry.attempts + 1;
+    let pending: Option<HashSet<String>> = entry.pending_subscribers
+        .as_deref()
+        .map(|names| names.split(',').map(str::to_string).collect());
+
+    let mut failed = Vec::new();
+    let mut errors = Vec::new();
+    match serde_json::from_str::<DomainEvent>(&entry.payload) {
+        Ok(event) => {
+            for subscriber in subscribers {
+                if pending.as_ref().is_some_and(|pending| !pending.contains(subscriber.name())) {
+                    continue;
+                }
+                if let Err(err) = subscriber.handle(state, &event).await {
+                    failed.push(subscriber.name());
+                    errors.push(format!("{}: {}", subscriber.name(), err));
+                }
+            }
+        }
+        // Retrying cannot fix an event this build does not understand
+        Err(err) => {
+            error!("Dropping unreadable outbox event {} ({}): {}", id, entry.event_type, err);
+            errors.push(format!("Unreadable payload: {}", err));
+        }
+    }
+
+    if !errors.is_empty() {
+        Span::current().record("otel.status_code", "ERROR");
+    }
+    let mut active_entry: entity::outbox::ActiveModel = entry.into();
+    active_entry.attempts = Set(attempts);
+    if errors.is_empty() {
+        active_entry.pending_subscribers = Set(None);
+        active_entry.last_error = Set(None);
+        active_entry.dispatched_at = Set(Some(now));
+    } else {
+        active_entry.last_error = Set(Some(errors.join("; ")));
+        if !failed.is_empty() {
+            active_entry.pending_subscribers = Set(Some(failed.join(",")));
+        }
+        if failed.is_empty() || attempts >= MAX_ATTEMPTS {
+            warn!("Giving up on outbox event {} after {} attempts", id, attempts);
+            active_entry.dispatched_at = Set(Some(now));
+        } else {
+            warn!("Outbox event {} failed on attempt {}: {}", id, attempts, errors.join("; "));
+            active_entry.available_at = Set(now + backoff(attempts));
+        }
+    }
+    active_entry.update(&state.db).await?;
+
+    Ok(())
+}
+
+/// Deletes events dispatched more than `jobs.outbox_retention_days` ago. Events
+/// that were given up on, or had errors, stay for inspection. Runs on
+/// `PRUNE_SCHEDULE`.
+#[derive(Serialize, Deserialize)]
+pub struct PruneOutbox;
+
+#[async_trait]
+impl Job for PruneOutbox {
+    const KIND: &'static str = "outbox.prune";
+
+    async fn run(self, state: &AppState) -> Result<(), String> {
+        let retention = chrono::Duration::days(config::get().jobs.outbox_retention_days);
+        let pruned = prune_outbox(&state.db, retention).await.map_err(|e| format!("Pruning the outbox failed: {}", e))?;
+        info!("Pruned {} dispatched outbox events", pruned);
+        Ok(())
+    }
+}
+
+pub async fn prune_outbox(db: &DatabaseConnection, retention: chrono::Duration) -> Result<u64, DbErr> {
+    let cutoff = (Utc::now() - retention).naive_utc();
+
+    let result = entity::outbox::Entity
+        ::delete_many()
+        .filter(entity::outbox::Column::DispatchedAt.lt(cutoff))
+        .filter(entity::outbox::Column::LastError.is_null())
+        .exec(db).await?;
+    Ok(result.rows_affected)
+}
@@ -0,0 +1,278 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::{
+    sea_query::{ Condition, Query, SelectStatement },
+    ColumnTrait,
+    ConnectionTrait,
+    DatabaseConnection,
+    DbErr,
+    EntityTrait,
+    QueryFilter,
+    QuerySelect,
+    TransactionTrait,
+};
+use serde::{ Deserialize, Serialize };
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    config,
+    jobs::{
+        image_processing::original_key,
+        queue::Job,
+    },
+    models::user_models::AppState,
+    services::{
+        mentions::delete_orphaned_links,
+        posts::{ posts_mentioning, render_posts },
+        reactions::delete_orphaned_reactions,
+    },
+    storage::{ self, StorageBackend },
+};
+
+/// When purges run, as a cron expression from `jobs.purge_schedule` (hourly by default).
+pub fn schedule() -> String {
+    config::get().jobs.purge_schedule.clone()
+}
+
+/// Hard-deletes rows that were soft-deleted more than `jobs.purge_retention_days` ago.
+#[derive(Serialize, Deserialize)]
+pub struct PurgeSoftDeleted;
+
+#[async_trait]
+impl Job for PurgeSoftDeleted {
+    const KIND: &'static str = "purge.soft_deleted";
+
+    async fn run(self, state: &AppState) -> Result<(), String> {
+        let retention = config::get().jobs.purge_retention_days;
+
+        purge_soft_deleted(&state.db, state.storage.as_ref(), chrono::Duration::days(retention)).await.map_err(
+            |e| format!("Purge of soft-deleted rows failed: {}", e)
+        )?;
+        info!("Purged soft-deleted rows older than {} days", retention);
+        Ok(())
+    }
+}
+
+/// Hard-deletes rows past the retention window in dependency order: comments,
+/// then posts, then uploads (their variants cascade), then users. Content
+/// belonging to a purged parent goes with it even if it was never soft-deleted
+/// itself, since those foreign keys do not cascade. Stored files, originals and
+/// variants alike, are removed once the transaction has committed.
+pub async fn purge_soft_deleted(
+    db: &DatabaseConnection,
+    storage: &dyn StorageBackend,
+    retention: chrono::Duration
+) -> Result<(), DbErr> {
+    use entity::{ comment, post, upload, user };
+
+    let cutoff = (Utc::now() - retention).naive_utc();
+    let txn = db.begin().await?;
+
+    let expired_users = Query::select()
+        .column(user::Column::Id)
+        .from(user::Entity)
+        .and_where(user::Column::DeletedAt.lt(cutoff))
+        .to_owned();
+
+    let expired_posts = Query::select()
+        .column(post::Column::Id)
+        .from(post::Entity)
+        .cond_where(
+            Condition::any()
+                .add(post::Column::DeletedAt.lt(cutoff))
+                .add(post::Column::UserId.in_subquery(expired_users.clone()))
+        )
+        .to_owned();
+
+    comment::Entity
+        ::delete_many()
+        .filter(
+            Condition::any()
+                .add(comment::Column::DeletedAt.lt(cutoff))
+                .add(comment::Column::PostId.in_subquery(expired_posts))
+                .add(comment::Column::UserId.in_subquery(expired_users.clone()))
+        )
+        .exec(&txn).await?;
+
+    post::Entity
+        ::delete_many()
+        .filter(
+            Condition::any()
+                .add(post::Column::DeletedAt.lt(cutoff))
+                .add(post::Column::UserId.in_subquery(expired_users.clone()))
+        )
+        .exec(&txn).await?;
+
+    let expired_uploads = Query::select()
+        .column(upload::Column::Id)
+        .from(upload::Entity)
+        .and_where(upload::Column::UserId.in_subquery(expired_users.clone()))
+        .to_owned();
+    let storage_keys = upload_object_keys(&txn, expired_uploads).await?;
+
+    upload::Entity
+        ::delete_many()
+        .filter(upload::Column::UserId.in_subquery(expired_users.clone()))
+        .exec(&txn).await?;
+
+    // Mentions of purged users go with them, and so must their links in posts
+    let stale_posts = posts_mentioning(&txn, expired_users).await?;
+    user::Entity::delete_many().filter(user::Column::DeletedAt.lt(cutoff)).exec(&txn).await?;
+
+    delete_orphaned_reactions(&txn).await?;
+    delete_orphaned_links(&txn).await?;
+    render_posts(&txn, stale_posts).await?;
+
+    txn.commit().await?;
+
+    storage::delete_objects(storage, storage_keys).await;
+
+    Ok(())
+}
+
+/// Storage keys of the uploads selected by `uploads`, of their originals and of
+/// every variant generated from them. Read them before deleting the rows, since
+/// the variants' rows go with their upload.
+pub async fn upload_object_keys<C: ConnectionTrait>(db: &C, uploads: SelectStatement) -> Result<Vec<String>, DbErr> {
+    let stored: Vec<(Uuid, String)> = entity::upload::Entity
+        ::find()
+        .select_only()
+        .column(entity::upload::Column::Id)
+        .column(entity::upload::Column::StorageKey)
+        .filter(entity::upload::Column::Id.in_subquery(uploads.clone()))
+        .into_tuple()
+        .all(db).await?;
+    let mut keys = Vec::new();
+    for (id, storage_key) in stored {
+        // processing normally removes the original, unless that last step failed
+        let original = original_key(id);
+        if storage_key != original {
+            keys.push(original);
+        }
+        keys.push(storage_key);
+    }
+    let variant_keys: Vec<String> = entity::upload_variant::Entity
+        ::find()
+        .select_only()
+        .column(entity::upload_variant::Column::StorageKey)
+        .filter(entity::upload_variant::Column::UploadId.in_subquery(uploads))
+        .into_tuple()
+        .all(db).await?;
+    keys.extend(variant_keys);
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use sea_orm::{ ActiveModelTrait, ActiveValue::Set };
+
+    use super::*;
+    use crate::{
+        storage::local::LocalStorage,
+        testing::{ self, create_comment, create_post, create_user },
+    };
+
+    const RETENTION: chrono::Duration = chrono::Duration::days(30);
+
+    async fn soft_delete_user(db: &DatabaseConnection, user: entity::user::Model, age: chrono::Duration) {
+        let mut active_user: entity::user::ActiveModel = user.into();
+        active_user.deleted_at = Set(Some((Utc::now() - age).naive_utc()));
+        active_user.update(db).await.unwrap();
+    }
+
+    async fn create_stored_upload(db: &DatabaseConnection, storage: &LocalStorage, user_id: i32) -> entity::upload::Model {
+        let id = Uuid::new_v4();
+        let storage_key = format!("uploads/{}/stripped", id);
+        storage.put(&storage_key, Bytes::from_static(b"image"), "image/png").await.unwrap();
+        storage.put(&original_key(id), Bytes::from_static(b"original"), "image/png").await.unwrap();
+
+        let upload = (entity::upload::ActiveModel {
+            id: Set(id),
+            user_id: Set(user_id),
+            storage_key: Set(storage_key),
+            content_type: Set("image/png".to_string()),
+            size_bytes: Set(5),
+            created_at: Set(Utc::now().naive_utc()),
+            purpose: Set("post".to_string()),
+            ..Default::default()
+        })
+            .insert(db).await
+            .unwrap();
+
+        let variant_key = format!("uploads/{}/w160", id);
+        storage.put(&variant_key, Bytes::from_static(b"thumbnail"), "image/png").await.unwrap();
+        (entity::upload_variant::ActiveModel {
+            upload_id: Set(id),
+            label: Set("w160".to_string()),
+            width: Set(160),
+            height: Set(160),
+            content_type: Set("image/png".to_string()),
+            storage_key: Set(variant_key),
+            size_bytes: Set(9),
+            created_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        })
+            .insert(db).await
+            .unwrap();
+
+        upload
+    }
+
+    #[tokio::test]
+    async fn purges_an_expired_user_with_everything_that_references_them() {
+        let Some(db) = testing::database().await else {
+            return;
+        };
+        let storage = LocalStorage::new(std::env::temp_dir().join("purge-tests"));
+        let user = create_user(&db).await;
+        let other = create_user(&db).await;
+        let post = create_post(&db, user.id).await;
+        // never soft-deleted, but it can only go once the post it replies to does
+        let reply = create_comment(&db, other.id, post.id).await;
+        let other_post = create_post(&db, other.id).await;
+        let comment = create_comment(&db, user.id, other_post.id).await;
+        let upload = create_stored_upload(&db, &storage, user.id).await;
+        soft_delete_user(&db, user.clone(), RETENTION * 2).await;
+
+        purge_soft_deleted(&db, &storage, RETENTION).await.unwrap();
+
+        assert!(entity::user::Entity::find_by_id(user.id).one(&db).await.unwrap().is_none());
+        assert!(entity::post::Entity::find_by_id(post.id).one(&db).await.unwrap().is_none());
+        assert!(entity::comment::Entity::find_by_id(reply.id).one(&db).await.unwrap().is_none());
+        assert!(entity::comment::Entity::find_by_id(comment.id).one(&db).await.unwrap().is_none());
+        assert!(entity::upload::Entity::find_by_id(upload.id).one(&db).await.unwrap().is_none());
+        for key in [upload.storage_key, original_key(upload.id), format!("uploads/{}/w160", upload.id)] {
+            assert!(storage.get(&key).await.unwrap().is_none(), "{} is still stored", key);
+        }
+
+        assert!(entity::user::Entity::find_by_id(other.id).one(&db).await.unwrap().is_some());
+        assert!(entity::post::Entity::find_by_id(other_post.id).one(&db).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn keeps_rows_deleted_within_the_retention_window() {
+        let Some(db) = testing::database().await else {
+            return;
+        };
+        let storage = LocalStorage::new(std::env::temp_dir().join("purge-tests"));
+        let user = create_user(&db).await;
+        let post = create_post(&db, user.id).await;
+        soft_delete_user(&db, user.clone(), RETENTION / 2).await;
+
+        let expired = create_user(&db).await;
+        let expired_post = create_post(&db, expired.id).await;
+        let mut active_post: entity::post::ActiveModel = expired_post.clone().into();
+        active_post.deleted_at = Set(Some((Utc::now() - RETENTION * 2).naive_utc()));
+        active_post.update(&db).await.unwrap();
+
+        purge_soft_deleted(&db, &storage, RETENTION).await.unwrap();
+
+        assert!(entity::user::Entity::find_by_id(user.id).one(&db).await.unwrap().is_some());
+        assert!(entity::post::Entity::find_by_id(post.id).one(&db).await.unwrap().is_some());
+        // an expired post goes on its own, leaving its active author
+        assert!(entity::post::Entity::find_by_id(expired_post.id).one(&db).await.unwrap().is_none());
+        assert!(entity::user::Entity::find_by_id(expired.id).one(&db).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,490 @@
+use std::{ collections::HashMap, future::Future, pin::Pin, str::FromStr, sync::Arc, time::Duration };
+
+use async_trait::async_trait;
+use chrono::{ NaiveDateTime, Utc };
+use sea_orm::{
+    sea_query::{ Expr, LockBehavior, LockType, OnConflict },
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait,
+    ConnectionTrait,
+    DatabaseConnection,
+    DbErr,
+    EntityTrait,
+    QueryFilter,
+    QueryOrder,
+    QuerySelect,
+    TransactionTrait,
+};
+use serde::{ de::DeserializeOwned, Deserialize, Serialize };
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+use tracing::{ error, field, info, info_span, warn, Instrument, Span };
+
+use crate::{ config, models::user_models::AppState, telemetry::traces };
+
+pub const STATUS_QUEUED: &str = "queued";
+pub const STATUS_RUNNING: &str = "running";
+pub const STATUS_SUCCEEDED: &str = "succeeded";
+/// Out of attempts; only an admin retry runs it again.
+pub const STATUS_FAILED: &str = "failed";
+
+pub const DEFAULT_QUEUE: &str = "default";
+
+const DEFAULT_CONCURRENCY: usize = 4;
+const BASE_BACKOFF_SECS: i64 = 10;
+const MAX_BACKOFF_SECS: i64 = 6 * 60 * 60;
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(1);
+const REAPER_INTERVAL: Duration = Duration::from_secs(60);
+
+pub const PRUNE_SCHEDULE: &str = "0 30 * * * *";
+
+/// A unit of background work. The job itself is the payload: it is stored as JSON
+/// in `job.payload` and handed back to `run` by whichever instance claims it.
+#[async_trait]
+pub trait Job: Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// Stored in `job.kind` to find the handler again, so it must stay stable.
+    const KIND: &'static str;
+    const QUEUE: &'static str = DEFAULT_QUEUE;
+    const MAX_ATTEMPTS: i32 = 5;
+
+    async fn run(self, state: &AppState) -> Result<(), String>;
+}
+
+/// Delay before retrying a job that has failed `attempts` times: doubling from
+/// `BASE_BACKOFF_SECS` up to `MAX_BACKOFF_SECS`.
+pub fn backoff(attempts: i32) -> chrono::Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    chrono::Duration::seconds(BASE_BACKOFF_SECS.saturating_mul(1 << exponent).min(MAX_BACKOFF_SECS))
+}
+
+/// Queues a job to run as soon as a worker is free. Pass the transaction making
+/// the change that needs the job, so the job only exists if the change commits.
+pub async fn enqueue<C: ConnectionTrait, J: Job>(db: &C, job: &J) -> Result<entity::job::Model, DbErr> {
+    enqueue_at(db, job, Utc::now().naive_utc()).await
+}
+
+pub async fn enqueue_at<C: ConnectionTrait, J: Job>(
+    db: &C,
+    job: &J,
+    run_at: NaiveDateTime
+) -> Result<entity::job::Model, DbErr> {
+    let payload = serde_json::to_string(job).map_err(|e| DbErr::Custom(e.to_string()))?;
+    insert_job(db, J::QUEUE, J::KIND, J::MAX_ATTEMPTS, payload, run_at).await
+}
+
+async fn insert_job<C: ConnectionTrait>(
+    db: &C,
+    queue: &str,
+    kind: &str,
+    max_attempts: i32,
+    payload: String,
+    run_at: NaiveDateTime
+) -> Result<entity::job::Model, DbErr> {
+    entity::job::Entity
+        ::insert(entity::job::ActiveModel {
+            queue: Set(queue.to_string()),
+            kind: Set(kind.to_string()),
+            payload: Set(payload),
+            status: Set(STATUS_QUEUED.to_string()),
+            attempts: Set(0),
+            max_attempts: Set(max_attempts),
+            run_at: Set(run_at),
+            created_at: Set(Utc::now().naive_utc()),
+            trace_context: Set(traces::current_traceparent()),
+            ..Default::default()
+        })
+        .exec_with_returning(db).await
+}
+
+type JobFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+type Handler = Arc<dyn (Fn(AppState, String) -> JobFuture) + Send + Sync>;
+
+/// A job enqueued every time its cron expression fires.
+struct ScheduledJob {
+    name: String,
+    schedule: cron::Schedule,
+    queue: &'static str,
+    kind: &'static str,
+    max_attempts: i32,
+    payload: String,
+}
+
+/// The job kinds this instance can run, the concurrency of each queue and the
+/// cron schedules. Built once at startup and then `start`ed.
+#[derive(Default)]
+pub struct JobRunner {
+    handlers: HashMap<&'static str, Handler>,
+    concurrency: HashMap<&'static str, usize>,
+    schedules: Vec<ScheduledJob>,
+}
+
+impl JobRunner {
+    pub fn register<J: Job>(mut self) -> Self {
+        let handler: Handler = Arc::new(|state: AppState, payload: String| {
+            Box::pin(async move {
+                let job: J = serde_json
+                    ::from_str(&payload)
+                    .map_err(|e| format!("Invalid {} payload: {}", J::KIND, e))?;
+                job.run(&state).await
+            })
+        });
+        self.handlers.insert(J::KIND, handler);
+        self.concurrency.entry(J::QUEUE).or_insert(DEFAULT_CONCURRENCY);
+        self
+    }
+
+    /// Caps how many jobs from `queue` this instance runs at once. The
+    /// `jobs.concurrency` setting takes precedence.
+    pub fn concurrency(mut self, queue: &'static str, limit: usize) -> Self {
+        self.concurrency.insert(queue, limit.max(1));
+        self
+    }
+
+    /// Enqueues `job` whenever the cron `expression` fires. Expressions include a
+    /// seconds field, e.g. `0 */5 * * * *` for every five minutes.
+    pub fn schedule<J: Job>(mut self, name: &str, expression: &str, job: J) -> Self {
+        let schedule = cron::Schedule
+            ::from_str(expression)
+            .unwrap_or_else(|e| panic!("Invalid cron expression for {}: {}", name, e));
+        let payload = serde_json::to_string(&job).expect("Scheduled job payloads serialize");
+        self.schedules.push(ScheduledJob {
+            name: name.to_string(),
+            schedule,
+            queue: J::QUEUE,
+            kind: J::KIND,
+            max_attempts: J::MAX_ATTEMPTS,
+            payload,
+        });
+        self.register::<J>()
+    }
+
+    /// Spawns a worker per queue plus the scheduler, which also requeues jobs left
+    /// running by an instance that went away. Polls every `jobs.poll_interval_ms`
+    /// and gives each job `jobs.timeout_secs` to finish. On shutdown the queues stop
+    /// claiming and wait for the jobs they are running; any cut off by the drain
+    /// timeout are requeued by the reaper once their timeout has passed.
+    pub fn start(mut self, state: AppState) {
+        let jobs = &config::get().jobs;
+        let poll_interval = Duration::from_millis(jobs.poll_interval_ms);
+        let timeout = Duration::from_secs(jobs.timeout_secs);
+        for (queue, &limit) in &jobs.concurrency {
+            match self.concurrency.keys().find(|known| **known == queue) {
+                Some(&queue) => {
+                    self.concurrency.insert(queue, limit);
+                }
+                None => warn!("Ignoring jobs.concurrency for unknown queue {}", queue),
+            }
+        }
+
+        let handlers = Arc::new(self.handlers);
+        for (queue, limit) in self.concurrency {
+            info!("Starting job queue {} with concurrency {}", queue, limit);
+            state.health.spawn_worker(
+                format!("jobs:{}", queue),
+                run_queue(state.clone(), queue, limit, handlers.clone(), poll_interval, timeout)
+            );
+        }
+        state.health.spawn_worker(
+            "job_scheduler",
+            run_scheduler(state.db.clone(), self.schedules, timeout, state.health.shutdown_token())
+        );
+    }
+}
+
+async fn run_queue(
+    state: AppState,
+    queue: &'static str,
+    limit: usize,
+    handlers: Arc<HashMap<&'static str, Handler>>,
+    poll_interval: Duration,
+    timeout: Duration
+) {
+    let shutdown = state.health.shutdown_token();
+    let permits = Arc::new(Semaphore::new(limit));
+    let mut ticker = tokio::time::interval(poll_interval);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = shutdown.cancelled() => break,
+        }
+        // Keep claiming while there is both capacity and work
+        loop {
+            let available = permits.available_permits();
+            if available == 0 || shutdown.is_cancelled() {
+                break;
+            }
+            let claimed = match claim(&state.db, queue, available as u64).await {
+                Ok(claimed) => claimed,
+                Err(err) => {
+                    error!("Claiming jobs from {} failed: {:?}", queue, err);
+                    break;
+                }
+            };
+            let exhausted = claimed.len() < available;
+
+            for job in claimed {
+                let permit = permits.clone().acquire_owned().await.expect("Job semaphore is never closed");
+                let handler = handlers.get(job.kind.as_str()).cloned();
+                let state = state.clone();
+                let span = job_span(&job);
+                tokio::spawn(
+                    async move {
+                        execute(&state, job, handler, timeout).await;
+                        drop(permit);
+                    }.instrument(span)
+                );
+            }
+            if exhausted {
+                break;
+            }
+        }
+    }
+
+    // Every permit back means every job this queue started has finished
+    let _ = permits.acquire_many(limit as u32).await;
+    info!("Job queue {} stopped", queue);
+}
+
+/// Marks up to `limit` due jobs as running and returns them. `SKIP LOCKED` lets
+/// every instance poll the same queue without handing out a job twice.
+async fn claim(db: &DatabaseConnection, queue: &str, limit: u64) -> Result<Vec<entity::job::Model>, DbErr> {
+    let now = Utc::now().naive_utc();
+    let txn = db.begin().await?;
+
+    let due = entity::job::Entity
+        ::find()
+        .filter(entity::job::Column::Queue.eq(queue))
+        .filter(entity::job::Column::Status.eq(STATUS_QUEUED))
+        .filter(entity::job::Column::RunAt.lte(now))
+        .order_by_asc(entity::job::Column::RunAt)
+        .order_by_asc(entity::job::Column::Id)
+        .limit(limit)
+        .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+        .all(&txn).await?;
+    if due.is_empty() {
+        return Ok(due);
+    }
+
+    let ids: Vec<i64> = due
+        .iter()
+        .map(|job| job.id)
+        .collect();
+    entity::job::Entity
+        ::update_many()
+        .col_expr(entity::job::Column::Status, Expr::value(STATUS_RUNNING))
+        .col_expr(entity::job::Column::LockedAt, Expr::value(now))
+        .col_expr(entity::job::Column::Attempts, Expr::col(entity::job::Column::Attempts).add(1))
+        .filter(entity::job::Column::Id.is_in(ids))
+        .exec(&txn).await?;
+    txn.commit().await?;
+
+    Ok(
+        due
+            .into_iter()
+            .map(|job| entity::job::Model {
+                status: STATUS_RUNNING.to_string(),
+                locked_at: Some(now),
+                attempts: job.attempts + 1,
+                ..job
+            })
+            .collect()
+    )
+}
+
+/// Each attempt gets its own span, in the trace of whatever queued the job.
+fn job_span(job: &entity::job::Model) -> Span {
+    let span =
+        info_span!(
+        "job",
+        job.id = job.id,
+        job.kind = %job.kind,
+        job.queue = %job.queue,
+        job.attempt = job.attempts,
+        otel.name = %format!("job {}", job.kind),
+        otel.kind = "consumer",
+        otel.status_code = field::Empty,
+    );
+    traces::continue_trace(&span, job.trace_context.as_deref());
+    span
+}
+
+async fn execute(state: &AppState, job: entity::job::Model, handler: Option<Handler>, timeout: Duration) {
+    let outcome = match handler {
+        Some(handler) =>
+            match tokio::time::timeout(timeout, handler(state.clone(), job.payload.clone())).await {
+                Ok(outcome) => outcome,
+                Err(_) => Err(format!("Timed out after {}s", timeout.as_secs())),
+            }
+        None => Err(format!("No handler registered for {}", job.kind)),
+    };
+
+    let id = job.id;
+    let kind = job.kind.clone();
+    let now = Utc::now().naive_utc();
+    let attempts = job.attempts;
+    let max_attempts = job.max_attempts;
+
+    if outcome.is_err() {
+        Span::current().record("otel.status_code", "ERROR");
+    }
+    let mut active_job: entity::job::ActiveModel = job.into();
+    active_job.locked_at = Set(None);
+    match outcome {
+        Ok(()) => {
+            active_job.status = Set(STATUS_SUCCEEDED.to_string());
+            active_job.last_error = Set(None);
+            active_job.finished_at = Set(Some(now));
+        }
+        Err(err) if attempts >= max_attempts => {
+            error!("Job {} ({}) failed for good after {} attempts: {}", id, kind, attempts, err);
+            active_job.status = Set(STATUS_FAILED.to_string());
+            active_job.last_error = Set(Some(err));
+            active_job.finished_at = Set(Some(now));
+        }
+        Err(err) => {
+            warn!("Job {} ({}) failed on attempt {}: {}", id, kind, attempts, err);
+            active_job.status = Set(STATUS_QUEUED.to_string());
+            active_job.last_error = Set(Some(err));
+            active_job.run_at = Set(now + backoff(attempts));
+        }
+    }
+
+    if let Err(err) = active_job.update(&state.db).await {
+        error!("Failed to record outcome of job {}: {:?}", id, err);
+    }
+}
+
+async fn run_scheduler(
+    db: DatabaseConnection,
+    schedules: Vec<ScheduledJob>,
+    timeout: Duration,
+    shutdown: CancellationToken
+) {
+    for scheduled in &schedules {
+        if let Err(err) = register_schedule(&db, scheduled).await {
+            error!("Failed to register schedule {}: {:?}", scheduled.name, err);
+        }
+    }
+
+    let mut scheduler = tokio::time::interval(SCHEDULER_INTERVAL);
+    let mut reaper = tokio::time::interval(REAPER_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = scheduler.tick() => {
+                for scheduled in &schedules {
+                    if let Err(err) = fire_if_due(&db, scheduled).await {
+                        error!("Failed to enqueue scheduled job {}: {:?}", scheduled.name, err);
+                    }
+                }
+            }
+            _ = reaper.tick() => {
+                if let Err(err) = requeue_abandoned(&db, timeout).await {
+                    error!("Failed to requeue abandoned jobs: {:?}", err);
+                }
+            }
+            _ = shutdown.cancelled() => break,
+        }
+    }
+}
+
+fn next_run(scheduled: &ScheduledJob) -> Option<NaiveDateTime> {
+    scheduled.schedule
+        .after(&Utc::now())
+        .next()
+        .map(|next| next.naive_utc())
+}
+
+async fn register_schedule(db: &DatabaseConnection, scheduled: &ScheduledJob) -> Result<(), DbErr> {
+    let Some(next_run_at) = next_run(scheduled) else {
+        return Ok(());
+    };
+
+    entity::job_schedule::Entity
+        ::insert(entity::job_schedule::ActiveModel {
+            name: Set(scheduled.name.clone()),
+            next_run_at: Set(next_run_at),
+        })
+        .on_conflict(OnConflict::column(entity::job_schedule::Column::Name).do_nothing().to_owned())
+        .exec_without_returning(db).await?;
+
+    Ok(())
+}
+
+/// Moves the schedule's next run forward and enqueues the job in one
+/// transaction. The conditional update only succeeds for one instance, so each
+/// firing is enqueued exactly once however many instances are running.
+async fn fire_if_due(db: &DatabaseConnection, scheduled: &ScheduledJob) -> Result<(), DbErr> {
+    let now = Utc::now().naive_utc();
+    let Some(next_run_at) = next_run(scheduled) else {
+        return Ok(());
+    };
+
+    let txn = db.begin().await?;
+    let claimed = entity::job_schedule::Entity
+        ::update_many()
+        .col_expr(entity::job_schedule::Column::NextRunAt, Expr::value(next_run_at))
+        .filter(entity::job_schedule::Column::Name.eq(&scheduled.name))
+        .filter(entity::job_schedule::Column::NextRunAt.lte(now))
+        .exec(&txn).await?;
+    if claimed.rows_affected == 0 {
+        return Ok(());
+    }
+
+    insert_job(
+        &txn,
+        scheduled.queue,
+        scheduled.kind,
+        scheduled.max_attempts,
+        scheduled.payload.clone(),
+        now
+    ).await?;
+    txn.commit().await
+}
+
+/// Jobs still marked running well past the timeout belonged to an instance that
+/// stopped mid-job; they go back in the queue, keeping their attempt count.
+async fn requeue_abandoned(db: &DatabaseConnection, timeout: Duration) -> Result<(), DbErr> {
+    let stale = Utc::now().naive_utc() - chrono::Duration::from_std(timeout * 2).unwrap_or_default();
+
+    let requeued = entity::job::Entity
+        ::update_many()
+        .col_expr(entity::job::Column::Status, Expr::value(STATUS_QUEUED))
+        .col_expr(entity::job::Column::LockedAt, Expr::value(Option::<NaiveDateTime>::None))
+        .filter(entity::job::Column::Status.eq(STATUS_RUNNING))
+        .filter(entity::job::Column::LockedAt.lt(stale))
+        .exec(db).await?;
+    if requeued.rows_affected > 0 {
+        warn!("Requeued {} abandoned jobs", requeued.rows_affected);
+    }
+
+    Ok(())
+}
+
+/// Deletes jobs that succeeded more than `jobs.succeeded_retention_days` ago.
+/// Failed jobs stay until someone has looked at them. Runs on `PRUNE_SCHEDULE`.
+#[derive(Serialize, Deserialize)]
+pub struct PruneJobs;
+
+#[async_trait]
+impl Job for PruneJobs {
+    const KIND: &'static str = "job.prune";
+
+    async fn run(self, state: &AppState) -> Result<(), String> {
+        let retention = chrono::Duration::days(config::get().jobs.succeeded_retention_days);
+        let pruned = prune_jobs(&state.db, retention).await.map_err(|e| format!("Pruning jobs failed: {}", e))?;
+        info!("Pruned {} succeeded jobs", pruned);
+        Ok(())
+    }
+}
+
+pub async fn prune_jobs(db: &DatabaseConnection, retention: chrono::Duration) -> Result<u64, DbErr> {
+    let cutoff = (Utc::now() - retention).naive_utc();
+
+    let result = entity::job::Entity
+        ::delete_many()
+        .filter(entity::job::Column::FinishedAt.lt(cutoff))
+        .filter(entity::job::Column::Status.eq(STATUS_SUCCEEDED))
+        .exec(db).await?;
+    Ok(result.rows_affected)
+}
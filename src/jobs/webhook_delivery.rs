@@ -0,0 +1,331 @@
+use std::{ sync::OnceLock, time::Duration };
+
+use async_trait::async_trait;
+use chrono::{ NaiveDateTime, Utc };
+use reqwest::Client;
+use sea_orm::{ ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter };
+use serde::{ Deserialize, Serialize };
+use tracing::{ field, info, info_span, warn, Instrument, Span };
+
+use crate::{
+    config,
+    jobs::queue::{ backoff, Job },
+    models::user_models::AppState,
+    services::webhooks::{ sign, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER },
+    telemetry::traces,
+};
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_DELIVERED: &str = "delivered";
+/// Gave up after `MAX_ATTEMPTS`; only a redelivery sends it again.
+pub const STATUS_DEAD: &str = "dead";
+
+pub const PRUNE_SCHEDULE: &str = "0 20 * * * *";
+
+const MAX_ATTEMPTS: i32 = 8;
+/// Longest response body kept in the delivery log.
+const MAX_LOGGED_BODY: usize = 1024;
+
+/// Shared client giving receivers `webhooks.timeout_secs` to answer.
+fn client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        let timeout = config::get().webhooks.timeout_secs;
+        Client::builder().timeout(Duration::from_secs(timeout)).build().expect("Failed to build webhook client")
+    })
+}
+
+/// Sends one queued delivery. The job is retried with the queue's backoff while
+/// the delivery row keeps the log; both give up after `MAX_ATTEMPTS`, leaving the
+/// delivery dead-lettered until someone redelivers it.
+#[derive(Serialize, Deserialize)]
+pub struct DeliverWebhook {
+    pub delivery_id: i64,
+}
+
+#[async_trait]
+impl Job for DeliverWebhook {
+    const KIND: &'static str = "webhook.deliver";
+    const QUEUE: &'static str = "webhooks";
+    const MAX_ATTEMPTS: i32 = MAX_ATTEMPTS;
+
+    async fn run(self, state: &AppState) -> Result<(), String> {
+        let db = &state.db;
+        let delivery = entity::webhook_delivery::Entity
+            ::find_by_id(self.delivery_id)
+            .one(db).await
+            .map_err(|e| format!("Failed to load delivery: {}", e))?;
+        // Gone with its webhook, or already settled
+        let Some(delivery) = delivery.filter(|delivery| delivery.status == STATUS_PENDING) else {
+            return Ok(());
+        };
+        let webhook = entity::webhook::Entity
+            ::find_by_id(delivery.webhook_id)
+            .one(db).await
+            .map_err(|e| format!("Failed to load webhook: {}", e))?;
+
+        let outcome = match webhook {
+            Some(webhook) if webhook.active => {
+                let span =
+                    info_span!(
+                    "webhook.send",
+                    webhook.id = webhook.id,
+                    delivery.id = delivery.id,
+                    http.response.status_code = field::Empty,
+                    otel.name = "POST webhook",
+                    otel.kind = "client",
+                    otel.status_code = field::Empty,
+                );
+                attempt(client(), &webhook, &delivery).instrument(span).await
+            }
+            _ => Err((None, "Webhook is disabled".to_string())),
+        };
+        let error = outcome.as_ref().err().map(|(_, message)| message.clone());
+        record_attempt(db, delivery, outcome).await.map_err(|e| format!("Failed to record delivery: {}", e))?;
+
+        match error {
+            Some(message) => Err(message),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Sends one signed delivery, passing on the trace it belongs to. Any 2xx response
+/// counts as delivered; otherwise the error carries the response status, if there
+/// was one.
+async fn attempt(
+    client: &Client,
+    webhook: &entity::webhook::Model,
+    delivery: &entity::webhook_delivery::Model
+) -> Result<u16, (Option<u16>, String)> {
+    let timestamp = Utc::now().timestamp();
+    let span = Span::current();
+    let response = client
+        .post(&webhook.url)
+        .headers(traces::outgoing_headers())
+        .header("content-type", "application/json")
+        .header(EVENT_HEADER, &delivery.event_type)
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(SIGNATURE_HEADER, sign(&webhook.secret, timestamp, &delivery.payload))
+        .body(delivery.payload.clone())
+        .send().await
+        .map_err(|e| {
+            span.record("otel.status_code", "ERROR");
+            (None, format!("Request failed: {}", e))
+        })?;
+
+    let status = response.status();
+    span.record("http.response.status_code", status.as_u16());
+    if status.is_success() {
+        return Ok(status.as_u16());
+    }
+    span.record("otel.status_code", "ERROR");
+    let body = response.text().await.unwrap_or_default();
+    let body: String = body.chars().take(MAX_LOGGED_BODY).collect();
+    Err((Some(status.as_u16()), format!("Receiver responded {}: {}", status, body)))
+}
+
+async fn record_attempt(
+    db: &DatabaseConnection,
+    delivery: entity::webhook_delivery::Model,
+    outcome: Result<u16, (Option<u16>, String)>
+) -> Result<(), DbErr> {
+    let id = delivery.id;
+    let delivery = with_attempt(delivery, outcome, Utc::now().naive_utc()).update(db).await?;
+    if delivery.status == STATUS_DELIVERED {
+        info!("Delivered webhook {} ({})", id, delivery.event_type);
+    }
+    Ok(())
+}
+
+/// The delivery once an attempt made at `now` ended with `outcome`: delivered,
+/// scheduled for a retry after the queue's backoff, or dead at `MAX_ATTEMPTS`.
+fn with_attempt(
+    delivery: entity::webhook_delivery::Model,
+    outcome: Result<u16, (Option<u16>, String)>,
+    now: NaiveDateTime
+) -> entity::webhook_delivery::ActiveModel {
+    let id = delivery.id;
+    let attempts = delivery.attempts + 1;
+
+    let mut active_delivery: entity::webhook_delivery::ActiveModel = delivery.into();
+    active_delivery.attempts = Set(attempts);
+    active_delivery.last_attempt_at = Set(Some(now));
+    match outcome {
+        Ok(status) => {
+            active_delivery.status = Set(STATUS_DELIVERED.to_string());
+            active_delivery.response_status = Set(Some(status as i32));
+            active_delivery.last_error = Set(None);
+            active_delivery.next_attempt_at = Set(None);
+            active_delivery.delivered_at = Set(Some(now));
+        }
+        Err((status, message)) => {
+            active_delivery.response_status = Set(status.map(i32::from));
+            active_delivery.last_error = Set(Some(message));
+            if attempts >= MAX_ATTEMPTS {
+                warn!("Webhook delivery {} dead-lettered after {} attempts", id, attempts);
+                active_delivery.status = Set(STATUS_DEAD.to_string());
+                active_delivery.next_attempt_at = Set(None);
+            } else {
+                active_delivery.next_attempt_at = Set(Some(now + backoff(attempts)));
+            }
+        }
+    }
+    active_delivery
+}
+
+/// Deletes delivered and dead deliveries older than
+/// `webhooks.delivery_retention_days`; pending ones are still queued. Runs on
+/// `PRUNE_SCHEDULE`.
+#[derive(Serialize, Deserialize)]
+pub struct PruneWebhookDeliveries;
+
+#[async_trait]
+impl Job for PruneWebhookDeliveries {
+    const KIND: &'static str = "webhook.prune";
+
+    async fn run(self, state: &AppState) -> Result<(), String> {
+        let retention = chrono::Duration::days(config::get().webhooks.delivery_retention_days);
+        let pruned = prune_deliveries(&state.db, retention).await.map_err(|e|
+            format!("Pruning webhook deliveries failed: {}", e)
+        )?;
+        info!("Pruned {} webhook deliveries", pruned);
+        Ok(())
+    }
+}
+
+pub async fn prune_deliveries(db: &DatabaseConnection, retention: chrono::Duration) -> Result<u64, DbErr> {
+    let cutoff = (Utc::now() - retention).naive_utc();
+
+    let result = entity::webhook_delivery::Entity
+        ::delete_many()
+        .filter(entity::webhook_delivery::Column::CreatedAt.lt(cutoff))
+        .filter(entity::webhook_delivery::Column::Status.ne(STATUS_PENDING))
+        .exec(db).await?;
+    Ok(result.rows_affected)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{ atomic::{ AtomicU16, Ordering }, Arc, Mutex };
+
+    use axum::{ body::Bytes, extract::State, http::{ HeaderMap, StatusCode }, routing::post, Router };
+    use hmac::{ Hmac, KeyInit, Mac };
+    use sha2::Sha256;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    const SECRET: &str = "whsec_test";
+    const PAYLOAD: &str = r#"{"id":"7","type":"user.created","data":{}}"#;
+
+    /// Answers every delivery with `status` and remembers what it was sent.
+    #[derive(Clone)]
+    struct Receiver {
+        status: Arc<AtomicU16>,
+        received: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+    }
+
+    async fn receive(State(receiver): State<Receiver>, headers: HeaderMap, body: Bytes) -> (StatusCode, &'static str) {
+        receiver.received.lock().unwrap().push((headers, body));
+        let status = StatusCode::from_u16(receiver.status.load(Ordering::SeqCst)).unwrap();
+        (status, "receiver is down")
+    }
+
+    async fn start_receiver(status: u16) -> (String, Receiver) {
+        let receiver = Receiver { status: Arc::new(AtomicU16::new(status)), received: Default::default() };
+        let app = Router::new().route("/hook", post(receive)).with_state(receiver.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (format!("http://{}/hook", addr), receiver)
+    }
+
+    fn webhook(url: String) -> entity::webhook::Model {
+        entity::webhook::Model {
+            id: 1,
+            url,
+            secret: SECRET.to_string(),
+            active: true,
+            created_by: None,
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    fn delivery(attempts: i32) -> entity::webhook_delivery::Model {
+        let now = Utc::now().naive_utc();
+        entity::webhook_delivery::Model {
+            id: 42,
+            webhook_id: 1,
+            event_type: "user.created".to_string(),
+            payload: PAYLOAD.to_string(),
+            status: STATUS_PENDING.to_string(),
+            attempts,
+            next_attempt_at: Some(now),
+            last_attempt_at: None,
+            response_status: None,
+            last_error: None,
+            created_at: now,
+            delivered_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn deliveries_carry_a_signature_the_receiver_can_verify() {
+        let (url, receiver) = start_receiver(204).await;
+
+        let outcome = attempt(&Client::new(), &webhook(url), &delivery(0)).await;
+        assert_eq!(outcome, Ok(204));
+
+        let received = receiver.received.lock().unwrap();
+        let (headers, body) = &received[0];
+        let header = |name: &str| headers.get(name).unwrap().to_str().unwrap();
+        assert_eq!(body.as_ref(), PAYLOAD.as_bytes());
+        assert_eq!(header(EVENT_HEADER), "user.created");
+        assert_eq!(header(DELIVERY_HEADER), "42");
+
+        // What a receiver does: recompute the signature and reject stale timestamps
+        let timestamp: i64 = header(TIMESTAMP_HEADER).parse().unwrap();
+        assert!((Utc::now().timestamp() - timestamp).abs() <= 5);
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(format!("{}.", timestamp).as_bytes());
+        mac.update(body);
+        let expected = hex::decode(header(SIGNATURE_HEADER).strip_prefix("sha256=").unwrap()).unwrap();
+        mac.verify_slice(&expected).expect("the signature verifies");
+
+        let delivered = with_attempt(delivery(0), outcome, Utc::now().naive_utc());
+        assert_eq!(delivered.status.unwrap(), STATUS_DELIVERED);
+        assert_eq!(delivered.response_status.unwrap(), Some(204));
+    }
+
+    #[tokio::test]
+    async fn failed_deliveries_record_the_error_and_retry_after_a_backoff() {
+        let (url, _receiver) = start_receiver(503).await;
+
+        let outcome = attempt(&Client::new(), &webhook(url), &delivery(2)).await;
+        let (status, message) = outcome.clone().unwrap_err();
+        assert_eq!(status, Some(503));
+        assert!(message.contains("receiver is down"), "{}", message);
+
+        let now = Utc::now().naive_utc();
+        let retried = with_attempt(delivery(2), outcome, now);
+        assert_eq!(retried.attempts.unwrap(), 3);
+        assert_eq!(retried.status.unwrap(), STATUS_PENDING);
+        assert_eq!(retried.response_status.unwrap(), Some(503));
+        assert_eq!(retried.last_error.unwrap(), Some(message));
+        assert_eq!(retried.next_attempt_at.unwrap(), Some(now + backoff(3)));
+    }
+
+    #[tokio::test]
+    async fn deliveries_are_dead_lettered_after_the_last_attempt() {
+        let (url, _receiver) = start_receiver(500).await;
+
+        let outcome = attempt(&Client::new(), &webhook(url), &delivery(MAX_ATTEMPTS - 1)).await;
+        let dead = with_attempt(delivery(MAX_ATTEMPTS - 1), outcome, Utc::now().naive_utc());
+        assert_eq!(dead.attempts.unwrap(), MAX_ATTEMPTS);
+        assert_eq!(dead.status.unwrap(), STATUS_DEAD);
+        assert_eq!(dead.next_attempt_at.unwrap(), None);
+        assert!(dead.last_error.unwrap().is_some());
+    }
+}
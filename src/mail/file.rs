@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::mail::{ MailTransport, OutgoingEmail };
+
+/// Writes each message as an `.eml` file under `mail.dir` (`./mail` by default),
+/// for opening in a mail client during development.
+pub struct FileTransport {
+    dir: PathBuf,
+}
+
+impl FileTransport {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl MailTransport for FileTransport {
+    async fn send(&self, email: &OutgoingEmail<'_>) -> Result<(), String> {
+        let message = email.to_message()?;
+        tokio::fs
+            ::create_dir_all(&self.dir).await
+            .map_err(|e| format!("Failed to create {}: {}", self.dir.display(), e))?;
+
+        let path = self.dir.join(format!("{}-{}.eml", Utc::now().format("%Y%m%dT%H%M%S"), Uuid::new_v4().simple()));
+        tokio::fs
+            ::write(&path, message.formatted()).await
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
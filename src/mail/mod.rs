@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use lettre::{ message::MultiPart, Message };
+
+use crate::config::{ MailConfig, MailTransportKind };
+
+pub mod file;
+pub mod smtp;
+pub mod stdout;
+pub mod templates;
+
+/// A rendered email ready to hand to a transport.
+pub struct OutgoingEmail<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+    pub subject: &'a str,
+    pub html: &'a str,
+    pub text: &'a str,
+}
+
+impl OutgoingEmail<'_> {
+    /// Builds a `multipart/alternative` message carrying both bodies.
+    pub fn to_message(&self) -> Result<Message, String> {
+        Message::builder()
+            .from(self.from.parse().map_err(|e| format!("Invalid sender {}: {}", self.from, e))?)
+            .to(self.to.parse().map_err(|e| format!("Invalid recipient {}: {}", self.to, e))?)
+            .subject(self.subject)
+            .multipart(MultiPart::alternative_plain_html(self.text.to_string(), self.html.to_string()))
+            .map_err(|e| format!("Failed to build message: {}", e))
+    }
+}
+
+/// Where outgoing email goes.
+#[async_trait]
+pub trait MailTransport: Send + Sync {
+    async fn send(&self, email: &OutgoingEmail<'_>) -> Result<(), String>;
+}
+
+/// Builds the transport selected by `mail.transport`: `smtp`, `file`, or `stdout`
+/// (the default, for development).
+pub fn from_config(config: &MailConfig) -> Arc<dyn MailTransport> {
+    match config.transport {
+        MailTransportKind::Smtp => Arc::new(smtp::SmtpTransport::new(&config.smtp_url)),
+        MailTransportKind::File => Arc::new(file::FileTransport::new(&config.dir)),
+        MailTransportKind::Stdout => Arc::new(stdout::StdoutTransport),
+    }
+}
@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+
+use crate::mail::{ MailTransport, OutgoingEmail };
+
+/// Prints the plaintext version of each message instead of sending it.
+pub struct StdoutTransport;
+
+#[async_trait]
+impl MailTransport for StdoutTransport {
+    async fn send(&self, email: &OutgoingEmail<'_>) -> Result<(), String> {
+        println!(
+            "----- email -----\nFrom: {}\nTo: {}\nSubject: {}\n\n{}\n-----------------",
+            email.from,
+            email.to,
+            email.subject,
+            email.text
+        );
+        Ok(())
+    }
+}
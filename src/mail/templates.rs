@@ -0,0 +1,97 @@
+use minijinja::Environment;
+
+/// Every email the server sends. Each has `<name>.html` and `<name>.txt` under
+/// `templates/email`, compiled into the binary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmailTemplate {
+    Verification,
+    PasswordReset,
+    NotificationDigest,
+    ExportReady,
+}
+
+impl EmailTemplate {
+    pub const ALL: [EmailTemplate; 4] = [
+        EmailTemplate::Verification,
+        EmailTemplate::PasswordReset,
+        EmailTemplate::NotificationDigest,
+        EmailTemplate::ExportReady,
+    ];
+
+    /// Stored in `email_outbox.template`.
+    pub fn name(self) -> &'static str {
+        match self {
+            EmailTemplate::Verification => "verification",
+            EmailTemplate::PasswordReset => "password_reset",
+            EmailTemplate::NotificationDigest => "notification_digest",
+            EmailTemplate::ExportReady => "export_ready",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|candidate| candidate.name() == name)
+    }
+
+    pub fn subject(self) -> &'static str {
+        match self {
+            EmailTemplate::Verification => "Verify your email address",
+            EmailTemplate::PasswordReset => "Reset your password",
+            EmailTemplate::NotificationDigest => "Your unread notifications",
+            EmailTemplate::ExportReady => "Your data export is ready",
+        }
+    }
+
+    /// Account emails, and answers to something the user asked for, are sent even
+    /// to addresses that unsubscribed; everything else respects the suppression
+    /// list and carries an unsubscribe link.
+    pub fn is_transactional(self) -> bool {
+        matches!(self, EmailTemplate::Verification | EmailTemplate::PasswordReset | EmailTemplate::ExportReady)
+    }
+
+    /// Emails whose links work for whoever holds them, signing in or downloading
+    /// data without a password. Their bodies are not kept once sending is over.
+    pub fn has_secret_link(self) -> bool {
+        matches!(self, EmailTemplate::Verification | EmailTemplate::PasswordReset | EmailTemplate::ExportReady)
+    }
+}
+
+/// The compiled templates. `.html` templates escape their variables; `.txt`
+/// templates do not.
+pub struct Templates {
+    env: Environment<'static>,
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        let mut env = Environment::new();
+        let sources = [
+            ("layout.html", include_str!("../../templates/email/layout.html")),
+            ("verification.html", include_str!("../../templates/email/verification.html")),
+            ("verification.txt", include_str!("../../templates/email/verification.txt")),
+            ("password_reset.html", include_str!("../../templates/email/password_reset.html")),
+            ("password_reset.txt", include_str!("../../templates/email/password_reset.txt")),
+            ("notification_digest.html", include_str!("../../templates/email/notification_digest.html")),
+            ("notification_digest.txt", include_str!("../../templates/email/notification_digest.txt")),
+            ("export_ready.html", include_str!("../../templates/email/export_ready.html")),
+            ("export_ready.txt", include_str!("../../templates/email/export_ready.txt")),
+        ];
+        for (name, source) in sources {
+            env.add_template(name, source).unwrap_or_else(|e| panic!("Invalid email template {}: {}", name, e));
+        }
+        Self { env }
+    }
+}
+
+impl Templates {
+    /// Renders the HTML and plaintext bodies, in that order.
+    pub fn render(&self, template: EmailTemplate, context: &serde_json::Value) -> Result<(String, String), String> {
+        let render = |extension: &str| {
+            let name = format!("{}.{}", template.name(), extension);
+            self.env
+                .get_template(&name)
+                .and_then(|compiled| compiled.render(context))
+                .map_err(|e| format!("Failed to render {}: {}", name, e))
+        };
+        Ok((render("html")?, render("txt")?))
+    }
+}
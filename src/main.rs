@@ -1,27 +1,68 @@
-use std::net::SocketAddr;
+use std::{ process, sync::Arc, time::Duration };
 
-use sea_orm::{ Database };
+use sea_orm::{ ConnectOptions, Database };
 use tokio::net::TcpListener;
-use axum::{ middleware, response::IntoResponse, Router };
-use tracing::{ info, error };
+use axum::{ middleware, Router };
+use tracing::{ info, error, warn };
 
-use crate::models::user_models::AppState;
+use crate::{ config::{ CliArgs, Config }, models::user_models::AppState, services::health::Health };
 use dotenv::dotenv;
-use std::env;
+mod config;
 mod models;
 mod routes;
 mod handlers;
 mod utils;
-// Store your connection string as a constant or environment variable
+mod jobs;
+mod storage;
+mod services;
+mod mail;
+mod server;
+mod telemetry;
+#[cfg(test)]
+mod testing;
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
 
-    let datbase_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let config = CliArgs::parse()
+        .and_then(|cli| {
+            let config = Config::load(cli.config_file.as_deref())?;
+            if cli.print_config {
+                print!("{}", config.redacted().to_toml());
+                process::exit(0);
+            }
+            Ok(config)
+        })
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(2);
+        });
+    config::init(config);
+    let config = config::get();
+
+    telemetry::init(config);
+    if config.server.dev_mode {
+        warn!("Running in dev mode, which accepts the built-in secrets; never enable it in production");
+    }
+
+    let database = &config.database;
+    let mut options = ConnectOptions::new(&database.url);
+    options
+        .max_connections(database.max_connections)
+        .min_connections(database.min_connections)
+        .connect_timeout(Duration::from_secs(database.connect_timeout_secs))
+        .acquire_timeout(Duration::from_secs(database.acquire_timeout_secs))
+        .idle_timeout(Duration::from_secs(database.idle_timeout_secs))
+        .sqlx_logging(database.sqlx_logging);
+
+    let health = Health::default();
+    if config.metrics.enabled {
+        telemetry::metrics::install(&health);
+    }
 
     // Connect to the DB and store the connection in app state
-    let db = match Database::connect(datbase_url).await {
+    let mut db = match Database::connect(options).await {
         Ok(db) => {
             info!("Database connected Successfully");
             db
@@ -32,21 +73,70 @@ async fn main() {
         }
     };
 
-    let app_state: AppState = AppState { db };
+    telemetry::observe_queries(&mut db, config);
+
+    let storage = storage::from_config(&config.storage).unwrap_or_else(|err| {
+        error!("Storage configuration is invalid: {}", err);
+        process::exit(2);
+    });
+
+    let bus: Arc<dyn services::bus::EventBus> = Arc::new(services::bus::InProcessBus::default());
+    let notifier = services::notifier::Notifier::new(db.clone(), bus.clone());
+    let mailer = services::mailer::Mailer::from_config(config);
 
-    // Initialize tracing
-    tracing_subscriber::fmt::init();
+    let app_state: AppState = AppState { db, storage, notifier, bus, mailer, health };
+
+    jobs::job_runner().start(app_state.clone());
+    jobs::outbox_dispatcher().start(app_state.clone());
 
     let app = Router::new()
 
         .merge(routes::user_routes::user_routes())
+        .merge(routes::post_routes::post_routes())
+        .merge(routes::bookmark_routes::bookmark_routes())
+        .merge(routes::notification_routes::notification_routes())
+        .merge(routes::admin_routes::admin_routes())
         .route_layer(middleware::from_fn_with_state(app_state.clone(), utils::guard::guard))
         .merge(routes::auth_routes::auth_routes())
-        .with_state(app_state);
+        .merge(routes::public_routes::public_routes())
+        .merge(routes::realtime_routes::realtime_routes())
+        .with_state(app_state.clone());
+    let app = match config.rate_limit.enabled {
+        true => utils::rate_limit::limit(app, config.rate_limit.requests_per_minute),
+        false => app,
+    };
+    let app = app.merge(routes::health_routes::health_routes().with_state(app_state.clone()));
+    let app = match config.metrics.enabled {
+        true =>
+            app
+                .merge(routes::metrics_routes::metrics_routes().with_state(app_state.clone()))
+                .route_layer(middleware::from_fn(telemetry::metrics::track_http)),
+        false => app,
+    };
+    let app = app.route_layer(middleware::from_fn(telemetry::requests::record_route));
+    // Outermost, so preflights are answered before auth or rate limiting
+    let app = app.layer(utils::cors::layer(&config.cors));
+    // Around CORS too, so every response, preflights included, gets a request id
+    let app = app.layer(middleware::from_fn(telemetry::requests::trace_request));
 
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
-    println!("Server is running on http://{}", addr);
+    let addr = config.server.bind_addr;
+    info!("Server is running on http://{}", addr);
 
     let listener = TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    let health = app_state.health.clone();
+    let readiness_grace = Duration::from_secs(config.server.readiness_grace_secs);
+    tokio::spawn(server::shutdown_signal(health.clone(), readiness_grace));
+    let drain_timeout = Duration::from_secs(config.server.drain_timeout_secs);
+    server::serve(listener, app, health.clone(), drain_timeout).await.unwrap();
+
+    // Workers were told to stop with the same signal; let them finish their work
+    // before the pool goes away underneath them
+    if tokio::time::timeout(drain_timeout, health.join_workers()).await.is_err() {
+        warn!("Background workers still running after {}s", drain_timeout.as_secs());
+    }
+    if let Err(err) = app_state.db.close().await {
+        error!("Failed to close the database pool: {:?}", err);
+    }
+    info!("Shutdown complete");
+    telemetry::traces::shutdown();
 }
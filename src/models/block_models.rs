@@ -0,0 +1,18 @@
+use chrono::NaiveDateTime;
+use serde::{ Deserialize, Serialize };
+use uuid::Uuid;
+
+/// A user the caller has blocked or muted.
+#[derive(Serialize)]
+pub struct HiddenUserModel {
+    pub uuid: Uuid,
+    pub name: String,
+    pub since: NaiveDateTime,
+}
+
+/// Block and mute lists are newest first; pass the last `since` seen as `before`.
+#[derive(Deserialize, Debug)]
+pub struct HiddenUserPageQuery {
+    pub before: Option<NaiveDateTime>,
+    pub limit: Option<u64>,
+}
@@ -0,0 +1,53 @@
+use chrono::NaiveDateTime;
+use serde::{ Deserialize, Serialize };
+use uuid::Uuid;
+
+use crate::models::post_models::PostResponseModel;
+
+#[derive(Deserialize, Debug)]
+pub struct CreateCollectionModel {
+    pub name: String,
+}
+
+/// Setting `shared` to true issues a share link; false revokes it.
+#[derive(Deserialize, Debug)]
+pub struct UpdateCollectionModel {
+    pub name: Option<String>,
+    pub shared: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct CollectionModel {
+    pub id: Uuid,
+    pub name: String,
+    pub share_url: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<entity::bookmark_collection::Model> for CollectionModel {
+    fn from(collection: entity::bookmark_collection::Model) -> Self {
+        Self {
+            id: collection.id,
+            name: collection.name,
+            share_url: collection.share_token.map(shared_collection_url),
+            created_at: collection.created_at,
+        }
+    }
+}
+
+pub fn shared_collection_url(token: Uuid) -> String {
+    format!("/bookmarks/shared/{}", token)
+}
+
+#[derive(Serialize)]
+pub struct BookmarkModel {
+    pub saved_at: NaiveDateTime,
+    pub post: PostResponseModel,
+}
+
+/// Bookmarks are listed newest first; pass the last `saved_at` seen as `before`.
+#[derive(Deserialize, Debug)]
+pub struct BookmarkPageQuery {
+    pub before: Option<NaiveDateTime>,
+    pub limit: Option<u64>,
+}
@@ -0,0 +1,34 @@
+use chrono::NaiveDateTime;
+use serde::{ Deserialize, Serialize };
+use uuid::Uuid;
+
+use crate::models::reaction_models::ReactionCountModel;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateCommentModel {
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateCommentModel {
+    pub text: String,
+}
+
+#[derive(Serialize)]
+pub struct CommentResponseModel {
+    pub id: i32,
+    pub post_id: i32,
+    pub text: String,
+    pub html: String,
+    pub author_uuid: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub edited_at: Option<NaiveDateTime>,
+    pub reactions: Vec<ReactionCountModel>,
+}
+
+/// Comments are listed oldest first; pass the last `id` seen as `after`.
+#[derive(Deserialize, Debug)]
+pub struct CommentPageQuery {
+    pub after: Option<i32>,
+    pub limit: Option<u64>,
+}
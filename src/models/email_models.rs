@@ -0,0 +1,18 @@
+use serde::Deserialize;
+
+/// `expires` and `signature` appended to links sent by email.
+#[derive(Deserialize)]
+pub struct SignedLinkQuery {
+    pub expires: i64,
+    pub signature: String,
+}
+
+#[derive(Deserialize)]
+pub struct PasswordResetRequestModel {
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordModel {
+    pub password: String,
+}
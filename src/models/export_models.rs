@@ -0,0 +1,109 @@
+use chrono::NaiveDateTime;
+use serde::{ Deserialize, Serialize };
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct ExportUserModel {
+    pub uuid: Uuid,
+    pub name: String,
+    pub email: String,
+    pub handle: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub deletion_requested_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct ExportPostModel {
+    pub id: i32,
+    pub title: String,
+    pub text: String,
+    pub format: String,
+    pub image: String,
+    pub upload_id: Option<Uuid>,
+    pub created_at: String,
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct ExportCommentModel {
+    pub id: i32,
+    pub post_id: i32,
+    pub text: String,
+    pub created_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct ExportAuditEventModel {
+    pub id: i64,
+    pub event_type: String,
+    pub payload: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub struct DataExportResponseModel {
+    pub id: Uuid,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub completed_at: Option<NaiveDateTime>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub download_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SignedDownloadQuery {
+    pub expires: i64,
+    pub signature: String,
+}
+
+impl From<entity::user::Model> for ExportUserModel {
+    fn from(user: entity::user::Model) -> Self {
+        ExportUserModel {
+            uuid: user.uuid,
+            name: user.name,
+            email: user.email,
+            handle: user.handle,
+            created_at: user.created_at,
+            deletion_requested_at: user.deletion_requested_at,
+        }
+    }
+}
+
+impl From<entity::post::Model> for ExportPostModel {
+    fn from(post: entity::post::Model) -> Self {
+        ExportPostModel {
+            id: post.id,
+            title: post.title,
+            text: post.text,
+            format: post.format,
+            image: post.image,
+            upload_id: post.upload_id,
+            created_at: post.created_at,
+            deleted_at: post.deleted_at,
+        }
+    }
+}
+
+impl From<entity::comment::Model> for ExportCommentModel {
+    fn from(comment: entity::comment::Model) -> Self {
+        ExportCommentModel {
+            id: comment.id,
+            post_id: comment.post_id,
+            text: comment.text,
+            created_at: comment.created_at,
+            deleted_at: comment.deleted_at,
+        }
+    }
+}
+
+impl From<entity::audit_event::Model> for ExportAuditEventModel {
+    fn from(event: entity::audit_event::Model) -> Self {
+        ExportAuditEventModel {
+            id: event.id,
+            event_type: event.event_type,
+            payload: event.payload,
+            created_at: event.created_at,
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use chrono::NaiveDateTime;
+use serde::{ Deserialize, Serialize };
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct FollowUserModel {
+    pub uuid: Uuid,
+    pub name: String,
+    pub followed_at: NaiveDateTime,
+}
+
+/// Follow lists are newest first; pass the last `followed_at` and `uuid` seen as
+/// `before` and `before_uuid`. Follows made at the same instant are ordered by uuid.
+#[derive(Deserialize, Debug)]
+pub struct FollowPageQuery {
+    pub before: Option<NaiveDateTime>,
+    pub before_uuid: Option<Uuid>,
+    pub limit: Option<u64>,
+}
@@ -0,0 +1,54 @@
+use chrono::NaiveDateTime;
+use serde::{ Deserialize, Serialize };
+
+#[derive(Serialize)]
+pub struct JobModel {
+    pub id: i64,
+    pub queue: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: NaiveDateTime,
+    pub locked_at: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+impl From<entity::job::Model> for JobModel {
+    fn from(job: entity::job::Model) -> Self {
+        Self {
+            id: job.id,
+            queue: job.queue,
+            kind: job.kind,
+            payload: serde_json::from_str(&job.payload).unwrap_or_default(),
+            status: job.status,
+            attempts: job.attempts,
+            max_attempts: job.max_attempts,
+            run_at: job.run_at,
+            locked_at: job.locked_at,
+            last_error: job.last_error,
+            created_at: job.created_at,
+            finished_at: job.finished_at,
+        }
+    }
+}
+
+/// Jobs are listed newest first; pass the last `id` seen as `before`.
+#[derive(Deserialize, Debug)]
+pub struct JobPageQuery {
+    pub before: Option<i64>,
+    pub limit: Option<u64>,
+    pub status: Option<String>,
+    pub queue: Option<String>,
+    pub kind: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct QueueStatusCountModel {
+    pub queue: String,
+    pub status: String,
+    pub count: i64,
+}
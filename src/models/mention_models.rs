@@ -0,0 +1,26 @@
+use chrono::NaiveDateTime;
+use serde::{ Deserialize, Serialize };
+
+use crate::models::{ comment_models::CommentResponseModel, post_models::PostResponseModel };
+
+/// A post or comment that mentions a user or carries a hashtag.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum LinkedContentModel {
+    Post {
+        linked_at: NaiveDateTime,
+        post: PostResponseModel,
+    },
+    Comment {
+        linked_at: NaiveDateTime,
+        comment: CommentResponseModel,
+    },
+}
+
+/// Mentions and hashtags are listed newest first; pass the last `linked_at` seen
+/// as `before`.
+#[derive(Deserialize, Debug)]
+pub struct LinkPageQuery {
+    pub before: Option<NaiveDateTime>,
+    pub limit: Option<u64>,
+}
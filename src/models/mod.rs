@@ -1,2 +1,14 @@
 pub mod user_models;
 pub mod post_models;
+pub mod export_models;
+pub mod upload_models;
+pub mod comment_models;
+pub mod reaction_models;
+pub mod follow_models;
+pub mod block_models;
+pub mod bookmark_models;
+pub mod mention_models;
+pub mod notification_models;
+pub mod webhook_models;
+pub mod job_models;
+pub mod email_models;
@@ -0,0 +1,37 @@
+use chrono::NaiveDateTime;
+use serde::{ Deserialize, Serialize };
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct NotificationModel {
+    pub id: i32,
+    pub kind: String,
+    pub actor_uuid: Option<Uuid>,
+    pub target_type: Option<String>,
+    pub target_id: Option<i32>,
+    pub read_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl NotificationModel {
+    pub fn new(notification: entity::notification::Model, actor_uuid: Option<Uuid>) -> Self {
+        Self {
+            id: notification.id,
+            kind: notification.kind,
+            actor_uuid,
+            target_type: notification.target_type,
+            target_id: notification.target_id,
+            read_at: notification.read_at,
+            created_at: notification.created_at,
+        }
+    }
+}
+
+/// Notifications are listed newest first; pass the last `id` seen as `before`.
+#[derive(Deserialize, Debug)]
+pub struct NotificationPageQuery {
+    pub before: Option<i32>,
+    pub limit: Option<u64>,
+    #[serde(default)]
+    pub unread: bool,
+}
@@ -1,16 +1,65 @@
 use serde::{ Serialize, Deserialize };
+use uuid::Uuid;
 
-pub struct PostModel {
-    pub id: i32,
-    pub text: String,
-    pub user_id: i32,
-    pub image: String,
-    pub title: String,
+use crate::models::{ reaction_models::ReactionCountModel, upload_models::ImageModel };
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PostFormat {
+    #[default]
+    Plain,
+    Markdown,
+}
+
+impl PostFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PostFormat::Plain => "plain",
+            PostFormat::Markdown => "markdown",
+        }
+    }
+
+    /// Unknown values from the database fall back to plain text.
+    pub fn from_column(value: &str) -> Self {
+        match value {
+            "markdown" => PostFormat::Markdown,
+            _ => PostFormat::Plain,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreatePostModel {
     pub text: String,
-    pub image: String,
+    pub upload_id: Option<Uuid>,
     pub title: String,
+    #[serde(default)]
+    pub format: PostFormat,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdatePostModel {
+    pub title: Option<String>,
+    pub text: Option<String>,
+    pub format: Option<PostFormat>,
+}
+
+#[derive(Serialize)]
+pub struct PostResponseModel {
+    pub id: i32,
+    pub title: String,
+    pub text: String,
+    pub format: PostFormat,
+    pub html: String,
+    pub image: Option<ImageModel>,
+    pub author_uuid: Option<Uuid>,
+    pub created_at: String,
+    pub reactions: Vec<ReactionCountModel>,
+}
+
+/// Keyset pagination: pass the last `id` seen as `before` to get the next page.
+#[derive(Deserialize, Debug)]
+pub struct PostPageQuery {
+    pub before: Option<i32>,
+    pub limit: Option<u64>,
 }
@@ -0,0 +1,11 @@
+use serde::Serialize;
+
+/// Reaction kinds clients may send.
+pub const REACTION_KINDS: &[&str] = &["like", "love", "laugh", "wow", "sad", "angry"];
+
+#[derive(Serialize, Clone)]
+pub struct ReactionCountModel {
+    pub kind: String,
+    pub count: i64,
+    pub reacted_by_me: bool,
+}
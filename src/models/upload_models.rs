@@ -0,0 +1,81 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct UploadModel {
+    pub id: Uuid,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub original_filename: Option<String>,
+    pub purpose: String,
+    pub url: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ImageVariantModel {
+    pub label: String,
+    pub url: String,
+    pub width: i32,
+    pub height: i32,
+    pub content_type: String,
+}
+
+/// An uploaded image as embedded in other responses, with every processed size.
+#[derive(Serialize, Clone)]
+pub struct ImageModel {
+    pub id: Uuid,
+    pub url: String,
+    pub content_type: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub blurhash: Option<String>,
+    pub variants: Vec<ImageVariantModel>,
+}
+
+/// Public path an upload is served from.
+pub fn upload_url(id: Uuid) -> String {
+    format!("/uploads/{}", id)
+}
+
+pub fn variant_url(id: Uuid, label: &str) -> String {
+    format!("/uploads/{}/variants/{}", id, label)
+}
+
+impl ImageModel {
+    pub fn new(upload: entity::upload::Model, variants: Vec<entity::upload_variant::Model>) -> Self {
+        ImageModel {
+            id: upload.id,
+            url: upload_url(upload.id),
+            content_type: upload.content_type,
+            width: upload.width,
+            height: upload.height,
+            blurhash: upload.blurhash,
+            variants: variants
+                .into_iter()
+                .map(|variant| ImageVariantModel {
+                    url: variant_url(upload.id, &variant.label),
+                    label: variant.label,
+                    width: variant.width,
+                    height: variant.height,
+                    content_type: variant.content_type,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<entity::upload::Model> for UploadModel {
+    fn from(upload: entity::upload::Model) -> Self {
+        UploadModel {
+            id: upload.id,
+            content_type: upload.content_type,
+            size_bytes: upload.size_bytes,
+            original_filename: upload.original_filename,
+            purpose: upload.purpose,
+            url: upload_url(upload.id),
+            created_at: upload.created_at,
+        }
+    }
+}
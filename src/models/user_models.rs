@@ -1,14 +1,24 @@
+use std::sync::Arc;
+
 use chrono::NaiveDateTime;
 use sea_orm::DatabaseConnection;
 use uuid::Uuid;
 use serde::{ Serialize, Deserialize };
 
-#[derive(Serialize, Deserialize, Clone)]
+use crate::{
+    models::upload_models::ImageModel,
+    services::{ bus::EventBus, health::Health, mailer::Mailer, notifier::Notifier },
+    storage::StorageBackend,
+};
+
+#[derive(Serialize, Clone)]
 pub struct UserModel {
     pub name: String,
     pub email: String,
+    pub handle: Option<String>,
     pub password: String,
     pub uuid: Uuid,
+    pub avatar: Option<ImageModel>,
     pub created_at: NaiveDateTime,
 }
 
@@ -17,6 +27,7 @@ pub struct CreateUserModel {
     pub name: String,
     pub email: String,
     pub password: String,
+    pub handle: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -28,16 +39,23 @@ pub struct GetUserModel {
 #[derive(Serialize, Deserialize, Clone)]
 pub struct UpdateUserModel {
     pub name: String,
+    pub handle: Option<String>,
 }
 
-#[derive(serde::Serialize)]
-pub struct ErrorResponse {
-    pub error: String,
+#[derive(Deserialize)]
+pub struct SetAvatarModel {
+    /// An upload of the caller's made with the `avatar` purpose.
+    pub upload_id: Uuid,
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: DatabaseConnection,
+    pub storage: Arc<dyn StorageBackend>,
+    pub notifier: Notifier,
+    pub bus: Arc<dyn EventBus>,
+    pub mailer: Mailer,
+    pub health: Health,
 }
 
 #[derive(serde::Serialize)]
@@ -0,0 +1,125 @@
+use chrono::NaiveDateTime;
+use serde::{ Deserialize, Serialize };
+use uuid::Uuid;
+
+#[derive(Deserialize, Debug)]
+pub struct CreateWebhookModel {
+    pub url: String,
+    pub event_types: Vec<String>,
+    /// Generated when omitted.
+    pub secret: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct UpdateWebhookModel {
+    pub url: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub active: Option<bool>,
+}
+
+/// The secret is only returned when the webhook is created.
+#[derive(Serialize)]
+pub struct WebhookModel {
+    pub id: i32,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl WebhookModel {
+    pub fn new(webhook: entity::webhook::Model, event_types: Vec<String>) -> Self {
+        Self {
+            id: webhook.id,
+            url: webhook.url,
+            event_types,
+            active: webhook.active,
+            secret: None,
+            created_at: webhook.created_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct WebhookDeliveryModel {
+    pub id: i64,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: Option<NaiveDateTime>,
+    pub last_attempt_at: Option<NaiveDateTime>,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub delivered_at: Option<NaiveDateTime>,
+}
+
+impl From<entity::webhook_delivery::Model> for WebhookDeliveryModel {
+    fn from(delivery: entity::webhook_delivery::Model) -> Self {
+        Self {
+            id: delivery.id,
+            event_type: delivery.event_type,
+            payload: serde_json::from_str(&delivery.payload).unwrap_or_default(),
+            status: delivery.status,
+            attempts: delivery.attempts,
+            next_attempt_at: delivery.next_attempt_at,
+            last_attempt_at: delivery.last_attempt_at,
+            response_status: delivery.response_status,
+            last_error: delivery.last_error,
+            created_at: delivery.created_at,
+            delivered_at: delivery.delivered_at,
+        }
+    }
+}
+
+/// Deliveries are listed newest first; pass the last `id` seen as `before`.
+#[derive(Deserialize, Debug)]
+pub struct DeliveryPageQuery {
+    pub before: Option<i64>,
+    pub limit: Option<u64>,
+    pub status: Option<String>,
+}
+
+/// What webhooks are told about a user; never includes credentials or email.
+#[derive(Serialize)]
+pub struct WebhookUserModel {
+    pub uuid: Uuid,
+    pub name: String,
+    pub handle: Option<String>,
+}
+
+impl From<&entity::user::Model> for WebhookUserModel {
+    fn from(user: &entity::user::Model) -> Self {
+        Self {
+            uuid: user.uuid,
+            name: user.name.clone(),
+            handle: user.handle.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct WebhookPostModel {
+    pub id: i32,
+    pub author_uuid: Uuid,
+    pub title: String,
+    pub text: String,
+    pub format: String,
+    pub created_at: String,
+}
+
+impl WebhookPostModel {
+    pub fn new(post: &entity::post::Model, author_uuid: Uuid) -> Self {
+        Self {
+            id: post.id,
+            author_uuid,
+            title: post.title.clone(),
+            text: post.text.clone(),
+            format: post.format.clone(),
+            created_at: post.created_at.clone(),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use axum::{ middleware, routing::{ delete, get, post, put }, Router };
+use crate::{
+    handlers::{ admin_handlers, job_handlers, user_handlers, webhook_handlers },
+    models::user_models::AppState,
+    utils::guard::admin_guard,
+};
+
+/// Routes for administrators. The caller must merge these before layering `guard`.
+pub fn admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/users/{uuid}", delete(user_handlers::delete_user))
+        .route("/admin/users/{uuid}/restore", post(admin_handlers::restore_user))
+        .route("/admin/posts/{id}/restore", post(admin_handlers::restore_post))
+        .route("/admin/comments/{id}/restore", post(admin_handlers::restore_comment))
+        .route(
+            "/admin/webhooks",
+            get(webhook_handlers::list_webhooks).post(webhook_handlers::create_webhook)
+        )
+        .route(
+            "/admin/webhooks/{id}",
+            put(webhook_handlers::update_webhook).delete(webhook_handlers::delete_webhook)
+        )
+        .route("/admin/webhooks/{id}/ping", post(webhook_handlers::ping_webhook))
+        .route("/admin/webhooks/{id}/deliveries", get(webhook_handlers::list_deliveries))
+        .route(
+            "/admin/webhooks/{id}/deliveries/{delivery_id}/redeliver",
+            post(webhook_handlers::redeliver)
+        )
+        .route("/admin/jobs", get(job_handlers::list_jobs))
+        .route("/admin/jobs/summary", get(job_handlers::job_summary))
+        .route("/admin/jobs/{id}", get(job_handlers::get_job))
+        .route("/admin/jobs/{id}/retry", post(job_handlers::retry_job))
+        .route_layer(middleware::from_fn(admin_guard))
+}
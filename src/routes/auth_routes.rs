@@ -1,11 +1,21 @@
-use axum::{ http::method, routing::post, Router };
-use tower_http::cors::{ Any, CorsLayer };
-use crate::{ handlers::auth_handlers, models::user_models::AppState };
+use axum::{ routing::post, Router };
+use crate::{
+    config,
+    handlers::{ auth_handlers, email_handlers },
+    models::user_models::AppState,
+    utils::rate_limit,
+};
 pub fn auth_routes() -> Router<AppState> {
-    let cors = CorsLayer::new().allow_methods([method::Method::POST]).allow_origin(Any);
-
-    Router::new()
+    let config = config::get();
+    let router = Router::new()
         .route("/create-user", post(auth_handlers::create_user))
         .route("/get-user", post(auth_handlers::get_user))
-        .layer(cors)
+        .route("/password-reset", post(email_handlers::request_password_reset))
+        .route("/password-reset/{uuid}/{fingerprint}", post(email_handlers::reset_password));
+
+    // Sign-up, login and resets get a tighter limit than the rest of the API
+    match config.rate_limit.enabled {
+        true => rate_limit::limit(router, config.rate_limit.auth_requests_per_minute),
+        false => router,
+    }
 }
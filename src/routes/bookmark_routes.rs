@@ -0,0 +1,20 @@
+use axum::{ routing::{ get, put }, Router };
+use crate::{ handlers::bookmark_handlers, models::user_models::AppState };
+
+pub fn bookmark_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/bookmarks/collections",
+            get(bookmark_handlers::list_collections).post(bookmark_handlers::create_collection)
+        )
+        .route(
+            "/bookmarks/collections/{id}",
+            put(bookmark_handlers::update_collection).delete(bookmark_handlers::delete_collection)
+        )
+        .route("/bookmarks/collections/{id}/posts", get(bookmark_handlers::list_collection_posts))
+        .route(
+            "/bookmarks/collections/{id}/posts/{post_id}",
+            put(bookmark_handlers::add_bookmark).delete(bookmark_handlers::remove_bookmark)
+        )
+        .route("/bookmarks/shared/{token}", get(bookmark_handlers::list_shared_collection_posts))
+}
@@ -0,0 +1,9 @@
+use axum::{ routing::get, Router };
+use crate::{ handlers::health_handlers, models::user_models::AppState };
+
+/// Probes for the orchestrator. Merged outside auth and rate limiting.
+pub fn health_routes() -> Router<AppState> {
+    Router::new()
+        .route("/healthz", get(health_handlers::healthz))
+        .route("/readyz", get(health_handlers::readyz))
+}
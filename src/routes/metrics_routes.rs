@@ -0,0 +1,10 @@
+use axum::{ middleware, routing::get, Router };
+use crate::{ handlers::metrics_handlers, models::user_models::AppState, utils::guard::metrics_guard };
+
+/// The Prometheus scrape endpoint. Merged outside user auth and rate limiting,
+/// only when `metrics.enabled` is set, behind `metrics.token` if there is one.
+pub fn metrics_routes() -> Router<AppState> {
+    Router::new()
+        .route("/metrics", get(metrics_handlers::get_metrics))
+        .route_layer(middleware::from_fn(metrics_guard))
+}
@@ -1,2 +1,10 @@
 pub mod auth_routes;
 pub mod user_routes;
+pub mod admin_routes;
+pub mod public_routes;
+pub mod post_routes;
+pub mod bookmark_routes;
+pub mod notification_routes;
+pub mod realtime_routes;
+pub mod health_routes;
+pub mod metrics_routes;
@@ -0,0 +1,14 @@
+use axum::{ routing::{ get, post }, Router };
+use crate::{ handlers::notification_handlers, models::user_models::AppState };
+
+pub fn notification_routes() -> Router<AppState> {
+    Router::new()
+        .route("/notifications", get(notification_handlers::list_notifications))
+        .route("/notifications/unread-count", get(notification_handlers::unread_count))
+        .route("/notifications/read-all", post(notification_handlers::mark_all_read))
+        .route("/notifications/{id}/read", post(notification_handlers::mark_read))
+        .route(
+            "/me/notification-preferences",
+            get(notification_handlers::get_preferences).put(notification_handlers::update_preferences)
+        )
+}
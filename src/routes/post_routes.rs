@@ -0,0 +1,30 @@
+use axum::{ routing::{ get, put }, Router };
+use crate::{
+    handlers::{ comment_handlers, mention_handlers, post_handlers, reaction_handlers },
+    models::user_models::AppState,
+};
+
+pub fn post_routes() -> Router<AppState> {
+    Router::new()
+        .route("/posts", get(post_handlers::list_posts))
+        .route("/posts/{id}", get(post_handlers::get_post).put(post_handlers::update_post))
+        .route(
+            "/posts/{id}/comments",
+            get(comment_handlers::list_comments).post(comment_handlers::create_comment)
+        )
+        .route(
+            "/posts/{id}/reactions/{kind}",
+            put(reaction_handlers::add_post_reaction).delete(reaction_handlers::remove_post_reaction)
+        )
+        .route(
+            "/comments/{id}",
+            put(comment_handlers::update_comment).delete(comment_handlers::delete_comment)
+        )
+        .route(
+            "/comments/{id}/reactions/{kind}",
+            put(reaction_handlers::add_comment_reaction).delete(
+                reaction_handlers::remove_comment_reaction
+            )
+        )
+        .route("/hashtags/{tag}", get(mention_handlers::list_hashtag))
+}
@@ -0,0 +1,12 @@
+use axum::{ routing::get, Router };
+use crate::{ handlers::{ email_handlers, export_handlers, upload_handlers }, models::user_models::AppState };
+
+/// Routes that authorize requests themselves (e.g. signed links) instead of using `guard`.
+pub fn public_routes() -> Router<AppState> {
+    Router::new()
+        .route("/exports/{id}/download", get(export_handlers::download_export))
+        .route("/uploads/{id}", get(upload_handlers::get_upload))
+        .route("/uploads/{id}/variants/{label}", get(upload_handlers::get_upload_variant))
+        .route("/verify-email/{uuid}", get(email_handlers::verify_email))
+        .route("/unsubscribe/{uuid}", get(email_handlers::unsubscribe))
+}
@@ -0,0 +1,10 @@
+use axum::{ routing::get, Router };
+use crate::{ handlers::{ sse_handlers, ws_handlers }, models::user_models::AppState };
+
+/// Live connections authenticate themselves, since neither the WebSocket handshake
+/// nor `EventSource` can always carry an `Authorization` header for `guard`.
+pub fn realtime_routes() -> Router<AppState> {
+    Router::new()
+        .route("/ws", get(ws_handlers::ws_handler))
+        .route("/posts/{id}/comments/stream", get(sse_handlers::stream_comments))
+}
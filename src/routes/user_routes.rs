@@ -1,16 +1,53 @@
-use axum::{ http::Method, routing::{ delete, get, put, post }, Router };
-use tower_http::cors::{ Any, CorsLayer };
-use crate::{ handlers::{ post_handlers, user_handlers }, models::user_models::AppState };
+use axum::{ extract::DefaultBodyLimit, routing::{ delete, get, put, post }, Router };
+use crate::{
+    handlers::{
+        block_handlers,
+        email_handlers,
+        export_handlers,
+        follow_handlers,
+        mention_handlers,
+        post_handlers,
+        upload_handlers,
+        user_handlers,
+    },
+    models::user_models::AppState,
+};
 
 pub fn user_routes() -> Router<AppState> {
-    let cors = CorsLayer::new()
-        .allow_methods([Method::POST, Method::GET, Method::DELETE, Method::PUT])
-        .allow_origin(Any);
-
     Router::new()
         .route("/", get(user_handlers::get_all_users))
         .route("/{uuid}", put(user_handlers::update_user))
-        .route("/{uuid}", delete(user_handlers::delete_user))
+        .route("/me", delete(user_handlers::request_account_deletion))
+        .route("/me/avatar", put(user_handlers::set_avatar).delete(user_handlers::remove_avatar))
+        .route("/me/cancel-deletion", post(user_handlers::cancel_account_deletion))
+        .route("/me/export", post(export_handlers::request_export))
+        .route("/me/export/{id}", get(export_handlers::get_export))
+        .route(
+            "/users/{uuid}/follow",
+            put(follow_handlers::follow_user).delete(follow_handlers::unfollow_user)
+        )
+        .route("/users/{uuid}/followers", get(follow_handlers::list_followers))
+        .route("/users/{uuid}/following", get(follow_handlers::list_following))
+        .route("/users/{uuid}/mentions", get(mention_handlers::list_mentions))
+        .route(
+            "/users/{uuid}/block",
+            put(block_handlers::block_user).delete(block_handlers::unblock_user)
+        )
+        .route(
+            "/users/{uuid}/mute",
+            put(block_handlers::mute_user).delete(block_handlers::unmute_user)
+        )
+        .route("/me/blocks", get(block_handlers::list_blocks))
+        .route("/me/mutes", get(block_handlers::list_mutes))
+        .route("/me/verify-email", post(email_handlers::resend_verification))
+        .route("/me/resubscribe", post(email_handlers::resubscribe))
+        .route("/feed", get(follow_handlers::get_feed))
         .route("/user/post", post(post_handlers::create_post))
-        .layer(cors)
+        .route(
+            "/uploads",
+            post(upload_handlers::create_upload).layer(
+                // leave headroom for the multipart framing around the file itself
+                DefaultBodyLimit::max(upload_handlers::max_upload_bytes() + 64 * 1024)
+            )
+        )
 }
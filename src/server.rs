@@ -0,0 +1,178 @@
+use std::{ future::IntoFuture, io, net::SocketAddr, time::Duration };
+
+use axum::Router;
+use tokio::net::TcpListener;
+use tracing::{ info, warn };
+
+use crate::services::health::Health;
+
+/// Serves `app` until `health` begins shutting down, then stops accepting
+/// connections and gives in-flight requests up to `drain_timeout` to finish.
+/// Requests still running after that are dropped.
+pub async fn serve(listener: TcpListener, app: Router, health: Health, drain_timeout: Duration) -> io::Result<()> {
+    let shutdown = health.shutdown_token();
+    let server = axum
+        ::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+        .into_future();
+    let drain_deadline = async {
+        shutdown.cancelled().await;
+        tokio::time::sleep(drain_timeout).await;
+    };
+
+    tokio::select! {
+        served = server => served,
+        _ = drain_deadline => {
+            warn!("Requests still running after {}s, dropping them", drain_timeout.as_secs());
+            Ok(())
+        }
+    }
+}
+
+/// Resolves on Ctrl-C or SIGTERM, once `stop` has run.
+pub async fn shutdown_signal(health: Health, readiness_grace: Duration) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("Failed to listen for Ctrl-C");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix
+            ::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv().await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    info!("Shutdown signal received");
+    stop(&health, readiness_grace).await;
+}
+
+/// Fails readiness, keeps serving for `readiness_grace` so load balancers notice
+/// and route elsewhere, then stops accepting connections and tells workers and
+/// live connections to stop.
+pub async fn stop(health: &Health, readiness_grace: Duration) {
+    health.fail_readiness();
+    tokio::time::sleep(readiness_grace).await;
+    health.begin_shutdown();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::routing::get;
+    use sea_orm::DatabaseConnection;
+    use tokio::sync::Notify;
+
+    use super::*;
+    use crate::{
+        config::Config,
+        models::user_models::AppState,
+        routes::health_routes::health_routes,
+        services::{ bus::InProcessBus, mailer::Mailer, notifier::Notifier },
+        storage::local::LocalStorage,
+    };
+
+    async fn start(app: Router, drain_timeout: Duration) -> (SocketAddr, Health, tokio::task::JoinHandle<io::Result<()>>) {
+        start_with(app, Health::default(), drain_timeout).await
+    }
+
+    async fn start_with(
+        app: Router,
+        health: Health,
+        drain_timeout: Duration
+    ) -> (SocketAddr, Health, tokio::task::JoinHandle<io::Result<()>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(serve(listener, app, health.clone(), drain_timeout));
+        (addr, health, server)
+    }
+
+    #[tokio::test]
+    async fn readyz_fails_while_connections_are_still_accepted() {
+        let health = Health::default();
+        let bus = Arc::new(InProcessBus::default());
+        let state = AppState {
+            db: DatabaseConnection::Disconnected,
+            storage: Arc::new(LocalStorage::new(std::env::temp_dir())),
+            notifier: Notifier::new(DatabaseConnection::Disconnected, bus.clone()),
+            bus,
+            mailer: Mailer::from_config(&Config::default()),
+            health: health.clone(),
+        };
+        let (addr, health, server) = start_with(health_routes().with_state(state), health, Duration::from_secs(5)).await;
+
+        let stopping = tokio::spawn({
+            let health = health.clone();
+            async move { stop(&health, Duration::from_millis(500)).await }
+        });
+        while !health.is_shutting_down() {
+            tokio::task::yield_now().await;
+        }
+
+        let response = reqwest::get(format!("http://{}/readyz", addr)).await.expect("connections are still accepted");
+        assert_eq!(response.status(), 503);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["shutting_down"], true);
+        assert!(!server.is_finished());
+
+        stopping.await.unwrap();
+        tokio::time::timeout(Duration::from_secs(1), server).await.unwrap().unwrap().unwrap();
+        assert!(reqwest::get(format!("http://{}/readyz", addr)).await.is_err(), "new connections are refused");
+    }
+
+    #[tokio::test]
+    async fn in_flight_requests_complete_after_shutdown_begins() {
+        let started = Arc::new(Notify::new());
+        let app = Router::new().route(
+            "/slow",
+            get({
+                let started = started.clone();
+                move || async move {
+                    started.notify_one();
+                    tokio::time::sleep(Duration::from_millis(300)).await;
+                    "done"
+                }
+            })
+        );
+        let (addr, health, server) = start(app, Duration::from_secs(5)).await;
+
+        let request = tokio::spawn(reqwest::get(format!("http://{}/slow", addr)));
+        started.notified().await;
+        health.begin_shutdown();
+
+        let response = request.await.unwrap().unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.text().await.unwrap(), "done");
+
+        tokio::time::timeout(Duration::from_secs(1), server).await.unwrap().unwrap().unwrap();
+        assert!(reqwest::get(format!("http://{}/slow", addr)).await.is_err(), "new connections are refused");
+    }
+
+    #[tokio::test]
+    async fn shutdown_gives_up_on_requests_after_the_drain_timeout() {
+        let started = Arc::new(Notify::new());
+        let app = Router::new().route(
+            "/stuck",
+            get({
+                let started = started.clone();
+                move || async move {
+                    started.notify_one();
+                    std::future::pending::<()>().await;
+                }
+            })
+        );
+        let (addr, health, server) = start(app, Duration::from_millis(200)).await;
+
+        let _request = tokio::spawn(reqwest::get(format!("http://{}/stuck", addr)));
+        started.notified().await;
+        health.begin_shutdown();
+
+        tokio::time::timeout(Duration::from_secs(2), server).await.unwrap().unwrap().unwrap();
+    }
+}
@@ -0,0 +1,61 @@
+use chrono::{ Duration, Utc };
+use sea_orm::{ ConnectionTrait, DbErr };
+use sha2::{ Digest, Sha256 };
+use uuid::Uuid;
+
+use crate::{ mail::templates::EmailTemplate, services::mailer::Mailer, utils::signed_url };
+
+pub const VERIFICATION_TTL_HOURS: i64 = 48;
+pub const PASSWORD_RESET_TTL_MINUTES: i64 = 30;
+
+pub fn verification_path(uuid: Uuid) -> String {
+    format!("/verify-email/{}", uuid)
+}
+
+pub fn password_reset_path(uuid: Uuid, fingerprint: &str) -> String {
+    format!("/password-reset/{}/{}", uuid, fingerprint)
+}
+
+/// Changes whenever the password changes or sessions are revoked, so a reset link
+/// stops working once it has been used.
+pub fn password_fingerprint(user: &entity::user::Model) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(user.uuid.as_bytes());
+    hasher.update(user.password.as_bytes());
+    hasher.update(user.tokens_revoked_at.map(|revoked_at| revoked_at.to_string()).unwrap_or_default().as_bytes());
+    hex::encode(hasher.finalize())[..16].to_string()
+}
+
+pub async fn queue_verification<C: ConnectionTrait>(
+    db: &C,
+    mailer: &Mailer,
+    user: &entity::user::Model
+) -> Result<(), DbErr> {
+    let expires_at = Utc::now() + Duration::hours(VERIFICATION_TTL_HOURS);
+    let verify_url = mailer.url(&signed_url::sign(&verification_path(user.uuid), expires_at));
+    let context =
+        serde_json::json!({
+        "name": user.name,
+        "verify_url": verify_url,
+        "expires_in_hours": VERIFICATION_TTL_HOURS,
+    });
+    mailer.queue(db, user, EmailTemplate::Verification, context).await?;
+    Ok(())
+}
+
+pub async fn queue_password_reset<C: ConnectionTrait>(
+    db: &C,
+    mailer: &Mailer,
+    user: &entity::user::Model
+) -> Result<(), DbErr> {
+    let expires_at = Utc::now() + Duration::minutes(PASSWORD_RESET_TTL_MINUTES);
+    let path = password_reset_path(user.uuid, &password_fingerprint(user));
+    let context =
+        serde_json::json!({
+        "name": user.name,
+        "reset_url": mailer.url(&signed_url::sign(&path, expires_at)),
+        "expires_in_minutes": PASSWORD_RESET_TTL_MINUTES,
+    });
+    mailer.queue(db, user, EmailTemplate::PasswordReset, context).await?;
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use std::collections::HashSet;
+
+use sea_orm::{
+    ColumnTrait,
+    Condition,
+    ConnectionTrait,
+    DbErr,
+    EntityTrait,
+    PaginatorTrait,
+    QueryFilter,
+    QuerySelect,
+};
+
+use crate::utils::visibility::hidden_authors;
+
+/// Whether `a` has blocked `b` or `b` has blocked `a`.
+pub async fn is_blocked_either_way<C: ConnectionTrait>(db: &C, a: i32, b: i32) -> Result<bool, DbErr> {
+    use entity::block;
+
+    let count = block::Entity
+        ::find()
+        .filter(
+            Condition::any()
+                .add(block::Column::BlockerId.eq(a).and(block::Column::BlockedId.eq(b)))
+                .add(block::Column::BlockerId.eq(b).and(block::Column::BlockedId.eq(a)))
+        )
+        .count(db).await?;
+
+    Ok(count > 0)
+}
+
+/// Ids of the users whose content is hidden from `viewer_id`, for filtering live
+/// events that cannot go through `Visibility`.
+pub async fn hidden_author_ids<C: ConnectionTrait>(db: &C, viewer_id: i32) -> Result<HashSet<i32>, DbErr> {
+    let hidden: Vec<i32> = entity::user::Entity
+        ::find()
+        .select_only()
+        .column(entity::user::Column::Id)
+        .filter(entity::user::Column::Id.in_subquery(hidden_authors(viewer_id)))
+        .into_tuple()
+        .all(db).await?;
+
+    Ok(hidden.into_iter().collect())
+}
@@ -0,0 +1,74 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// An event fanned out to live connections. `author_id` lets each connection drop
+/// events from users its viewer has blocked or muted; `id` is set for events that
+/// were also persisted and can be replayed.
+#[derive(Clone, Debug, Serialize)]
+pub struct BusEvent {
+    pub topic: String,
+    pub event: String,
+    #[serde(skip)]
+    pub author_id: Option<i32>,
+    #[serde(skip)]
+    pub id: Option<i64>,
+    pub data: serde_json::Value,
+}
+
+impl BusEvent {
+    pub fn new(topic: String, event: &str, author_id: Option<i32>, data: impl Serialize) -> Self {
+        Self {
+            topic,
+            event: event.to_string(),
+            author_id,
+            id: None,
+            data: serde_json::to_value(data).unwrap_or_default(),
+        }
+    }
+}
+
+pub fn notifications_topic(user_id: i32) -> String {
+    format!("notifications:{}", user_id)
+}
+
+pub fn post_topic(post_id: i32) -> String {
+    format!("post:{}", post_id)
+}
+
+pub const FEED_TOPIC: &str = "feed";
+
+/// Publish/subscribe between request handlers and live connections. Every
+/// subscriber sees every event and filters by topic itself. The in-process bus
+/// only reaches connections on this instance; a Postgres `LISTEN/NOTIFY` backed
+/// implementation can relay events between instances behind the same trait.
+pub trait EventBus: Send + Sync {
+    fn publish(&self, event: BusEvent);
+
+    /// Slow subscribers that fall more than the channel capacity behind miss
+    /// events and are told how many through `RecvError::Lagged`.
+    fn subscribe(&self) -> broadcast::Receiver<BusEvent>;
+}
+
+pub struct InProcessBus {
+    sender: broadcast::Sender<BusEvent>,
+}
+
+impl Default for InProcessBus {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl EventBus for InProcessBus {
+    fn publish(&self, event: BusEvent) {
+        // An error only means nobody is connected
+        let _ = self.sender.send(event);
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<BusEvent> {
+        self.sender.subscribe()
+    }
+}
@@ -0,0 +1,53 @@
+use chrono::Utc;
+use sea_orm::{ sea_query::LockType, ActiveValue::Set, ConnectionTrait, DbErr, EntityTrait, QuerySelect };
+use serde::Serialize;
+
+use crate::services::bus::{ post_topic, BusEvent, EventBus };
+
+pub const COMMENT_CREATED: &str = "comment.created";
+pub const COMMENT_UPDATED: &str = "comment.updated";
+pub const COMMENT_DELETED: &str = "comment.deleted";
+
+/// Appends an event to the post's comment log. Call inside the transaction that
+/// changes the comment so the log never disagrees with the table.
+///
+/// Appends to one post's log are serialized until the transaction ends, so its
+/// events commit in id order: once an id is visible, every lower one for the post
+/// is too, and streams can resume from the last id they sent.
+pub async fn record_comment_event<C: ConnectionTrait>(
+    db: &C,
+    kind: &str,
+    comment: &entity::comment::Model,
+    payload: impl Serialize
+) -> Result<entity::comment_event::Model, DbErr> {
+    let payload = serde_json::to_string(&payload).map_err(|e| DbErr::Custom(e.to_string()))?;
+
+    // Unlike `FOR UPDATE`, this does not hold up comments being inserted meanwhile
+    entity::post::Entity
+        ::find_by_id(comment.post_id)
+        .select_only()
+        .column(entity::post::Column::Id)
+        .lock(LockType::NoKeyUpdate)
+        .into_tuple::<i32>()
+        .one(db).await?;
+
+    entity::comment_event::Entity
+        ::insert(entity::comment_event::ActiveModel {
+            post_id: Set(comment.post_id),
+            comment_id: Set(comment.id),
+            author_id: Set(comment.user_id),
+            kind: Set(kind.to_string()),
+            payload: Set(payload),
+            created_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        })
+        .exec_with_returning(db).await
+}
+
+/// Pushes a recorded event to live subscribers; call once it has committed.
+pub fn publish_comment_event(bus: &dyn EventBus, event: &entity::comment_event::Model) {
+    let data: serde_json::Value = serde_json::from_str(&event.payload).unwrap_or_default();
+    let mut bus_event = BusEvent::new(post_topic(event.post_id), &event.kind, Some(event.author_id), data);
+    bus_event.id = Some(event.id);
+    bus.publish(bus_event);
+}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use sea_orm::{ ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter };
+use uuid::Uuid;
+
+use crate::{
+    models::{ comment_models::CommentResponseModel, post_models::PostFormat },
+    services::{ mentions::mention_maps, reactions::{ reaction_summaries, TARGET_COMMENT } },
+    utils::markdown,
+};
+
+/// Builds API responses for a page of comments as seen by `viewer_id`. Comments
+/// are plain text, rendered on the fly with their mentions and hashtags linked.
+pub async fn comment_responses<C: ConnectionTrait>(
+    db: &C,
+    comments: Vec<entity::comment::Model>,
+    viewer_id: i32
+) -> Result<Vec<CommentResponseModel>, DbErr> {
+    let user_ids: Vec<i32> = comments
+        .iter()
+        .map(|comment| comment.user_id)
+        .collect();
+    let comment_ids: Vec<i32> = comments
+        .iter()
+        .map(|comment| comment.id)
+        .collect();
+
+    let authors: HashMap<i32, Uuid> = entity::user::Entity
+        ::find()
+        .filter(entity::user::Column::Id.is_in(user_ids))
+        .all(db).await?
+        .into_iter()
+        .map(|user| (user.id, user.uuid))
+        .collect();
+
+    let mut reactions = reaction_summaries(db, TARGET_COMMENT, comment_ids.clone(), viewer_id).await?;
+    let mut mentions = mention_maps(db, TARGET_COMMENT, comment_ids).await?;
+
+    Ok(
+        comments
+            .into_iter()
+            .map(|comment| CommentResponseModel {
+                id: comment.id,
+                post_id: comment.post_id,
+                html: markdown::render(
+                    &comment.text,
+                    PostFormat::Plain,
+                    &mentions.remove(&comment.id).unwrap_or_default()
+                ),
+                text: comment.text,
+                author_uuid: authors.get(&comment.user_id).copied(),
+                created_at: comment.created_at,
+                edited_at: comment.edited_at,
+                reactions: reactions.remove(&comment.id).unwrap_or_default(),
+            })
+            .collect()
+    )
+}
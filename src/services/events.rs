@@ -0,0 +1,148 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::{ ActiveValue::Set, ConnectionTrait, DbErr, EntityTrait };
+use serde::{ Deserialize, Serialize };
+use uuid::Uuid;
+
+use crate::{ models::user_models::AppState, telemetry::traces };
+
+/// Something that happened to the domain, recorded in the `outbox` by the same
+/// transaction that made the change. Events carry ids rather than snapshots, so
+/// subscribers load whatever they need once the change is visible.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    UserCreated {
+        user_id: i32,
+    },
+    UserUpdated {
+        user_id: i32,
+    },
+    /// The row may already be gone when a deletion is finalized, so the uuid
+    /// travels with the event.
+    UserDeleted {
+        user_id: i32,
+        uuid: Uuid,
+    },
+    PostPublished {
+        post_id: i32,
+        author_id: i32,
+        mentioned: Vec<i32>,
+    },
+    PostUpdated {
+        post_id: i32,
+        author_id: i32,
+        mentioned: Vec<i32>,
+    },
+    /// `event_id` is the `comment_event` row live streams resume from.
+    CommentAdded {
+        comment_id: i32,
+        post_id: i32,
+        author_id: i32,
+        post_author_id: i32,
+        mentioned: Vec<i32>,
+        event_id: i64,
+    },
+    CommentEdited {
+        comment_id: i32,
+        post_id: i32,
+        author_id: i32,
+        mentioned: Vec<i32>,
+        event_id: i64,
+    },
+    CommentDeleted {
+        comment_id: i32,
+        post_id: i32,
+        author_id: i32,
+        event_id: i64,
+    },
+    ReactionAdded {
+        user_id: i32,
+        target_type: String,
+        target_id: i32,
+        target_author_id: i32,
+    },
+    UserFollowed {
+        follower_id: i32,
+        followee_id: i32,
+    },
+}
+
+impl DomainEvent {
+    /// Stored in `outbox.event_type`.
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            DomainEvent::UserCreated { .. } => "user.created",
+            DomainEvent::UserUpdated { .. } => "user.updated",
+            DomainEvent::UserDeleted { .. } => "user.deleted",
+            DomainEvent::PostPublished { .. } => "post.published",
+            DomainEvent::PostUpdated { .. } => "post.updated",
+            DomainEvent::CommentAdded { .. } => "comment.added",
+            DomainEvent::CommentEdited { .. } => "comment.edited",
+            DomainEvent::CommentDeleted { .. } => "comment.deleted",
+            DomainEvent::ReactionAdded { .. } => "reaction.added",
+            DomainEvent::UserFollowed { .. } => "user.followed",
+        }
+    }
+
+    /// The user whose action, or account, the event records. Their audit log in
+    /// `audit_event` gets the event, which is what data exports include.
+    pub fn actor_id(&self) -> i32 {
+        match self {
+            DomainEvent::UserCreated { user_id }
+            | DomainEvent::UserUpdated { user_id }
+            | DomainEvent::UserDeleted { user_id, .. }
+            | DomainEvent::ReactionAdded { user_id, .. } => *user_id,
+            DomainEvent::PostPublished { author_id, .. }
+            | DomainEvent::PostUpdated { author_id, .. }
+            | DomainEvent::CommentAdded { author_id, .. }
+            | DomainEvent::CommentEdited { author_id, .. }
+            | DomainEvent::CommentDeleted { author_id, .. } => *author_id,
+            DomainEvent::UserFollowed { follower_id, .. } => *follower_id,
+        }
+    }
+}
+
+/// Writes the event to the outbox and to the actor's audit log. Pass the
+/// transaction making the change, so the event is dispatched if and only if the
+/// change commits. Dispatching it continues the current trace.
+pub async fn record<C: ConnectionTrait>(db: &C, event: &DomainEvent) -> Result<(), DbErr> {
+    let now = Utc::now().naive_utc();
+    let payload = serde_json::to_string(event).map_err(|e| DbErr::Custom(e.to_string()))?;
+
+    // The outbox is pruned once dispatched; the audit log lives as long as the user
+    entity::audit_event::Entity
+        ::insert(entity::audit_event::ActiveModel {
+            user_id: Set(event.actor_id()),
+            event_type: Set(event.event_type().to_string()),
+            payload: Set(payload.clone()),
+            created_at: Set(now),
+            ..Default::default()
+        })
+        .exec_without_returning(db).await?;
+
+    entity::outbox::Entity
+        ::insert(entity::outbox::ActiveModel {
+            event_type: Set(event.event_type().to_string()),
+            payload: Set(payload),
+            attempts: Set(0),
+            available_at: Set(now),
+            created_at: Set(now),
+            trace_context: Set(traces::current_traceparent()),
+            ..Default::default()
+        })
+        .exec_without_returning(db).await?;
+
+    Ok(())
+}
+
+/// Reacts to committed domain events. Delivery is at least once: a subscriber that
+/// fails is handed the event again later, so handling should tolerate repeats.
+#[async_trait]
+pub trait EventSubscriber: Send + Sync + 'static {
+    /// Stored in `outbox.pending_subscribers` while the subscriber still owes an
+    /// event, so it must stay stable.
+    fn name(&self) -> &'static str;
+
+    async fn handle(&self, state: &AppState, event: &DomainEvent) -> Result<(), String>;
+}
@@ -0,0 +1,69 @@
+use std::{ future::Future, sync::{ Arc, Mutex } };
+
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::error;
+
+/// What readiness knows beyond the database: the background workers this process
+/// started, and whether it has begun shutting down.
+#[derive(Clone, Default)]
+pub struct Health {
+    inner: Arc<HealthInner>,
+}
+
+#[derive(Default)]
+struct HealthInner {
+    unready: CancellationToken,
+    shutdown: CancellationToken,
+    workers: Mutex<Vec<(String, JoinHandle<()>)>>,
+}
+
+impl Health {
+    /// Spawns a long-running background task. One that exits or panics makes the
+    /// instance unready. Workers should return once `shutdown_token` is cancelled.
+    pub fn spawn_worker(&self, name: impl Into<String>, worker: impl Future<Output = ()> + Send + 'static) {
+        let handle = tokio::spawn(worker);
+        self.inner.workers.lock().expect("health lock poisoned").push((name.into(), handle));
+    }
+
+    /// Names of the workers that are running and of those that have stopped.
+    pub fn workers(&self) -> (Vec<String>, Vec<String>) {
+        let workers = self.inner.workers.lock().expect("health lock poisoned");
+        let (stopped, running): (Vec<_>, Vec<_>) = workers.iter().partition(|(_, handle)| handle.is_finished());
+        let names = |workers: Vec<&(String, JoinHandle<()>)>| workers.into_iter().map(|(name, _)| name.clone()).collect();
+        (names(running), names(stopped))
+    }
+
+    /// Waits for every worker to return.
+    pub async fn join_workers(&self) {
+        let workers = std::mem::take(&mut *self.inner.workers.lock().expect("health lock poisoned"));
+        for (name, handle) in workers {
+            if let Err(err) = handle.await {
+                error!("Worker {} did not stop cleanly: {}", name, err);
+            }
+        }
+    }
+
+    /// Fails readiness so load balancers stop sending traffic here, while requests
+    /// are still accepted and served.
+    pub fn fail_readiness(&self) {
+        self.inner.unready.cancel();
+    }
+
+    /// Stops accepting connections and tells workers and live connections to wind
+    /// down. Fails readiness too, if that has not happened yet.
+    pub fn begin_shutdown(&self) {
+        self.inner.unready.cancel();
+        self.inner.shutdown.cancel();
+    }
+
+    /// Whether readiness has been failed on the way to shutting down.
+    pub fn is_shutting_down(&self) -> bool {
+        self.inner.unready.is_cancelled()
+    }
+
+    /// Cancelled when shutdown begins.
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.inner.shutdown.clone()
+    }
+}
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use chrono::{ Duration, Utc };
+use sea_orm::{
+    sea_query::OnConflict,
+    ActiveValue::Set,
+    ConnectionTrait,
+    DbErr,
+    EntityTrait,
+    PaginatorTrait,
+};
+use tracing::{ debug, field, info_span, Instrument };
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    jobs::{ email_delivery::{ SendEmail, STATUS_PENDING }, queue },
+    mail::{ self, templates::{ EmailTemplate, Templates }, MailTransport, OutgoingEmail },
+    utils::signed_url,
+};
+
+/// Unsubscribe links keep working long after the email was sent.
+const UNSUBSCRIBE_LINK_TTL_DAYS: i64 = 365;
+
+pub const SUPPRESSION_UNSUBSCRIBED: &str = "unsubscribed";
+
+pub fn unsubscribe_path(uuid: Uuid) -> String {
+    format!("/unsubscribe/{}", uuid)
+}
+
+/// Renders templated emails and queues them for delivery. Emails are stored in
+/// `email_outbox` and sent by the `SendEmail` job, which retries on failure.
+#[derive(Clone)]
+pub struct Mailer {
+    transport: Arc<dyn MailTransport>,
+    templates: Arc<Templates>,
+    from: String,
+    base_url: String,
+}
+
+impl Mailer {
+    /// Sends through the configured transport as `mail.from`, linking back to
+    /// `server.base_url`.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            transport: mail::from_config(&config.mail),
+            templates: Arc::new(Templates::default()),
+            from: config.mail.from.clone(),
+            base_url: config.server.base_url.clone(),
+        }
+    }
+
+    /// An absolute link to a path on this server.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url.trim_end_matches('/'), path)
+    }
+
+    /// Renders the email for `recipient` and queues it. Pass the transaction making
+    /// the change that prompted the email, so it is only sent if that commits.
+    /// Returns `None` when the recipient unsubscribed from this kind of email.
+    pub async fn queue<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        recipient: &entity::user::Model,
+        template: EmailTemplate,
+        context: serde_json::Value
+    ) -> Result<Option<entity::email_outbox::Model>, DbErr> {
+        if !template.is_transactional() && is_suppressed(db, &recipient.email).await? {
+            debug!("Not sending {} to suppressed user {}", template.name(), recipient.uuid);
+            return Ok(None);
+        }
+
+        let mut context = context;
+        if let Some(fields) = context.as_object_mut() {
+            fields.insert("subject".to_string(), template.subject().into());
+            if !template.is_transactional() {
+                let expires_at = Utc::now() + Duration::days(UNSUBSCRIBE_LINK_TTL_DAYS);
+                let link = self.url(&signed_url::sign(&unsubscribe_path(recipient.uuid), expires_at));
+                fields.insert("unsubscribe_url".to_string(), link.into());
+            }
+        }
+        let (html, text) = self.templates.render(template, &context).map_err(DbErr::Custom)?;
+
+        let email = entity::email_outbox::Entity
+            ::insert(entity::email_outbox::ActiveModel {
+                recipient: Set(recipient.email.clone()),
+                template: Set(template.name().to_string()),
+                subject: Set(template.subject().to_string()),
+                html: Set(html),
+                text: Set(text),
+                status: Set(STATUS_PENDING.to_string()),
+                attempts: Set(0),
+                created_at: Set(Utc::now().naive_utc()),
+                ..Default::default()
+            })
+            .exec_with_returning(db).await?;
+        queue::enqueue(db, &(SendEmail { email_id: email.id })).await?;
+
+        Ok(Some(email))
+    }
+
+    /// Hands a stored email to the transport.
+    pub async fn deliver(&self, email: &entity::email_outbox::Model) -> Result<(), String> {
+        let span =
+            info_span!(
+            "mail.send",
+            email.id = email.id,
+            email.template = %email.template,
+            otel.name = %format!("send {}", email.template),
+            otel.kind = "client",
+            otel.status_code = field::Empty,
+        );
+        let outgoing = OutgoingEmail {
+            from: &self.from,
+            to: &email.recipient,
+            subject: &email.subject,
+            html: &email.html,
+            text: &email.text,
+        };
+        self.transport
+            .send(&outgoing)
+            .instrument(span.clone()).await
+            .inspect_err(|_| {
+                span.record("otel.status_code", "ERROR");
+            })
+    }
+}
+
+/// Suppressions are keyed by the lower-cased address.
+fn suppression_key(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+pub async fn is_suppressed<C: ConnectionTrait>(db: &C, email: &str) -> Result<bool, DbErr> {
+    let count = entity::email_suppression::Entity::find_by_id(suppression_key(email)).count(db).await?;
+    Ok(count > 0)
+}
+
+/// Suppressing an address twice keeps the first reason.
+pub async fn suppress<C: ConnectionTrait>(db: &C, email: &str, reason: &str) -> Result<(), DbErr> {
+    entity::email_suppression::Entity
+        ::insert(entity::email_suppression::ActiveModel {
+            email: Set(suppression_key(email)),
+            reason: Set(reason.to_string()),
+            created_at: Set(Utc::now().naive_utc()),
+        })
+        .on_conflict(OnConflict::column(entity::email_suppression::Column::Email).do_nothing().to_owned())
+        .exec_without_returning(db).await?;
+    Ok(())
+}
+
+pub async fn unsuppress<C: ConnectionTrait>(db: &C, email: &str) -> Result<(), DbErr> {
+    entity::email_suppression::Entity::delete_by_id(suppression_key(email)).exec(db).await?;
+    Ok(())
+}
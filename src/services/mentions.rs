@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+
+use chrono::{ NaiveDateTime, Utc };
+use sea_orm::{
+    sea_query::Query,
+    ActiveValue::Set,
+    ColumnTrait,
+    Condition,
+    ConnectionTrait,
+    DbErr,
+    EntityTrait,
+    JoinType,
+    QueryFilter,
+    QuerySelect,
+    QueryTrait,
+    RelationTrait,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::mention_models::LinkedContentModel,
+    services::{
+        comments::comment_responses,
+        posts::post_responses,
+        reactions::{ TARGET_COMMENT, TARGET_POST },
+    },
+    utils::{ mentions::{ extract_hashtags, extract_mentions }, soft_delete::SoftDelete, visibility::Visibility },
+};
+
+pub struct RecordedLinks {
+    /// Mentioned handles that resolved to a user, for rendering.
+    pub handles: HashMap<String, Uuid>,
+    /// Users mentioned now who were not mentioned by the previous text.
+    pub newly_mentioned: Vec<i32>,
+}
+
+/// Replaces the mentions and hashtags recorded for a post or comment with the
+/// ones in `text`. Handles nobody has claimed are not recorded.
+pub async fn record_links<C: ConnectionTrait>(
+    db: &C,
+    target_type: &str,
+    target_id: i32,
+    text: &str
+) -> Result<RecordedLinks, DbErr> {
+    use entity::{ hashtag, mention, user };
+
+    let previously_mentioned: Vec<i32> = mention::Entity
+        ::find()
+        .select_only()
+        .column(mention::Column::UserId)
+        .filter(mention::Column::TargetType.eq(target_type))
+        .filter(mention::Column::TargetId.eq(target_id))
+        .into_tuple()
+        .all(db).await?;
+
+    mention::Entity
+        ::delete_many()
+        .filter(mention::Column::TargetType.eq(target_type))
+        .filter(mention::Column::TargetId.eq(target_id))
+        .exec(db).await?;
+    hashtag::Entity
+        ::delete_many()
+        .filter(hashtag::Column::TargetType.eq(target_type))
+        .filter(hashtag::Column::TargetId.eq(target_id))
+        .exec(db).await?;
+
+    let now = Utc::now().naive_utc();
+    let handles = extract_mentions(text);
+    let mut recorded = RecordedLinks { handles: HashMap::new(), newly_mentioned: Vec::new() };
+
+    if !handles.is_empty() {
+        let users = user::Entity
+            ::find_active()
+            .filter(user::Column::Handle.is_in(handles))
+            .all(db).await?;
+
+        if !users.is_empty() {
+            mention::Entity
+                ::insert_many(
+                    users.iter().map(|user| mention::ActiveModel {
+                        target_type: Set(target_type.to_string()),
+                        target_id: Set(target_id),
+                        user_id: Set(user.id),
+                        created_at: Set(now),
+                    })
+                )
+                .exec_without_returning(db).await?;
+        }
+
+        recorded.newly_mentioned = users
+            .iter()
+            .map(|user| user.id)
+            .filter(|id| !previously_mentioned.contains(id))
+            .collect();
+        recorded.handles = users
+            .into_iter()
+            .filter_map(|user| user.handle.map(|handle| (handle, user.uuid)))
+            .collect();
+    }
+
+    let tags = extract_hashtags(text);
+    if !tags.is_empty() {
+        hashtag::Entity
+            ::insert_many(
+                tags.into_iter().map(|tag| hashtag::ActiveModel {
+                    tag: Set(tag),
+                    target_type: Set(target_type.to_string()),
+                    target_id: Set(target_id),
+                    created_at: Set(now),
+                })
+            )
+            .exec_without_returning(db).await?;
+    }
+
+    Ok(recorded)
+}
+
+/// Resolved mentions per target id, keyed by handle, for rendering a page of
+/// posts or comments.
+pub async fn mention_maps<C: ConnectionTrait>(
+    db: &C,
+    target_type: &str,
+    target_ids: Vec<i32>
+) -> Result<HashMap<i32, HashMap<String, Uuid>>, DbErr> {
+    use entity::{ mention, user };
+
+    if target_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let rows: Vec<(i32, Option<String>, Uuid)> = mention::Entity
+        ::find()
+        .select_only()
+        .column(mention::Column::TargetId)
+        .column(user::Column::Handle)
+        .column(user::Column::Uuid)
+        .join(JoinType::InnerJoin, mention::Relation::User.def())
+        .filter(mention::Column::TargetType.eq(target_type))
+        .filter(mention::Column::TargetId.is_in(target_ids))
+        .into_tuple()
+        .all(db).await?;
+
+    let mut maps: HashMap<i32, HashMap<String, Uuid>> = HashMap::new();
+    for (target_id, handle, uuid) in rows {
+        if let Some(handle) = handle {
+            maps.entry(target_id).or_default().insert(handle, uuid);
+        }
+    }
+    Ok(maps)
+}
+
+/// Restricts a mention or hashtag query to targets `viewer_id` can see.
+pub fn visible_targets(
+    target_type_column: impl ColumnTrait,
+    target_id_column: impl ColumnTrait,
+    viewer_id: i32
+) -> Condition {
+    let visible_posts = entity::post::Entity
+        ::find_visible_to(viewer_id)
+        .select_only()
+        .column(entity::post::Column::Id)
+        .into_query();
+    let visible_comments = entity::comment::Entity
+        ::find_visible_to(viewer_id)
+        .select_only()
+        .column(entity::comment::Column::Id)
+        .into_query();
+
+    Condition::any()
+        .add(
+            Condition::all()
+                .add(target_type_column.eq(TARGET_POST))
+                .add(target_id_column.in_subquery(visible_posts))
+        )
+        .add(
+            Condition::all()
+                .add(target_type_column.eq(TARGET_COMMENT))
+                .add(target_id_column.in_subquery(visible_comments))
+        )
+}
+
+/// Builds responses for mention or hashtag rows of `(target_type, target_id,
+/// linked_at)`, keeping their order.
+pub async fn linked_content<C: ConnectionTrait>(
+    db: &C,
+    rows: Vec<(String, i32, NaiveDateTime)>,
+    viewer_id: i32
+) -> Result<Vec<LinkedContentModel>, DbErr> {
+    let ids_of = |target_type: &str| -> Vec<i32> {
+        rows.iter()
+            .filter(|(row_type, _, _)| row_type == target_type)
+            .map(|(_, id, _)| *id)
+            .collect()
+    };
+
+    let posts = entity::post::Entity
+        ::find()
+        .filter(entity::post::Column::Id.is_in(ids_of(TARGET_POST)))
+        .all(db).await?;
+    let comments = entity::comment::Entity
+        ::find()
+        .filter(entity::comment::Column::Id.is_in(ids_of(TARGET_COMMENT)))
+        .all(db).await?;
+
+    let mut posts: HashMap<i32, _> = post_responses(db, posts, viewer_id).await?
+        .into_iter()
+        .map(|post| (post.id, post))
+        .collect();
+    let mut comments: HashMap<i32, _> = comment_responses(db, comments, viewer_id).await?
+        .into_iter()
+        .map(|comment| (comment.id, comment))
+        .collect();
+
+    Ok(
+        rows
+            .into_iter()
+            .filter_map(|(target_type, id, linked_at)| {
+                match target_type.as_str() {
+                    TARGET_POST => posts.remove(&id).map(|post| LinkedContentModel::Post { linked_at, post }),
+                    TARGET_COMMENT =>
+                        comments.remove(&id).map(|comment| LinkedContentModel::Comment { linked_at, comment }),
+                    _ => None,
+                }
+            })
+            .collect()
+    )
+}
+
+/// Deletes mentions and hashtags whose post or comment no longer exists.
+pub async fn delete_orphaned_links<C: ConnectionTrait>(db: &C) -> Result<(), DbErr> {
+    use entity::{ comment, hashtag, mention, post };
+
+    let post_ids = Query::select().column(post::Column::Id).from(post::Entity).to_owned();
+    let comment_ids = Query::select().column(comment::Column::Id).from(comment::Entity).to_owned();
+
+    mention::Entity
+        ::delete_many()
+        .filter(
+            Condition::any()
+                .add(
+                    Condition::all()
+                        .add(mention::Column::TargetType.eq(TARGET_POST))
+                        .add(mention::Column::TargetId.not_in_subquery(post_ids.clone()))
+                )
+                .add(
+                    Condition::all()
+                        .add(mention::Column::TargetType.eq(TARGET_COMMENT))
+                        .add(mention::Column::TargetId.not_in_subquery(comment_ids.clone()))
+                )
+        )
+        .exec(db).await?;
+
+    hashtag::Entity
+        ::delete_many()
+        .filter(
+            Condition::any()
+                .add(
+                    Condition::all()
+                        .add(hashtag::Column::TargetType.eq(TARGET_POST))
+                        .add(hashtag::Column::TargetId.not_in_subquery(post_ids))
+                )
+                .add(
+                    Condition::all()
+                        .add(hashtag::Column::TargetType.eq(TARGET_COMMENT))
+                        .add(hashtag::Column::TargetId.not_in_subquery(comment_ids))
+                )
+        )
+        .exec(db).await?;
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+pub mod posts;
+pub mod reactions;
+pub mod comments;
+pub mod blocks;
+pub mod mentions;
+pub mod notifier;
+pub mod bus;
+pub mod comment_events;
+pub mod webhooks;
+pub mod events;
+pub mod subscribers;
+pub mod mailer;
+pub mod account_emails;
+pub mod health;
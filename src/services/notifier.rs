@@ -0,0 +1,196 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue::Set,
+    ColumnTrait,
+    ConnectionTrait,
+    DatabaseConnection,
+    DbErr,
+    EntityTrait,
+    PaginatorTrait,
+    QueryFilter,
+    sea_query::{ Expr, OnConflict },
+};
+use crate::{
+    models::notification_models::NotificationModel,
+    services::bus::{ notifications_topic, BusEvent, EventBus },
+    utils::visibility::hidden_authors,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationKind {
+    Comment,
+    Reaction,
+    Follow,
+    Mention,
+}
+
+impl NotificationKind {
+    pub const ALL: [NotificationKind; 4] = [
+        NotificationKind::Comment,
+        NotificationKind::Reaction,
+        NotificationKind::Follow,
+        NotificationKind::Mention,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NotificationKind::Comment => "comment",
+            NotificationKind::Reaction => "reaction",
+            NotificationKind::Follow => "follow",
+            NotificationKind::Mention => "mention",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|candidate| candidate.as_str() == kind)
+    }
+}
+
+/// Something `actor_id` did that `recipient_id` may want to hear about. The target
+/// is the post or comment the event happened on, if any.
+#[derive(Clone, Debug)]
+pub struct NotificationEvent {
+    pub recipient_id: i32,
+    pub actor_id: i32,
+    pub kind: NotificationKind,
+    pub target: Option<(&'static str, i32)>,
+}
+
+/// Records in-app notifications and pushes them to the recipient's live
+/// connections. Driven by `NotificationSubscriber` once the change that caused the
+/// notification has committed.
+#[derive(Clone)]
+pub struct Notifier {
+    db: DatabaseConnection,
+    bus: Arc<dyn EventBus>,
+}
+
+impl Notifier {
+    pub fn new(db: DatabaseConnection, bus: Arc<dyn EventBus>) -> Self {
+        Self { db, bus }
+    }
+
+    pub async fn notify(&self, event: NotificationEvent) -> Result<(), DbErr> {
+        self.record(&event).await
+    }
+
+    pub async fn notify_mentions(
+        &self,
+        actor_id: i32,
+        target_type: &'static str,
+        target_id: i32,
+        mentioned: &[i32]
+    ) -> Result<(), DbErr> {
+        for &recipient_id in mentioned {
+            self.notify(NotificationEvent {
+                recipient_id,
+                actor_id,
+                kind: NotificationKind::Mention,
+                target: Some((target_type, target_id)),
+            }).await?;
+        }
+        Ok(())
+    }
+
+    /// Skips self-notifications, kinds the recipient turned off, and actors the
+    /// recipient has muted or blocked or who blocked the recipient. Mentions are
+    /// unique per recipient, target and actor, so an outbox retry that delivers the
+    /// same mention again neither stores nor pushes a second copy.
+    async fn record(&self, event: &NotificationEvent) -> Result<(), DbErr> {
+        if event.recipient_id == event.actor_id || !is_enabled(&self.db, event.recipient_id, event.kind).await? {
+            return Ok(());
+        }
+
+        let hidden = entity::user::Entity
+            ::find()
+            .filter(entity::user::Column::Id.eq(event.actor_id))
+            .filter(entity::user::Column::Id.in_subquery(hidden_authors(event.recipient_id)))
+            .count(&self.db).await?;
+        if hidden > 0 {
+            return Ok(());
+        }
+
+        let inserted = entity::notification::Entity
+            ::insert(entity::notification::ActiveModel {
+                user_id: Set(event.recipient_id),
+                actor_id: Set(Some(event.actor_id)),
+                kind: Set(event.kind.as_str().to_string()),
+                target_type: Set(event.target.map(|(target_type, _)| target_type.to_string())),
+                target_id: Set(event.target.map(|(_, target_id)| target_id)),
+                read_at: Set(None),
+                created_at: Set(Utc::now().naive_utc()),
+                ..Default::default()
+            })
+            .on_conflict(
+                OnConflict::columns([
+                    entity::notification::Column::UserId,
+                    entity::notification::Column::TargetType,
+                    entity::notification::Column::TargetId,
+                    entity::notification::Column::ActorId,
+                ])
+                    .target_and_where(Expr::col(entity::notification::Column::Kind).eq(NotificationKind::Mention.as_str()))
+                    .do_nothing()
+                    .to_owned()
+            )
+            .exec_with_returning_many(&self.db).await?;
+        let Some(notification) = inserted.into_iter().next() else {
+            return Ok(());
+        };
+
+        let actor_uuid = entity::user::Entity
+            ::find_by_id(event.actor_id)
+            .one(&self.db).await?
+            .map(|actor| actor.uuid);
+        self.bus.publish(
+            BusEvent::new(
+                notifications_topic(event.recipient_id),
+                "notification.created",
+                Some(event.actor_id),
+                NotificationModel::new(notification, actor_uuid)
+            )
+        );
+
+        Ok(())
+    }
+}
+
+/// Kinds are enabled unless the user stored a preference turning them off.
+pub async fn is_enabled<C: ConnectionTrait>(db: &C, user_id: i32, kind: NotificationKind) -> Result<bool, DbErr> {
+    let preference = entity::notification_preference::Entity
+        ::find_by_id((user_id, kind.as_str().to_string()))
+        .one(db).await?;
+
+    Ok(preference.is_none_or(|preference| preference.enabled))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sea_orm::{ ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter };
+
+    use super::Notifier;
+    use crate::{ services::{ bus::InProcessBus, reactions::TARGET_POST }, testing };
+
+    #[tokio::test]
+    async fn delivering_the_same_mentions_twice_notifies_once() {
+        let Some(db) = testing::database().await else { return };
+        let author = testing::create_user(&db).await;
+        let mentioned = testing::create_user(&db).await;
+        let post = testing::create_post(&db, author.id).await;
+        let notifier = Notifier::new(db.clone(), Arc::new(InProcessBus::default()));
+
+        for _ in 0..2 {
+            notifier.notify_mentions(author.id, TARGET_POST, post.id, &[mentioned.id]).await.unwrap();
+        }
+
+        let notifications = entity::notification::Entity
+            ::find()
+            .filter(entity::notification::Column::UserId.eq(mentioned.id))
+            .count(&db).await
+            .unwrap();
+        assert_eq!(notifications, 1);
+    }
+}
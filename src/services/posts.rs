@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use sea_orm::{
+    sea_query::{ Expr, SelectStatement },
+    ColumnTrait,
+    ConnectionTrait,
+    DbErr,
+    EntityTrait,
+    QueryFilter,
+    QuerySelect,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::{ post_models::{ PostFormat, PostResponseModel }, upload_models::ImageModel },
+    services::{ mentions::mention_maps, reactions::{ reaction_summaries, TARGET_POST } },
+    utils::markdown,
+};
+
+/// Builds API responses for a page of posts as seen by `viewer_id`, loading
+/// authors, images and reactions for the whole page at once rather than per post.
+pub async fn post_responses<C: ConnectionTrait>(
+    db: &C,
+    posts: Vec<entity::post::Model>,
+    viewer_id: i32
+) -> Result<Vec<PostResponseModel>, DbErr> {
+    let user_ids: Vec<i32> = posts
+        .iter()
+        .map(|post| post.user_id)
+        .collect();
+    let upload_ids: Vec<Uuid> = posts
+        .iter()
+        .filter_map(|post| post.upload_id)
+        .collect();
+
+    let authors: HashMap<i32, Uuid> = entity::user::Entity
+        ::find()
+        .filter(entity::user::Column::Id.is_in(user_ids))
+        .all(db).await?
+        .into_iter()
+        .map(|user| (user.id, user.uuid))
+        .collect();
+
+    let images = load_images(db, upload_ids).await?;
+    let post_ids = posts
+        .iter()
+        .map(|post| post.id)
+        .collect();
+    let mut reactions = reaction_summaries(db, TARGET_POST, post_ids, viewer_id).await?;
+    // Posts are rendered when written; only ones from before rendering existed
+    // have no HTML yet
+    let unrendered = posts
+        .iter()
+        .filter(|post| post.rendered_html.is_none())
+        .map(|post| post.id)
+        .collect();
+    let mut mentions = mention_maps(db, TARGET_POST, unrendered).await?;
+
+    let mut responses = Vec::with_capacity(posts.len());
+    for post in posts {
+        let format = PostFormat::from_column(&post.format);
+        let html = post.rendered_html.unwrap_or_else(|| {
+            markdown::render(&post.text, format, &mentions.remove(&post.id).unwrap_or_default())
+        });
+
+        responses.push(PostResponseModel {
+            id: post.id,
+            title: post.title,
+            text: post.text,
+            format,
+            html,
+            image: post.upload_id.and_then(|id| images.get(&id).cloned()),
+            author_uuid: authors.get(&post.user_id).copied(),
+            reactions: reactions.remove(&post.id).unwrap_or_default(),
+            created_at: post.created_at,
+        });
+    }
+
+    Ok(responses)
+}
+
+/// Posts linking to one of `users` in their cached HTML. It goes stale when such a
+/// user changes handle or is deleted; pass the ids to `render_posts` afterwards.
+pub async fn posts_mentioning<C: ConnectionTrait>(db: &C, users: SelectStatement) -> Result<Vec<i32>, DbErr> {
+    entity::mention::Entity
+        ::find()
+        .select_only()
+        .distinct()
+        .column(entity::mention::Column::TargetId)
+        .filter(entity::mention::Column::TargetType.eq(TARGET_POST))
+        .filter(entity::mention::Column::UserId.in_subquery(users))
+        .into_tuple()
+        .all(db).await
+}
+
+/// Renders the posts again and caches the result, linking mentions to whoever
+/// holds the handle now. Ids of posts that are gone are skipped.
+pub async fn render_posts<C: ConnectionTrait>(db: &C, post_ids: Vec<i32>) -> Result<(), DbErr> {
+    if post_ids.is_empty() {
+        return Ok(());
+    }
+
+    let posts = entity::post::Entity
+        ::find()
+        .filter(entity::post::Column::Id.is_in(post_ids.clone()))
+        .all(db).await?;
+    let mut mentions = mention_maps(db, TARGET_POST, post_ids).await?;
+
+    for post in posts {
+        let post_mentions = mentions.remove(&post.id).unwrap_or_default();
+        let html = markdown::render(&post.text, PostFormat::from_column(&post.format), &post_mentions);
+        entity::post::Entity
+            ::update_many()
+            .col_expr(entity::post::Column::RenderedHtml, Expr::value(html))
+            .filter(entity::post::Column::Id.eq(post.id))
+            .exec(db).await?;
+    }
+
+    Ok(())
+}
+
+/// Uploads by id with all of their variants, for embedding in responses.
+pub async fn load_images<C: ConnectionTrait>(
+    db: &C,
+    upload_ids: Vec<Uuid>
+) -> Result<HashMap<Uuid, ImageModel>, DbErr> {
+    if upload_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut variants: HashMap<Uuid, Vec<entity::upload_variant::Model>> = HashMap::new();
+    for variant in entity::upload_variant::Entity
+        ::find()
+        .filter(entity::upload_variant::Column::UploadId.is_in(upload_ids.clone()))
+        .all(db).await? {
+        variants.entry(variant.upload_id).or_default().push(variant);
+    }
+
+    Ok(
+        entity::upload::Entity
+            ::find()
+            .filter(entity::upload::Column::Id.is_in(upload_ids))
+            .all(db).await?
+            .into_iter()
+            .map(|upload| {
+                let upload_variants = variants.remove(&upload.id).unwrap_or_default();
+                (upload.id, ImageModel::new(upload, upload_variants))
+            })
+            .collect()
+    )
+}
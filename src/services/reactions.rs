@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use sea_orm::{
+    sea_query::{ Expr, Query },
+    ColumnTrait,
+    ConnectionTrait,
+    DbErr,
+    EntityTrait,
+    QueryFilter,
+    QueryOrder,
+    QuerySelect,
+};
+
+use crate::models::reaction_models::ReactionCountModel;
+
+pub const TARGET_POST: &str = "post";
+pub const TARGET_COMMENT: &str = "comment";
+
+/// Counts reactions per kind for every target in one grouped query, flagging the
+/// kinds `viewer_id` used.
+pub async fn reaction_summaries<C: ConnectionTrait>(
+    db: &C,
+    target_type: &str,
+    target_ids: Vec<i32>,
+    viewer_id: i32
+) -> Result<HashMap<i32, Vec<ReactionCountModel>>, DbErr> {
+    use entity::reaction;
+
+    if target_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let rows: Vec<(i32, String, i64, bool)> = reaction::Entity
+        ::find()
+        .select_only()
+        .column(reaction::Column::TargetId)
+        .column(reaction::Column::Kind)
+        .column_as(reaction::Column::Id.count(), "count")
+        .column_as(
+            Expr::cust_with_values("BOOL_OR(\"reaction\".\"user_id\" = $1)", [viewer_id]),
+            "reacted_by_me"
+        )
+        .filter(reaction::Column::TargetType.eq(target_type))
+        .filter(reaction::Column::TargetId.is_in(target_ids))
+        .group_by(reaction::Column::TargetId)
+        .group_by(reaction::Column::Kind)
+        .order_by_asc(reaction::Column::Kind)
+        .into_tuple()
+        .all(db).await?;
+
+    let mut summaries: HashMap<i32, Vec<ReactionCountModel>> = HashMap::new();
+    for (target_id, kind, count, reacted_by_me) in rows {
+        summaries.entry(target_id).or_default().push(ReactionCountModel { kind, count, reacted_by_me });
+    }
+
+    Ok(summaries)
+}
+
+/// Reactions are not foreign keys to their polymorphic targets, so they must be
+/// cleared whenever posts or comments are hard-deleted.
+pub async fn delete_orphaned_reactions<C: ConnectionTrait>(db: &C) -> Result<(), DbErr> {
+    use entity::{ comment, post, reaction };
+
+    reaction::Entity
+        ::delete_many()
+        .filter(reaction::Column::TargetType.eq(TARGET_POST))
+        .filter(
+            reaction::Column::TargetId.not_in_subquery(
+                Query::select().column(post::Column::Id).from(post::Entity).to_owned()
+            )
+        )
+        .exec(db).await?;
+
+    reaction::Entity
+        ::delete_many()
+        .filter(reaction::Column::TargetType.eq(TARGET_COMMENT))
+        .filter(
+            reaction::Column::TargetId.not_in_subquery(
+                Query::select().column(comment::Column::Id).from(comment::Entity).to_owned()
+            )
+        )
+        .exec(db).await?;
+
+    Ok(())
+}
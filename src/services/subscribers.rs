@@ -0,0 +1,199 @@
+use async_trait::async_trait;
+use sea_orm::{ EntityTrait, TransactionTrait };
+
+use crate::{
+    models::{
+        user_models::AppState,
+        webhook_models::{ WebhookPostModel, WebhookUserModel },
+    },
+    services::{
+        account_emails::queue_verification,
+        bus::{ BusEvent, FEED_TOPIC },
+        comment_events::publish_comment_event,
+        events::{ DomainEvent, EventSubscriber },
+        notifier::{ NotificationEvent, NotificationKind },
+        posts::post_responses,
+        reactions::{ TARGET_COMMENT, TARGET_POST },
+        webhooks,
+    },
+};
+
+/// In-app notifications for comments, mentions, reactions and follows.
+pub struct NotificationSubscriber;
+
+#[async_trait]
+impl EventSubscriber for NotificationSubscriber {
+    fn name(&self) -> &'static str {
+        "notifications"
+    }
+
+    async fn handle(&self, state: &AppState, event: &DomainEvent) -> Result<(), String> {
+        let notifier = &state.notifier;
+        let outcome = match event {
+            DomainEvent::PostPublished { post_id, author_id, mentioned } | DomainEvent::PostUpdated {
+                post_id,
+                author_id,
+                mentioned,
+            } => notifier.notify_mentions(*author_id, TARGET_POST, *post_id, mentioned).await,
+            DomainEvent::CommentAdded { comment_id, author_id, post_author_id, mentioned, .. } => {
+                notifier.notify(NotificationEvent {
+                    recipient_id: *post_author_id,
+                    actor_id: *author_id,
+                    kind: NotificationKind::Comment,
+                    target: Some((TARGET_COMMENT, *comment_id)),
+                }).await.and(notifier.notify_mentions(*author_id, TARGET_COMMENT, *comment_id, mentioned).await)
+            }
+            DomainEvent::CommentEdited { comment_id, author_id, mentioned, .. } =>
+                notifier.notify_mentions(*author_id, TARGET_COMMENT, *comment_id, mentioned).await,
+            DomainEvent::ReactionAdded { user_id, target_type, target_id, target_author_id } => {
+                let target_type = match target_type.as_str() {
+                    TARGET_POST => TARGET_POST,
+                    _ => TARGET_COMMENT,
+                };
+                notifier.notify(NotificationEvent {
+                    recipient_id: *target_author_id,
+                    actor_id: *user_id,
+                    kind: NotificationKind::Reaction,
+                    target: Some((target_type, *target_id)),
+                }).await
+            }
+            DomainEvent::UserFollowed { follower_id, followee_id } =>
+                notifier.notify(NotificationEvent {
+                    recipient_id: *followee_id,
+                    actor_id: *follower_id,
+                    kind: NotificationKind::Follow,
+                    target: None,
+                }).await,
+            _ => Ok(()),
+        };
+
+        outcome.map_err(|e| format!("Failed to notify: {}", e))
+    }
+}
+
+/// Pushes new posts to the feed and comment changes to the post's live streams.
+pub struct RealtimeSubscriber;
+
+#[async_trait]
+impl EventSubscriber for RealtimeSubscriber {
+    fn name(&self) -> &'static str {
+        "realtime"
+    }
+
+    async fn handle(&self, state: &AppState, event: &DomainEvent) -> Result<(), String> {
+        let db = &state.db;
+        match event {
+            DomainEvent::PostPublished { post_id, author_id, .. } => {
+                let post = entity::post::Entity
+                    ::find_by_id(*post_id)
+                    .one(db).await
+                    .map_err(|e| format!("Failed to load post: {}", e))?;
+                let Some(post) = post.filter(|post| post.deleted_at.is_none()) else {
+                    return Ok(());
+                };
+                let responses = post_responses(db, vec![post], *author_id).await.map_err(|e|
+                    format!("Failed to load post: {}", e)
+                )?;
+                for response in responses {
+                    state.bus.publish(
+                        BusEvent::new(FEED_TOPIC.to_string(), "post.created", Some(*author_id), response)
+                    );
+                }
+            }
+            | DomainEvent::CommentAdded { event_id, .. }
+            | DomainEvent::CommentEdited { event_id, .. }
+            | DomainEvent::CommentDeleted { event_id, .. } => {
+                let comment_event = entity::comment_event::Entity
+                    ::find_by_id(*event_id)
+                    .one(db).await
+                    .map_err(|e| format!("Failed to load comment event: {}", e))?;
+                if let Some(comment_event) = comment_event {
+                    publish_comment_event(state.bus.as_ref(), &comment_event);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Queues outgoing webhook deliveries for the event types webhooks subscribe to.
+pub struct WebhookSubscriber;
+
+#[async_trait]
+impl EventSubscriber for WebhookSubscriber {
+    fn name(&self) -> &'static str {
+        "webhooks"
+    }
+
+    async fn handle(&self, state: &AppState, event: &DomainEvent) -> Result<(), String> {
+        let db = &state.db;
+        let load_error = |e: sea_orm::DbErr| format!("Failed to load webhook data: {}", e);
+        let enqueue_error = |e: sea_orm::DbErr| format!("Failed to queue webhooks: {}", e);
+
+        let (event_type, data) = match event {
+            DomainEvent::UserCreated { user_id } | DomainEvent::UserUpdated { user_id } => {
+                let Some(user) = entity::user::Entity::find_by_id(*user_id).one(db).await.map_err(load_error)? else {
+                    return Ok(());
+                };
+                let event_type = match event {
+                    DomainEvent::UserCreated { .. } => webhooks::USER_CREATED,
+                    _ => webhooks::USER_UPDATED,
+                };
+                (event_type, serde_json::to_value(WebhookUserModel::from(&user)).unwrap_or_default())
+            }
+            DomainEvent::UserDeleted { uuid, .. } => (webhooks::USER_DELETED, serde_json::json!({"uuid": uuid})),
+            DomainEvent::PostPublished { post_id, .. } | DomainEvent::PostUpdated { post_id, .. } => {
+                let post = entity::post::Entity::find_by_id(*post_id).one(db).await.map_err(load_error)?;
+                let Some(post) = post.filter(|post| post.deleted_at.is_none()) else {
+                    return Ok(());
+                };
+                let Some(author) = entity::user::Entity
+                    ::find_by_id(post.user_id)
+                    .one(db).await
+                    .map_err(load_error)? else {
+                    return Ok(());
+                };
+                let event_type = match event {
+                    DomainEvent::PostPublished { .. } => webhooks::POST_CREATED,
+                    _ => webhooks::POST_UPDATED,
+                };
+                (event_type, serde_json::to_value(WebhookPostModel::new(&post, author.uuid)).unwrap_or_default())
+            }
+            _ => {
+                return Ok(());
+            }
+        };
+
+        // Deliveries and their jobs go in together, or not at all
+        let txn = db.begin().await.map_err(enqueue_error)?;
+        webhooks::enqueue(&txn, event_type, data).await.map_err(enqueue_error)?;
+        txn.commit().await.map_err(enqueue_error)
+    }
+}
+
+/// Emails new users a link to verify their address.
+pub struct MailSubscriber;
+
+#[async_trait]
+impl EventSubscriber for MailSubscriber {
+    fn name(&self) -> &'static str {
+        "mail"
+    }
+
+    async fn handle(&self, state: &AppState, event: &DomainEvent) -> Result<(), String> {
+        let DomainEvent::UserCreated { user_id } = event else {
+            return Ok(());
+        };
+        let db = &state.db;
+        let mail_error = |e: sea_orm::DbErr| format!("Failed to queue verification email: {}", e);
+
+        let user = entity::user::Entity::find_by_id(*user_id).one(db).await.map_err(mail_error)?;
+        let Some(user) = user.filter(|user| user.email_verified_at.is_none() && user.deleted_at.is_none()) else {
+            return Ok(());
+        };
+        let txn = db.begin().await.map_err(mail_error)?;
+        queue_verification(&txn, &state.mailer, &user).await.map_err(mail_error)?;
+        txn.commit().await.map_err(mail_error)
+    }
+}
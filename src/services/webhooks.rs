@@ -0,0 +1,122 @@
+use chrono::Utc;
+use hmac::{ Hmac, KeyInit, Mac };
+use sea_orm::{
+    ActiveValue::Set,
+    ColumnTrait,
+    ConnectionTrait,
+    DbErr,
+    EntityTrait,
+    JoinType,
+    QueryFilter,
+    QuerySelect,
+    RelationTrait,
+};
+use serde::Serialize;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::jobs::{ queue, webhook_delivery::{ DeliverWebhook, STATUS_PENDING } };
+
+pub const USER_CREATED: &str = "user.created";
+pub const USER_UPDATED: &str = "user.updated";
+pub const USER_DELETED: &str = "user.deleted";
+pub const POST_CREATED: &str = "post.created";
+pub const POST_UPDATED: &str = "post.updated";
+/// Sent only on request, to check a receiver is reachable.
+pub const PING: &str = "ping";
+
+/// Event types a webhook may subscribe to.
+pub const WEBHOOK_EVENT_TYPES: &[&str] = &[USER_CREATED, USER_UPDATED, USER_DELETED, POST_CREATED, POST_UPDATED];
+
+pub const SIGNATURE_HEADER: &str = "x-webhook-signature";
+pub const TIMESTAMP_HEADER: &str = "x-webhook-timestamp";
+pub const EVENT_HEADER: &str = "x-webhook-event";
+pub const DELIVERY_HEADER: &str = "x-webhook-delivery";
+
+/// The body every delivery carries. `id` identifies the event, so receivers can
+/// drop duplicates when a delivery is retried or redelivered.
+#[derive(Serialize)]
+struct Envelope<'a, T: Serialize> {
+    id: Uuid,
+    #[serde(rename = "type")]
+    event_type: &'a str,
+    created_at: chrono::NaiveDateTime,
+    data: T,
+}
+
+/// Queues a delivery of the event to every active webhook subscribed to it. Call
+/// inside a transaction so each delivery is written together with its job.
+pub async fn enqueue<C: ConnectionTrait>(db: &C, event_type: &str, data: impl Serialize) -> Result<(), DbErr> {
+    let webhook_ids: Vec<i32> = entity::webhook::Entity
+        ::find()
+        .select_only()
+        .column(entity::webhook::Column::Id)
+        .join(JoinType::InnerJoin, entity::webhook::Relation::WebhookEventType.def())
+        .filter(entity::webhook::Column::Active.eq(true))
+        .filter(entity::webhook_event_type::Column::EventType.eq(event_type))
+        .into_tuple()
+        .all(db).await?;
+
+    enqueue_for(db, webhook_ids, event_type, data).await
+}
+
+/// Queues a delivery of the event to the given webhooks regardless of what they
+/// subscribe to.
+pub async fn enqueue_for<C: ConnectionTrait>(
+    db: &C,
+    webhook_ids: Vec<i32>,
+    event_type: &str,
+    data: impl Serialize
+) -> Result<(), DbErr> {
+    if webhook_ids.is_empty() {
+        return Ok(());
+    }
+
+    let now = Utc::now().naive_utc();
+    let payload = serde_json
+        ::to_string(
+            &(Envelope {
+                id: Uuid::new_v4(),
+                event_type,
+                created_at: now,
+                data,
+            })
+        )
+        .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+    let deliveries = entity::webhook_delivery::Entity
+        ::insert_many(
+            webhook_ids.into_iter().map(|webhook_id| entity::webhook_delivery::ActiveModel {
+                webhook_id: Set(webhook_id),
+                event_type: Set(event_type.to_string()),
+                payload: Set(payload.clone()),
+                status: Set(STATUS_PENDING.to_string()),
+                attempts: Set(0),
+                next_attempt_at: Set(Some(now)),
+                created_at: Set(now),
+                ..Default::default()
+            })
+        )
+        .exec_with_returning_many(db).await?;
+
+    for delivery in deliveries {
+        queue::enqueue(db, &(DeliverWebhook { delivery_id: delivery.id })).await?;
+    }
+
+    Ok(())
+}
+
+/// Signs `"{timestamp}.{body}"` with the webhook's secret. Receivers recompute it
+/// and reject stale timestamps so a captured delivery cannot be replayed later.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// A random secret for webhooks created without one.
+pub fn generate_secret() -> String {
+    format!("whsec_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
@@ -0,0 +1,55 @@
+use std::{ io::ErrorKind, path::{ Component, Path, PathBuf } };
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use super::StorageBackend;
+
+/// Stores objects as files under a root directory.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        LocalStorage { root: root.into() }
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf, String> {
+        let relative = Path::new(key);
+        // Keys are generated by us, but never let one escape the root directory
+        if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(format!("Invalid storage key: {}", key));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for LocalStorage {
+    async fn put(&self, key: &str, bytes: Bytes, _content_type: &str) -> Result<(), String> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs
+                ::create_dir_all(parent).await
+                .map_err(|e| format!("Failed to create storage dir: {}", e))?;
+        }
+        tokio::fs::write(&path, &bytes).await.map_err(|e| format!("Failed to write object: {}", e))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>, String> {
+        match tokio::fs::read(self.path_for(key)?).await {
+            Ok(bytes) => Ok(Some(Bytes::from(bytes))),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(format!("Failed to read object: {}", err)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        match tokio::fs::remove_file(self.path_for(key)?).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(format!("Failed to delete object: {}", err)),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use tracing::error;
+
+use crate::config::{ StorageConfig, StorageKind };
+
+pub mod local;
+pub mod s3;
+
+/// Where uploaded files live. Keys are opaque, `/`-separated object names.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    async fn put(&self, key: &str, bytes: Bytes, content_type: &str) -> Result<(), String>;
+
+    /// Returns `Ok(None)` when nothing is stored under `key`.
+    async fn get(&self, key: &str) -> Result<Option<Bytes>, String>;
+
+    async fn delete(&self, key: &str) -> Result<(), String>;
+}
+
+/// Builds the backend selected by `storage.backend`.
+pub fn from_config(config: &StorageConfig) -> Result<Arc<dyn StorageBackend>, String> {
+    Ok(match config.backend {
+        StorageKind::S3 => Arc::new(s3::S3Storage::from_config(&config.s3)?),
+        StorageKind::Local => Arc::new(local::LocalStorage::new(&config.local_root)),
+    })
+}
+
+/// Best-effort removal of objects whose rows are already gone; failures are only logged.
+pub async fn delete_objects(storage: &dyn StorageBackend, keys: Vec<String>) {
+    for key in keys {
+        if let Err(err) = storage.delete(&key).await {
+            error!("Failed to delete stored object {}: {}", key, err);
+        }
+    }
+}
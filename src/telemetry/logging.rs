@@ -0,0 +1,25 @@
+use tracing::Subscriber;
+use tracing_subscriber::{ registry::LookupSpan, EnvFilter, Layer };
+
+use crate::config::{ LogFormat, LoggingConfig };
+
+/// Writes events at or above `logging.level` to stdout in `logging.format`.
+pub fn layer<S>(config: &LoggingConfig) -> Box<dyn Layer<S> + Send + Sync>
+    where S: Subscriber + for<'span> LookupSpan<'span>
+{
+    let filter = EnvFilter::new(&config.level);
+    match config.format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().with_filter(filter).boxed(),
+        LogFormat::Pretty => tracing_subscriber::fmt::layer().pretty().with_filter(filter).boxed(),
+        // Each line lists its enclosing spans and their fields, so it carries the
+        // request id and user
+        LogFormat::Json =>
+            tracing_subscriber::fmt
+                ::layer()
+                .json()
+                .with_current_span(false)
+                .with_span_list(true)
+                .with_filter(filter)
+                .boxed(),
+    }
+}
@@ -0,0 +1,133 @@
+use std::{ collections::BTreeSet, sync::{ Mutex, OnceLock }, time::{ Duration, Instant } };
+
+use ::metrics::{ counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram, Unit };
+use axum::{ extract::{ MatchedPath, Request }, middleware::Next, response::Response };
+use metrics_exporter_prometheus::{ Matcher, PrometheusBuilder, PrometheusHandle };
+use sea_orm::{ metric::Info, DatabaseConnection };
+
+use crate::{ services::health::Health, telemetry::query_operation };
+
+const HTTP_REQUESTS: &str = "http_requests_total";
+const HTTP_DURATION: &str = "http_request_duration_seconds";
+const DB_QUERY_DURATION: &str = "db_query_duration_seconds";
+const DB_POOL_CONNECTIONS: &str = "db_pool_connections";
+const DB_POOL_MAX_CONNECTIONS: &str = "db_pool_max_connections";
+const WEBSOCKET_CONNECTIONS: &str = "websocket_connections_active";
+const JOB_QUEUE_DEPTH: &str = "job_queue_depth";
+const LOGINS: &str = "auth_logins_total";
+
+/// Request and query latencies span sub-millisecond lookups to slow exports.
+const DURATION_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+/// How often histogram samples are folded into buckets between scrapes.
+const UPKEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Installs the global Prometheus recorder and its upkeep worker. Metrics recorded
+/// before this, or when it is never called, are discarded.
+pub fn install(health: &Health) {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("duration_seconds".to_string()), DURATION_BUCKETS)
+        .expect("Duration buckets are not empty")
+        .install_recorder()
+        .expect("Failed to install the metrics recorder");
+
+    describe_counter!(HTTP_REQUESTS, "HTTP requests by method, matched route and status");
+    describe_histogram!(HTTP_DURATION, Unit::Seconds, "HTTP request latency by method, matched route and status");
+    describe_histogram!(DB_QUERY_DURATION, Unit::Seconds, "Database statement latency by operation and outcome");
+    describe_gauge!(DB_POOL_CONNECTIONS, "Database pool connections by state");
+    describe_gauge!(DB_POOL_MAX_CONNECTIONS, "Database pool size limit");
+    describe_gauge!(WEBSOCKET_CONNECTIONS, "Open WebSocket connections");
+    describe_gauge!(JOB_QUEUE_DEPTH, "Jobs waiting or running, by queue and status");
+    describe_counter!(LOGINS, "Login attempts by outcome");
+    // Report zero rather than nothing until the first connection opens
+    gauge!(WEBSOCKET_CONNECTIONS).set(0.0);
+
+    let shutdown = health.shutdown_token();
+    let upkeep = handle.clone();
+    health.spawn_worker("metrics_upkeep", async move {
+        let mut ticker = tokio::time::interval(UPKEEP_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => upkeep.run_upkeep(),
+                _ = shutdown.cancelled() => break,
+            }
+        }
+    });
+
+    HANDLE.set(handle).expect("Metrics were installed twice");
+}
+
+/// The current metrics in the Prometheus text format, or `None` if metrics are off.
+pub fn render() -> Option<String> {
+    HANDLE.get().map(PrometheusHandle::render)
+}
+
+/// Counts and times every request, labelled by the route pattern it matched
+/// rather than its path, so ids do not blow up the number of series.
+pub async fn track_http(req: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(req).await;
+
+    let labels = [("method", method), ("route", route), ("status", response.status().as_u16().to_string())];
+    counter!(HTTP_REQUESTS, &labels).increment(1);
+    histogram!(HTTP_DURATION, &labels).record(started.elapsed().as_secs_f64());
+    response
+}
+
+pub fn record_query(info: &Info<'_>) {
+    let outcome = if info.failed { "error" } else { "ok" };
+    histogram!(DB_QUERY_DURATION, "operation" => query_operation(&info.statement.sql), "outcome" => outcome).record(
+        info.elapsed.as_secs_f64()
+    );
+}
+
+pub fn record_pool(db: &DatabaseConnection) {
+    let pool = db.get_postgres_connection_pool();
+    let idle = pool.num_idle() as f64;
+    gauge!(DB_POOL_CONNECTIONS, "state" => "idle").set(idle);
+    gauge!(DB_POOL_CONNECTIONS, "state" => "in_use").set(f64::from(pool.size()) - idle);
+    gauge!(DB_POOL_MAX_CONNECTIONS).set(pool.options().get_max_connections());
+}
+
+/// Sets the depth of each `(queue, status, count)`. Pairs seen on an earlier
+/// scrape but missing now are zeroed rather than left at their last value.
+pub fn record_job_queue_depth(depths: Vec<(String, String, i64)>) {
+    static SEEN: Mutex<BTreeSet<(String, String)>> = Mutex::new(BTreeSet::new());
+    let mut seen = SEEN.lock().expect("queue depth lock poisoned");
+
+    for (queue, status) in seen.iter() {
+        gauge!(JOB_QUEUE_DEPTH, "queue" => queue.clone(), "status" => status.clone()).set(0.0);
+    }
+    for (queue, status, count) in depths {
+        gauge!(JOB_QUEUE_DEPTH, "queue" => queue.clone(), "status" => status.clone()).set(count as f64);
+        seen.insert((queue, status));
+    }
+}
+
+pub fn record_login(success: bool) {
+    counter!(LOGINS, "outcome" => if success { "success" } else { "failure" }).increment(1);
+}
+
+/// Counts an open WebSocket for as long as it is held.
+pub struct WebSocketConnection(());
+
+impl WebSocketConnection {
+    pub fn open() -> Self {
+        gauge!(WEBSOCKET_CONNECTIONS).increment(1.0);
+        WebSocketConnection(())
+    }
+}
+
+impl Drop for WebSocketConnection {
+    fn drop(&mut self) {
+        gauge!(WEBSOCKET_CONNECTIONS).decrement(1.0);
+    }
+}
@@ -0,0 +1,46 @@
+use sea_orm::DatabaseConnection;
+use tracing_subscriber::{ layer::SubscriberExt, util::SubscriberInitExt };
+
+use crate::config::Config;
+
+pub mod logging;
+pub mod metrics;
+pub mod requests;
+pub mod traces;
+
+/// Installs the global subscriber: log output, plus trace export when enabled.
+/// Called before anything else logs, so events from connecting to the database
+/// and starting workers are kept.
+pub fn init(config: &Config) {
+    tracing_subscriber
+        ::registry()
+        .with(logging::layer(&config.logging))
+        .with(config.tracing.enabled.then(|| traces::layer(&config.tracing)))
+        .init();
+}
+
+/// Times every statement run through `db` and the connections cloned from it, for
+/// metrics and traces as enabled. Must be called before `db` is cloned.
+pub fn observe_queries(db: &mut DatabaseConnection, config: &Config) {
+    let (metrics_enabled, tracing_enabled) = (config.metrics.enabled, config.tracing.enabled);
+    if !metrics_enabled && !tracing_enabled {
+        return;
+    }
+    db.set_metric_callback(move |info| {
+        if metrics_enabled {
+            metrics::record_query(info);
+        }
+        if tracing_enabled {
+            traces::record_query(info);
+        }
+    });
+}
+
+/// The statement's verb, e.g. `SELECT`, or `OTHER` for anything but plain CRUD.
+fn query_operation(sql: &str) -> String {
+    sql.split_whitespace()
+        .next()
+        .map(str::to_ascii_uppercase)
+        .filter(|verb| matches!(verb.as_str(), "SELECT" | "INSERT" | "UPDATE" | "DELETE"))
+        .unwrap_or_else(|| "OTHER".to_string())
+}
@@ -0,0 +1,105 @@
+use std::time::Instant;
+
+use axum::{ extract::{ MatchedPath, Request }, http::{ HeaderName, HeaderValue }, middleware::Next, response::Response };
+use tracing::{ error, field, info, info_span, Instrument, Span };
+
+use crate::telemetry::traces;
+
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest caller-supplied request id that is kept rather than replaced.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Query parameters whose values are credentials and never reach the logs.
+const SECRET_PARAMS: &[&str] = &["token", "access_token", "signature", "password"];
+
+/// Runs the whole request inside a `request` span carrying its id, so every event
+/// logged while handling it can be tied back to it. The id is taken from
+/// `X-Request-Id` when the caller sent a usable one, generated otherwise, and
+/// echoed on the response. `guard` adds the authenticated user to the span.
+///
+/// When tracing is on, the span is the request's server span, continuing the
+/// caller's trace if it sent a `traceparent`.
+pub async fn trace_request(mut req: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let request_id = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let header = HeaderValue::from_str(&request_id).expect("Request ids are visible ASCII");
+    req.headers_mut().insert(REQUEST_ID_HEADER.clone(), header.clone());
+
+    let span =
+        info_span!(
+        "request",
+        request_id = %request_id,
+        method = %req.method(),
+        path = %redacted_path(&req),
+        route = field::Empty,
+        user = field::Empty,
+        status = field::Empty,
+        otel.name = %req.method(),
+        otel.kind = "server",
+        otel.status_code = field::Empty,
+    );
+    traces::continue_trace_from(&span, req.headers());
+
+    let mut response = next.run(req).instrument(span.clone()).await;
+
+    let status = response.status();
+    let latency_ms = (started.elapsed().as_secs_f64() * 1_000_000.0).round() / 1000.0;
+    span.record("status", status.as_u16());
+    span.in_scope(|| {
+        if status.is_server_error() {
+            span.record("otel.status_code", "ERROR");
+            error!(latency_ms, "request failed");
+        } else {
+            info!(latency_ms, "request completed");
+        }
+    });
+
+    response.headers_mut().insert(REQUEST_ID_HEADER.clone(), header);
+    response
+}
+
+/// Names the request's span after the route it matched, e.g. `GET /posts/{id}`.
+/// Layered on the routes, since which one matched is only known once routing has
+/// run.
+pub async fn record_route(req: Request, next: Next) -> Response {
+    if let Some(route) = req.extensions().get::<MatchedPath>() {
+        Span::current().record("route", route.as_str());
+        traces::rename_current_span(format!("{} {}", req.method(), route.as_str()));
+    }
+    next.run(req).await
+}
+
+/// Ids end up in log lines and response headers, so only short ids made of
+/// characters safe in both are accepted.
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty() &&
+        id.len() <= MAX_REQUEST_ID_LEN &&
+        id.bytes().all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.'))
+}
+
+/// The path and query, with the values of `SECRET_PARAMS` masked.
+fn redacted_path(req: &Request) -> String {
+    let path = req.uri().path();
+    let Some(query) = req.uri().query() else {
+        return path.to_string();
+    };
+
+    let query: Vec<String> = query
+        .split('&')
+        .map(|pair| {
+            match pair.split_once('=') {
+                Some((name, _)) if SECRET_PARAMS.iter().any(|secret| name.eq_ignore_ascii_case(secret)) =>
+                    format!("{}=redacted", name),
+                _ => pair.to_string(),
+            }
+        })
+        .collect();
+    format!("{}?{}", path, query.join("&"))
+}
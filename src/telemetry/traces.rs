@@ -0,0 +1,274 @@
+use std::{ collections::HashMap, sync::OnceLock, time::{ Duration, SystemTime } };
+
+use axum::http::HeaderMap;
+use opentelemetry::{
+    global,
+    trace::{ Span as _, SpanKind, Status, TraceContextExt, Tracer, TracerProvider },
+    Context,
+    KeyValue,
+};
+use opentelemetry_http::{ HeaderExtractor, HeaderInjector };
+use opentelemetry_otlp::{ Protocol, SpanExporter, WithExportConfig };
+use opentelemetry_sdk::{ propagation::TraceContextPropagator, trace::{ Sampler, SdkTracerProvider }, Resource };
+use sea_orm::metric::Info;
+use tracing::{ error, Level, Span, Subscriber };
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{ filter::Targets, registry::LookupSpan, Layer };
+
+use crate::{ config::{ OtlpProtocol, TracingConfig }, telemetry::query_operation };
+
+const TRACEPARENT: &str = "traceparent";
+/// A collector slower than this loses the batch rather than holding up the next.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+static PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+/// Turns this crate's spans into OpenTelemetry spans, exported in batches to
+/// `tracing.endpoint`, and installs W3C trace context propagation.
+pub fn layer<S>(config: &TracingConfig) -> Box<dyn Layer<S> + Send + Sync>
+    where S: Subscriber + Send + Sync + for<'span> LookupSpan<'span>
+{
+    let provider = provider(config);
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(provider.clone());
+    PROVIDER.set(provider.clone()).expect("Tracing was installed twice");
+    layer_for(&provider)
+}
+
+fn provider(config: &TracingConfig) -> SdkTracerProvider {
+    let protocol = match config.protocol {
+        OtlpProtocol::Protobuf => Protocol::HttpBinary,
+        OtlpProtocol::Json => Protocol::HttpJson,
+    };
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(&config.endpoint)
+        .with_protocol(protocol)
+        .with_timeout(EXPORT_TIMEOUT)
+        .build()
+        .expect("Failed to build the OTLP exporter");
+
+    SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(config.sample_ratio))))
+        .with_resource(Resource::builder().with_service_name(config.service_name.clone()).build())
+        .build()
+}
+
+fn layer_for<S>(provider: &SdkTracerProvider) -> Box<dyn Layer<S> + Send + Sync>
+    where S: Subscriber + Send + Sync + for<'span> LookupSpan<'span>
+{
+    tracing_opentelemetry
+        ::layer()
+        .with_tracer(provider.tracer(env!("CARGO_PKG_NAME")))
+        // Spans from dependencies would bury the ones describing what the app did
+        .with_filter(Targets::new().with_target(env!("CARGO_CRATE_NAME"), Level::INFO))
+        .boxed()
+}
+
+/// Exports the spans still buffered. Called last thing on shutdown.
+pub fn shutdown() {
+    if let Some(provider) = PROVIDER.get()
+        && let Err(err) = provider.shutdown()
+    {
+        error!("Failed to export the remaining traces: {}", err);
+    }
+}
+
+/// Makes `span` part of the caller's trace when it sent `traceparent` and
+/// `tracestate` headers. Must be called before the span is first entered.
+pub fn continue_trace_from(span: &Span, headers: &HeaderMap) {
+    let context = global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    set_parent(span, context);
+}
+
+/// Headers continuing the current trace in a service we call.
+pub fn outgoing_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let context = Span::current().context();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut HeaderInjector(&mut headers)));
+    headers
+}
+
+/// The current trace as a `traceparent`, stored with queued work so that running
+/// it later continues the trace that queued it.
+pub fn current_traceparent() -> Option<String> {
+    let context = Span::current().context();
+    if !context.span().span_context().is_valid() {
+        return None;
+    }
+    let mut carrier = HashMap::new();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut carrier));
+    carrier.remove(TRACEPARENT)
+}
+
+/// Makes `span` part of the trace a stored `traceparent` belongs to. Must be
+/// called before the span is first entered.
+pub fn continue_trace(span: &Span, traceparent: Option<&str>) {
+    let Some(traceparent) = traceparent else {
+        return;
+    };
+    let carrier = HashMap::from([(TRACEPARENT.to_string(), traceparent.to_string())]);
+    let context = global::get_text_map_propagator(|propagator| propagator.extract(&carrier));
+    set_parent(span, context);
+}
+
+fn set_parent(span: &Span, context: Context) {
+    // A context without a span means there is no trace to continue
+    if context.span().span_context().is_valid() {
+        // Fails only when tracing is off, which leaves nothing to continue either
+        let _ = span.set_parent(context);
+    }
+}
+
+/// Renames the current span's exported span. `otel.name` only takes effect
+/// before a span is first entered; this works after.
+pub fn rename_current_span(name: String) {
+    Span::current().context().span().update_name(name);
+}
+
+/// Records a finished statement as a child of the current span. SeaORM only
+/// reports statements once they are done, so the span is backdated by the time
+/// the statement took. Statements run outside a traced span, like queue polling,
+/// are left out.
+pub fn record_query(info: &Info<'_>) {
+    let parent = Span::current().context();
+    if !parent.span().span_context().is_valid() {
+        return;
+    }
+
+    let ended = SystemTime::now();
+    let operation = query_operation(&info.statement.sql);
+    let tracer = global::tracer(env!("CARGO_PKG_NAME"));
+    let mut span = tracer
+        .span_builder(operation.clone())
+        .with_kind(SpanKind::Client)
+        .with_start_time(ended - info.elapsed)
+        .with_attributes([
+            KeyValue::new("db.system.name", "postgresql"),
+            KeyValue::new("db.operation.name", operation),
+            // Values are bound separately, so the text holds placeholders, not data
+            KeyValue::new("db.query.text", info.statement.sql.clone()),
+        ])
+        .start_with_context(&tracer, &parent);
+    if info.failed {
+        span.set_status(Status::error("Statement failed"));
+    }
+    span.end_with_timestamp(ended);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{ Arc, Mutex };
+
+    use axum::{ body::Bytes, middleware, routing::{ get, post }, Router };
+    use tokio::net::TcpListener;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+    use crate::{ config::TracingConfig, telemetry::requests };
+
+    const CALLER_TRACE_ID: &str = "0af7651916cd43dd8448eb211c80319c";
+    const CALLER_SPAN_ID: &str = "b7ad6b7169203331";
+
+    async fn serve(app: Router) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}", addr)
+    }
+
+    /// Every span exported to the stand-in collector, as OTLP JSON.
+    fn exported_spans(batches: &[Bytes]) -> Vec<serde_json::Value> {
+        let mut spans = Vec::new();
+        for batch in batches {
+            let batch: serde_json::Value = serde_json::from_slice(batch).unwrap();
+            for resource in batch["resourceSpans"].as_array().unwrap() {
+                for scope in resource["scopeSpans"].as_array().unwrap() {
+                    spans.extend(scope["spans"].as_array().unwrap().iter().cloned());
+                }
+            }
+        }
+        spans
+    }
+
+    #[tokio::test]
+    async fn requests_continue_the_callers_trace_and_pass_it_on() {
+        let batches = Arc::new(Mutex::new(Vec::<Bytes>::new()));
+        let collector = serve(
+            Router::new().route(
+                "/v1/traces",
+                post({
+                    let batches = batches.clone();
+                    move |body: Bytes| async move { batches.lock().unwrap().push(body) }
+                })
+            )
+        ).await;
+
+        // Stands in for a webhook receiver, remembering the trace it was called in
+        let received = Arc::new(Mutex::new(None::<String>));
+        let receiver = serve(
+            Router::new().route(
+                "/hook",
+                get({
+                    let received = received.clone();
+                    move |headers: HeaderMap| async move {
+                        *received.lock().unwrap() = headers
+                            .get(TRACEPARENT)
+                            .map(|value| value.to_str().unwrap().to_string());
+                    }
+                })
+            )
+        ).await;
+
+        let provider = provider(
+            &(TracingConfig {
+                enabled: true,
+                endpoint: format!("{}/v1/traces", collector),
+                protocol: OtlpProtocol::Json,
+                ..Default::default()
+            })
+        );
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        // The test runtime is single-threaded, so every task sees this subscriber
+        let _subscriber = tracing::subscriber::set_default(tracing_subscriber::registry().with(layer_for(&provider)));
+
+        let app = Router::new()
+            .route(
+                "/posts/{id}",
+                get(move || async move {
+                    reqwest::Client
+                        ::new()
+                        .get(format!("{}/hook", receiver))
+                        .headers(outgoing_headers())
+                        .send().await
+                        .unwrap();
+                })
+            )
+            .route_layer(middleware::from_fn(requests::record_route))
+            .layer(middleware::from_fn(requests::trace_request));
+        let app = serve(app).await;
+
+        let response = reqwest::Client
+            ::new()
+            .get(format!("{}/posts/7", app))
+            .header(TRACEPARENT, format!("00-{}-{}-01", CALLER_TRACE_ID, CALLER_SPAN_ID))
+            .send().await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        // Flushing blocks on the export, which the collector on this runtime answers
+        tokio::task::spawn_blocking(move || provider.force_flush()).await.unwrap().unwrap();
+
+        let spans = exported_spans(&batches.lock().unwrap());
+        let server_span = spans
+            .iter()
+            .find(|span| span["name"] == "GET /posts/{id}")
+            .unwrap_or_else(|| panic!("the request is exported under its route: {:?}", spans));
+        assert_eq!(server_span["traceId"], CALLER_TRACE_ID);
+        assert_eq!(server_span["parentSpanId"], CALLER_SPAN_ID);
+
+        let outgoing = received.lock().unwrap().clone().expect("the receiver was sent a traceparent");
+        assert_eq!(outgoing, format!("00-{}-{}-01", CALLER_TRACE_ID, server_span["spanId"].as_str().unwrap()));
+    }
+}
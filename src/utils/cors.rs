@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use axum::http::{ HeaderName, HeaderValue, Method };
+use reqwest::Url;
+use tower_http::cors::{ AllowOrigin, Any, CorsLayer };
+
+use crate::config::CorsConfig;
+
+/// One entry of `cors.allowed_origins`: `*`, an exact origin such as
+/// `https://app.example.com`, or `https://*.example.com` for any subdomain.
+#[derive(Clone, Debug)]
+pub enum OriginPattern {
+    Any,
+    Exact(String),
+    /// `prefix` is the scheme up to `://`; `suffix` is `.example.com` plus any port.
+    Subdomain { prefix: String, suffix: String },
+}
+
+impl OriginPattern {
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let pattern = pattern.trim().trim_end_matches('/').to_ascii_lowercase();
+        if pattern == "*" {
+            return Ok(OriginPattern::Any);
+        }
+
+        let invalid = || format!("{:?} is not an origin such as https://app.example.com", pattern);
+        let (scheme, host) = pattern.split_once("://").ok_or_else(invalid)?;
+        let suffix = host.strip_prefix('*').filter(|suffix| suffix.starts_with('.'));
+        if suffix.unwrap_or(host).contains('*') {
+            return Err(format!("{:?} may only use * as its first label, as in https://*.example.com", pattern));
+        }
+
+        // Check the shape of the pattern with the wildcard swapped for a real label
+        let probe = match suffix {
+            Some(suffix) => format!("{}://x{}", scheme, suffix),
+            None => pattern.clone(),
+        };
+        let url = Url::parse(&probe).map_err(|_| invalid())?;
+        if !matches!(url.scheme(), "http" | "https") || url.path() != "/" || url.query().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(match suffix {
+            Some(suffix) => OriginPattern::Subdomain { prefix: format!("{}://", scheme), suffix: suffix.to_string() },
+            None => OriginPattern::Exact(pattern),
+        })
+    }
+
+    pub fn matches(&self, origin: &str) -> bool {
+        match self {
+            OriginPattern::Any => true,
+            OriginPattern::Exact(exact) => origin.eq_ignore_ascii_case(exact),
+            OriginPattern::Subdomain { prefix, suffix } => {
+                let origin = origin.to_ascii_lowercase();
+                let Some(subdomain) = origin
+                    .strip_prefix(prefix.as_str())
+                    .and_then(|host| host.strip_suffix(suffix.as_str())) else {
+                    return false;
+                };
+                !subdomain.is_empty() &&
+                    subdomain.split('.').all(|label| !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+            }
+        }
+    }
+}
+
+/// The single CORS layer for the whole API, built from `cors.*`. The config is
+/// validated at startup, so nothing here can fail.
+pub fn layer(config: &CorsConfig) -> CorsLayer {
+    let patterns: Vec<OriginPattern> = config.allowed_origins
+        .iter()
+        .filter_map(|pattern| OriginPattern::parse(pattern).ok())
+        .collect();
+
+    let allow_origin = match patterns.iter().any(|pattern| matches!(pattern, OriginPattern::Any)) {
+        true => AllowOrigin::from(Any),
+        false =>
+            AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+                origin.to_str().is_ok_and(|origin| patterns.iter().any(|pattern| pattern.matches(origin)))
+            }),
+    };
+
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(config.allowed_methods.iter().filter_map(|method| method.parse::<Method>().ok()).collect::<Vec<_>>())
+        .allow_headers(header_names(&config.allowed_headers))
+        .expose_headers(header_names(&config.exposed_headers))
+        .allow_credentials(config.allow_credentials)
+        .max_age(Duration::from_secs(config.max_age_secs))
+}
+
+fn header_names(names: &[String]) -> Vec<HeaderName> {
+    names
+        .iter()
+        .filter_map(|name| name.parse().ok())
+        .collect()
+}
@@ -1,46 +1,90 @@
-use axum::{ extract::{ Request, State }, middleware::Next, response::Response };
-use hyper::StatusCode;
-use sea_orm::{ EntityTrait, QueryFilter, ColumnTrait };
+use axum::{ extract::{ Request, State }, middleware::Next, response::Response, Extension };
+use hyper::{ HeaderMap, StatusCode };
+use sea_orm::{ QueryFilter, ColumnTrait, DatabaseConnection };
+use sha2::{ Digest, Sha256 };
+use tracing::{ field, info_span, Instrument, Span };
 
+use crate::config;
 use crate::models::user_models::AppState;
-use crate::utils::{ api_errors::APIError, jwt::decode_jwt };
+use crate::utils::{ api_errors::APIError, jwt::decode_jwt, soft_delete::SoftDelete };
+
+/// The token from the `Authorization` header, with any "Bearer " prefix stripped.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let token_header = headers.get("authorization").and_then(|h| h.to_str().ok())?;
+
+    // strip "Bearer " prefix if present
+    Some(
+        token_header
+            .strip_prefix("Bearer ")
+            .or_else(|| token_header.strip_prefix("bearer "))
+            .unwrap_or(token_header)
+    )
+}
+
+/// Lets a request through to `/metrics` only with `metrics.token` as its bearer
+/// token, when one is configured.
+pub async fn metrics_guard(req: Request, next: Next) -> Result<Response, APIError> {
+    let expected = &config::get().metrics.token;
+    if !expected.is_empty() {
+        // Comparing digests keeps the check from leaking how much of the token matched
+        let matches = bearer_token(req.headers())
+            .is_some_and(|token| Sha256::digest(token.as_bytes()) == Sha256::digest(expected.as_bytes()));
+        if !matches {
+            return Err(APIError {
+                message: "Invalid metrics token".to_string(),
+                status_code: StatusCode::UNAUTHORIZED,
+                error_code: Some(1),
+            });
+        }
+    }
+
+    Ok(next.run(req).await)
+}
 
 pub async fn guard(
     State(app_state): State<AppState>,
     mut req: Request,
     next: Next
 ) -> Result<Response, APIError> {
-    let token_header = req
-        .headers()
-        .get("authorization")
-        .and_then(|h| h.to_str().ok())
-        .ok_or(APIError {
-            message: "Token Not Found".to_string(),
-            status_code: StatusCode::UNAUTHORIZED,
-            error_code: Some(1),
+    let token = bearer_token(req.headers()).ok_or(APIError {
+        message: "Token Not Found".to_string(),
+        status_code: StatusCode::UNAUTHORIZED,
+        error_code: Some(1),
+    })?;
+
+    let identity = authenticate(&app_state.db, token).await?;
+
+    // Insert the user identity into request extensions
+    req.extensions_mut().insert(identity);
+
+    Ok(next.run(req).await)
+}
+
+/// Resolves a bearer token to the active user it was issued to. Shared by `guard`
+/// and connections that cannot send an `Authorization` header, like WebSockets.
+pub async fn authenticate(db: &DatabaseConnection, token: &str) -> Result<entity::user::Model, APIError> {
+    let span = info_span!("authenticate", otel.status_code = field::Empty);
+    let identity = verify(db, token)
+        .instrument(span.clone()).await
+        .inspect_err(|_| {
+            span.record("otel.status_code", "ERROR");
         })?;
 
-    // strip "Bearer " prefix if present
-    let token = token_header
-        .strip_prefix("Bearer ")
-        .or_else(|| token_header.strip_prefix("bearer "))
-        .unwrap_or(token_header);
+    // Attribute the rest of the request's logs to this user
+    Span::current().record("user", field::display(identity.uuid));
 
-    println!("token ---- {}", token);
+    Ok(identity)
+}
 
+async fn verify(db: &DatabaseConnection, token: &str) -> Result<entity::user::Model, APIError> {
     let claims = decode_jwt(token).map_err(|_| APIError {
         message: "Token Not Found".to_string(), // Fixed typo: "Fount" -> "Found"
         status_code: StatusCode::UNAUTHORIZED,
         error_code: Some(1),
     })?;
 
-    println!("claims {:?}", claims);
-
-    // Access the database from app_state
-    let db = &app_state.db;
-
     let identity = entity::user::Entity
-        ::find()
+        ::find_active()
         .filter(entity::user::Column::Email.eq(claims.email.to_lowercase()))
         .one(db).await
         .map_err(|err| APIError {
@@ -54,8 +98,33 @@ pub async fn guard(
             error_code: Some(1),
         })?;
 
-    // Insert the user identity into request extensions
-    req.extensions_mut().insert(identity);
+    // Tokens issued before a revocation (e.g. account deletion) are no longer valid
+    if let Some(revoked_at) = identity.tokens_revoked_at
+        && (claims.iat as i64) <= revoked_at.and_utc().timestamp()
+    {
+        return Err(APIError {
+            message: "Token Revoked".to_string(),
+            status_code: StatusCode::UNAUTHORIZED,
+            error_code: Some(1),
+        });
+    }
+
+    Ok(identity)
+}
+
+/// Must be layered inside `guard`, which provides the identity extension.
+pub async fn admin_guard(
+    Extension(identity): Extension<entity::user::Model>,
+    req: Request,
+    next: Next
+) -> Result<Response, APIError> {
+    if !identity.is_admin {
+        return Err(APIError {
+            message: "FORBIDDEN".to_string(),
+            status_code: StatusCode::FORBIDDEN,
+            error_code: Some(1),
+        });
+    }
 
     Ok(next.run(req).await)
 }
@@ -1,8 +1,9 @@
 use chrono::{ Duration, Utc };
-use hyper::StatusCode;
 use jsonwebtoken::{ decode, encode, DecodingKey, EncodingKey, Header, Validation };
 use serde::{ Deserialize, Serialize };
 
+use crate::config;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Claims {
     pub exp: usize,
@@ -12,7 +13,8 @@ pub struct Claims {
 
 pub fn encode_jwt(email: String) -> Result<String, String> {
     let now = Utc::now();
-    let expire = Duration::hours(24);
+    let jwt = &config::get().jwt;
+    let expire = Duration::hours(jwt.expiry_hours);
 
     let claims = Claims {
         exp: (now + expire).timestamp() as usize,
@@ -20,18 +22,15 @@ pub fn encode_jwt(email: String) -> Result<String, String> {
         email,
     };
 
-    let secret = "kkdfik-kfiadfk-asdkiew84";
-
     // map_err to provide a relevant error message
-    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_ref())).map_err(|err|
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(jwt.secret.as_ref())).map_err(|err|
         format!("Failed to encode JWT: {}", err)
     )
 }
 
 pub fn decode_jwt(token: &str) -> Result<Claims, String> {
-    let secret = "kkdfik-kfiadfk-asdkiew84";
-    let res = decode(token, &DecodingKey::from_secret(secret.as_ref()), &Validation::default())
+    let secret = &config::get().jwt.secret;
+    decode(token, &DecodingKey::from_secret(secret.as_ref()), &Validation::default())
         .map(|data| data.claims)
-        .map_err(|err| format!("Failed to decode JWT: {}", err));
-    return res;
+        .map_err(|err| format!("Failed to decode JWT: {}", err))
 }
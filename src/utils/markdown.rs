@@ -0,0 +1,180 @@
+use std::collections::{ HashMap, HashSet };
+
+use ammonia::Builder;
+use pulldown_cmark::{ html, CowStr, Event, Options, Parser, Tag, TagEnd, TextMergeStream };
+use uuid::Uuid;
+
+use crate::{ models::post_models::PostFormat, utils::mentions::linkify };
+
+const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "blockquote",
+    "br",
+    "code",
+    "del",
+    "em",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "strong",
+    "table",
+    "tbody",
+    "td",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+/// Renders a post or comment body to HTML that is safe to embed in a page.
+/// `mentions` maps the handles that resolved to a user onto their uuid.
+pub fn render(text: &str, format: PostFormat, mentions: &HashMap<String, Uuid>) -> String {
+    match format {
+        PostFormat::Plain => render_plain(text, mentions),
+        PostFormat::Markdown => sanitize(&render_markdown(text, mentions)),
+    }
+}
+
+fn render_markdown(text: &str, mentions: &HashMap<String, Uuid>) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+
+    // Mentions and hashtags are linked in running text only, not inside code or
+    // inside links the author wrote themselves
+    let mut code_depth = 0;
+    let mut link_depth = 0;
+    let events = TextMergeStream::new(Parser::new_ext(text, options)).map(|event| {
+        match &event {
+            Event::Start(Tag::CodeBlock(_)) => {
+                code_depth += 1;
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                code_depth -= 1;
+            }
+            Event::Start(Tag::Link { .. } | Tag::Image { .. }) => {
+                link_depth += 1;
+            }
+            Event::End(TagEnd::Link | TagEnd::Image) => {
+                link_depth -= 1;
+            }
+            Event::Text(text) if code_depth == 0 && link_depth == 0 => {
+                return Event::InlineHtml(CowStr::from(linkify(text, mentions)));
+            }
+            _ => {}
+        }
+        event
+    });
+
+    let mut output = String::new();
+    html::push_html(&mut output, events);
+    output
+}
+
+/// Drops everything outside the tag allowlist (including any raw HTML the author
+/// wrote into the markdown) and marks links `rel="nofollow"`.
+fn sanitize(unsafe_html: &str) -> String {
+    Builder::default()
+        .tags(ALLOWED_TAGS.iter().copied().collect::<HashSet<_>>())
+        .url_schemes(["http", "https", "mailto"].into_iter().collect())
+        .link_rel(Some("nofollow noopener noreferrer"))
+        .clean(unsafe_html)
+        .to_string()
+}
+
+/// Escapes the text and keeps its paragraphs and line breaks.
+fn render_plain(text: &str, mentions: &HashMap<String, Uuid>) -> String {
+    text.replace("\r\n", "\n")
+        .split("\n\n")
+        .filter(|paragraph| !paragraph.trim().is_empty())
+        .map(|paragraph| format!("<p>{}</p>", linkify(paragraph.trim(), mentions).replace('\n', "<br>")))
+        .collect()
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn markdown(text: &str) -> String {
+        render(text, PostFormat::Markdown, &HashMap::new())
+    }
+
+    #[test]
+    fn script_tags_are_removed() {
+        let html = markdown("Hi <script>alert(1)</script>\n\n<script>\nalert(2)\n</script>");
+        assert!(!html.contains("<script"), "{}", html);
+        assert!(!html.contains("alert(2)"), "{}", html);
+    }
+
+    #[test]
+    fn javascript_links_lose_their_target() {
+        for text in ["[click](javascript:alert(1))", "[click](JavaScript:alert(1))", "<a href=\"javascript:alert(1)\">click</a>"] {
+            let html = markdown(text);
+            assert!(!html.to_lowercase().contains("javascript:"), "{}", html);
+        }
+    }
+
+    #[test]
+    fn event_handler_attributes_are_removed() {
+        let html = markdown("<p onclick=\"alert(1)\">hi</p> <a href=\"https://example.com\" onmouseover=\"alert(2)\">x</a>");
+        assert!(!html.contains("onclick"), "{}", html);
+        assert!(!html.contains("onmouseover"), "{}", html);
+        assert!(html.contains("https://example.com"), "{}", html);
+    }
+
+    #[test]
+    fn raw_html_outside_the_allowlist_is_removed() {
+        let html = markdown("<img src=x onerror=alert(1)><iframe src=\"https://evil.test\"></iframe><style>p{}</style>**ok**");
+        for tag in ["<img", "<iframe", "<style", "onerror"] {
+            assert!(!html.contains(tag), "{} in {}", tag, html);
+        }
+        assert!(html.contains("<strong>ok</strong>"), "{}", html);
+    }
+
+    #[test]
+    fn links_are_marked_nofollow() {
+        let html = markdown("[site](https://example.com) and <a href=\"https://example.org\" rel=\"me\">me</a>");
+        assert_eq!(html.matches("rel=\"nofollow noopener noreferrer\"").count(), 2, "{}", html);
+        assert!(!html.contains("rel=\"me\""), "{}", html);
+    }
+
+    #[test]
+    fn plain_text_is_escaped() {
+        assert_eq!(
+            render("<b>hi</b> & bye\nsecond line", PostFormat::Plain, &HashMap::new()),
+            "<p>&lt;b&gt;hi&lt;/b&gt; &amp; bye<br>second line</p>"
+        );
+    }
+
+    #[test]
+    fn mentions_are_linked_outside_code_only() {
+        let alice = Uuid::new_v4();
+        let mentions = HashMap::from([("alice".to_string(), alice)]);
+        let html = render("Hi @alice\n\n```\n@alice\n```\n\n`@alice`", PostFormat::Markdown, &mentions);
+        assert_eq!(html.matches("<a href=").count(), 1, "{}", html);
+        assert!(html.contains(&format!("/users/{}/mentions", alice)), "{}", html);
+    }
+}
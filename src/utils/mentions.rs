@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::utils::markdown::escape_html;
+
+pub const MIN_HANDLE_LENGTH: usize = 3;
+pub const MAX_HANDLE_LENGTH: usize = 30;
+const MAX_TAG_LENGTH: usize = 64;
+
+/// A piece of text split at `@handle` and `#hashtag` tokens. Handles and tags are
+/// lowercased; `raw` keeps the text as written.
+enum Segment<'a> {
+    Text(&'a str),
+    Mention {
+        raw: &'a str,
+        handle: String,
+    },
+    Hashtag {
+        raw: &'a str,
+        tag: String,
+    },
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Whether `c` glues onto a neighbouring token, so that no token starts right
+/// after it or ends right before it.
+fn joins_token(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '@' || c == '#'
+}
+
+/// Splits `text` into plain runs and tokens. A token must start the text or follow
+/// a non-word character, so e-mail addresses are left alone, and must not run on
+/// into letters a handle or tag cannot hold. Code spans and URLs are plain text.
+fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut plain_start = 0;
+    let mut previous: Option<char> = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let at_boundary = previous.is_none_or(|p| !joins_token(p));
+        previous = Some(c);
+
+        let skip_to = match c {
+            '`' => Some(code_span_end(text, start)),
+            'h' | 'H' if at_boundary => url_end(text, start),
+            _ => None,
+        };
+        if let Some(end) = skip_to {
+            while chars.peek().is_some_and(|&(i, _)| i < end) {
+                previous = chars.next().map(|(_, ch)| ch);
+            }
+            continue;
+        }
+        if (c != '@' && c != '#') || !at_boundary {
+            continue;
+        }
+
+        let body_start = start + c.len_utf8();
+        let body_len = text[body_start..].find(|ch: char| !is_word_char(ch)).unwrap_or(text.len() - body_start);
+        let body = &text[body_start..body_start + body_len];
+        if text[body_start + body_len..].chars().next().is_some_and(joins_token) {
+            continue;
+        }
+        let token = match c {
+            '@' if (MIN_HANDLE_LENGTH..=MAX_HANDLE_LENGTH).contains(&body.len()) =>
+                Segment::Mention { raw: &text[start..body_start + body_len], handle: body.to_ascii_lowercase() },
+            // Tags need a letter so issue numbers like `#12` stay plain text
+            '#' if
+                !body.is_empty() &&
+                body.len() <= MAX_TAG_LENGTH &&
+                body.chars().any(|ch| ch.is_ascii_alphabetic())
+            => Segment::Hashtag { raw: &text[start..body_start + body_len], tag: body.to_ascii_lowercase() },
+            _ => {
+                continue;
+            }
+        };
+
+        if plain_start < start {
+            segments.push(Segment::Text(&text[plain_start..start]));
+        }
+        segments.push(token);
+        plain_start = body_start + body_len;
+        while chars.peek().is_some_and(|&(i, _)| i < plain_start) {
+            previous = chars.next().map(|(_, ch)| ch);
+        }
+    }
+
+    if plain_start < text.len() {
+        segments.push(Segment::Text(&text[plain_start..]));
+    }
+    segments
+}
+
+/// Where the code span opened by the backticks at `start` ends: after the next run
+/// of as many backticks, or after the opening run if nothing closes it.
+fn code_span_end(text: &str, start: usize) -> usize {
+    let backticks = |at: usize| text[at..].len() - text[at..].trim_start_matches('`').len();
+    let run = backticks(start);
+    let mut search = start + run;
+    while let Some(found) = text[search..].find('`') {
+        let closing = backticks(search + found);
+        if closing == run {
+            return search + found + closing;
+        }
+        search += found + closing;
+    }
+    start + run
+}
+
+/// Where the URL starting at `start` ends, if one starts there.
+fn url_end(text: &str, start: usize) -> Option<usize> {
+    let rest = &text[start..];
+    let is_url = ["http://", "https://"]
+        .iter()
+        .any(|scheme| rest.get(..scheme.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme)));
+    is_url.then(|| start + rest.find(char::is_whitespace).unwrap_or(rest.len()))
+}
+
+/// Distinct lowercased handles mentioned in `text`.
+pub fn extract_mentions(text: &str) -> Vec<String> {
+    let mut handles = Vec::new();
+    for segment in segments(text) {
+        if let Segment::Mention { handle, .. } = segment && !handles.contains(&handle) {
+            handles.push(handle);
+        }
+    }
+    handles
+}
+
+/// Distinct lowercased hashtags in `text`.
+pub fn extract_hashtags(text: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    for segment in segments(text) {
+        if let Segment::Hashtag { tag, .. } = segment && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Normalizes a handle chosen by a user, or returns `None` if it is not valid.
+pub fn normalize_handle(handle: &str) -> Option<String> {
+    let handle = handle.trim().trim_start_matches('@');
+    let valid =
+        (MIN_HANDLE_LENGTH..=MAX_HANDLE_LENGTH).contains(&handle.len()) && handle.chars().all(is_word_char);
+    valid.then(|| handle.to_ascii_lowercase())
+}
+
+pub fn mentions_url(user_uuid: Uuid) -> String {
+    format!("/users/{}/mentions", user_uuid)
+}
+
+pub fn hashtag_url(tag: &str) -> String {
+    format!("/hashtags/{}", tag)
+}
+
+/// Escapes `text` for HTML and turns hashtags, and mentions of handles found in
+/// `mentions`, into links. Mentions of unknown handles are left as text.
+pub fn linkify(text: &str, mentions: &HashMap<String, Uuid>) -> String {
+    let mut html = String::with_capacity(text.len());
+    for segment in segments(text) {
+        match segment {
+            Segment::Text(text) => html.push_str(&escape_html(text)),
+            Segment::Mention { raw, handle } =>
+                match mentions.get(&handle) {
+                    Some(uuid) =>
+                        html.push_str(
+                            &format!("<a href=\"{}\">{}</a>", mentions_url(*uuid), escape_html(raw))
+                        ),
+                    None => html.push_str(&escape_html(raw)),
+                }
+            Segment::Hashtag { raw, tag } =>
+                html.push_str(&format!("<a href=\"{}\">{}</a>", hashtag_url(&tag), escape_html(raw))),
+        }
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mentions_and_hashtags_are_lowercased_and_distinct() {
+        assert_eq!(extract_mentions("@Alice and @alice, then @bob_2"), ["alice", "bob_2"]);
+        assert_eq!(extract_hashtags("#Rust #rust #web_dev"), ["rust", "web_dev"]);
+    }
+
+    #[test]
+    fn trailing_punctuation_ends_a_token() {
+        assert_eq!(extract_mentions("Thanks @alice! Ask @bob, or (@carol)."), ["alice", "bob", "carol"]);
+        assert_eq!(extract_hashtags("Loving #rust. Also #axum?"), ["rust", "axum"]);
+    }
+
+    #[test]
+    fn email_addresses_are_not_mentions() {
+        assert!(extract_mentions("Write to alice@example.com or bob.smith@example.org").is_empty());
+    }
+
+    #[test]
+    fn urls_are_left_alone() {
+        let text = "See https://example.com/#intro and HTTP://example.com/a-b#c or https://social.example/@alice";
+        assert!(extract_hashtags(text).is_empty());
+        assert!(extract_mentions(text).is_empty());
+        assert_eq!(extract_hashtags("https://example.com/#intro #rust"), ["rust"]);
+    }
+
+    #[test]
+    fn code_spans_are_left_alone() {
+        assert!(extract_mentions("Run `@decorator` or ``@a `b` @c``").is_empty());
+        assert!(extract_hashtags("```\n#include <stdio.h>\n```").is_empty());
+        assert_eq!(extract_mentions("`@code` then @alice"), ["alice"]);
+        // An unclosed backtick is a literal one
+        assert_eq!(extract_mentions("it`s @alice"), ["alice"]);
+    }
+
+    #[test]
+    fn issue_numbers_and_short_handles_are_not_tokens() {
+        assert!(extract_hashtags("Fixes #12").is_empty());
+        assert!(extract_mentions("@al is too short").is_empty());
+    }
+
+    #[test]
+    fn tokens_do_not_split_words_outside_ascii() {
+        assert!(extract_mentions("@josé").is_empty());
+        assert!(extract_hashtags("#café").is_empty());
+        assert!(extract_mentions("née@alice").is_empty());
+        assert_eq!(extract_mentions("¡@alice! 日本 @bob。"), ["alice", "bob"]);
+    }
+
+    #[test]
+    fn linkify_escapes_text_and_links_known_handles_and_tags() {
+        let alice = Uuid::new_v4();
+        let mentions = HashMap::from([("alice".to_string(), alice)]);
+
+        assert_eq!(
+            linkify("<b>@Alice</b> & @unknown #Rust", &mentions),
+            format!(
+                "&lt;b&gt;<a href=\"{}\">@Alice</a>&lt;/b&gt; &amp; @unknown <a href=\"/hashtags/rust\">#Rust</a>",
+                mentions_url(alice)
+            )
+        );
+        assert_eq!(linkify("`@alice` https://x.test/#rust", &mentions), "`@alice` https://x.test/#rust");
+    }
+}
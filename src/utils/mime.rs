@@ -0,0 +1,19 @@
+/// Image types accepted for upload, identified by their leading magic bytes.
+const IMAGE_SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\xFF\xD8\xFF", "image/jpeg"),
+    (b"\x89PNG\r\n\x1A\n", "image/png"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+];
+
+/// Detects the image MIME type from the file contents, ignoring whatever the
+/// client claimed. Returns `None` for anything that is not an allowed image.
+pub fn sniff_image_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+
+    IMAGE_SIGNATURES.iter()
+        .find(|(signature, _)| bytes.starts_with(signature))
+        .map(|(_, mime)| *mime)
+}
@@ -1,3 +1,11 @@
 pub mod api_errors;
 pub mod jwt;
 pub mod guard;
+pub mod soft_delete;
+pub mod signed_url;
+pub mod mime;
+pub mod markdown;
+pub mod visibility;
+pub mod mentions;
+pub mod rate_limit;
+pub mod cors;
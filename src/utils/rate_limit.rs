@@ -0,0 +1,130 @@
+use std::{ collections::HashMap, net::{ IpAddr, SocketAddr }, sync::{ Arc, Mutex }, time::{ Duration, Instant } };
+
+use axum::{
+    extract::{ ConnectInfo, Request, State },
+    middleware::{ self, Next },
+    response::{ IntoResponse, Response },
+    Router,
+};
+use hyper::{ header, HeaderMap, StatusCode };
+
+use crate::{ config, utils::api_errors::APIError };
+
+const WINDOW: Duration = Duration::from_secs(60);
+/// Past this many tracked clients, expired windows are swept on the next request.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+/// Counts requests per client address in fixed one-minute windows.
+#[derive(Clone)]
+pub struct RateLimiter {
+    limit: u32,
+    windows: Arc<Mutex<HashMap<IpAddr, (Instant, u32)>>>,
+}
+
+impl RateLimiter {
+    pub fn per_minute(limit: u32) -> Self {
+        RateLimiter { limit, windows: Arc::default() }
+    }
+
+    /// Counts a request from `client`, or returns how long until it may try again.
+    fn check(&self, client: IpAddr) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().expect("rate limiter lock poisoned");
+        if windows.len() > SWEEP_THRESHOLD {
+            windows.retain(|_, (started, _)| now.duration_since(*started) < WINDOW);
+        }
+
+        let (started, count) = windows.entry(client).or_insert((now, 0));
+        if now.duration_since(*started) >= WINDOW {
+            *started = now;
+            *count = 0;
+        }
+        if *count >= self.limit {
+            return Err(WINDOW - now.duration_since(*started));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+/// Applies `limit` requests per minute per client to every route in `router`.
+pub fn limit<S: Clone + Send + Sync + 'static>(router: Router<S>, limit: u32) -> Router<S> {
+    router.route_layer(middleware::from_fn_with_state(RateLimiter::per_minute(limit), rate_limit))
+}
+
+/// The address to count a request against: the peer, unless the peer is a
+/// trusted proxy, in which case the nearest untrusted hop in `X-Forwarded-For`.
+/// Anything left of that hop was written by the client and could be forged.
+fn client_ip(peer: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> IpAddr {
+    if !trusted_proxies.contains(&peer) {
+        return peer;
+    }
+
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .rev()
+        .map_while(|hop| hop.trim().parse::<IpAddr>().ok())
+        .find(|hop| !trusted_proxies.contains(hop))
+        .unwrap_or(peer)
+}
+
+pub async fn rate_limit(State(limiter): State<RateLimiter>, req: Request, next: Next) -> Response {
+    // Requests only lack a peer address when the server is not serving with
+    // connect info, in which case there is nothing to key on
+    let Some(ConnectInfo(peer)) = req.extensions().get::<ConnectInfo<SocketAddr>>().copied() else {
+        return next.run(req).await;
+    };
+
+    let client = client_ip(peer.ip(), req.headers(), &config::get().rate_limit.trusted_proxies);
+    match limiter.check(client) {
+        Ok(()) => next.run(req).await,
+        Err(retry_after) => {
+            let mut response = (APIError {
+                message: "Too many requests".to_string(),
+                status_code: StatusCode::TOO_MANY_REQUESTS,
+                error_code: Some(7),
+            }).into_response();
+            response.headers_mut().insert(header::RETRY_AFTER, (retry_after.as_secs() + 1).into());
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use hyper::HeaderMap;
+
+    use super::client_ip;
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    fn forwarded_for(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn keys_on_the_peer_unless_it_is_a_trusted_proxy() {
+        let headers = forwarded_for("203.0.113.7");
+
+        assert_eq!(client_ip(ip("198.51.100.1"), &headers, &[]), ip("198.51.100.1"));
+        assert_eq!(client_ip(ip("10.0.0.1"), &headers, &[ip("10.0.0.1")]), ip("203.0.113.7"));
+        assert_eq!(client_ip(ip("10.0.0.1"), &HeaderMap::new(), &[ip("10.0.0.1")]), ip("10.0.0.1"));
+    }
+
+    #[test]
+    fn ignores_addresses_the_client_forwarded_itself() {
+        let trusted = [ip("10.0.0.1"), ip("10.0.0.2")];
+        let headers = forwarded_for("1.2.3.4, 203.0.113.7, 10.0.0.2");
+
+        assert_eq!(client_ip(ip("10.0.0.1"), &headers, &trusted), ip("203.0.113.7"));
+    }
+}
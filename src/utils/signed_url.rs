@@ -0,0 +1,37 @@
+use chrono::{ DateTime, Utc };
+use hmac::{ Hmac, KeyInit, Mac };
+use sha2::Sha256;
+
+use crate::config;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn mac_for(path: &str, expires: i64) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(config::get().signing.url_secret.as_bytes()).expect(
+        "HMAC accepts keys of any length"
+    );
+    mac.update(path.as_bytes());
+    mac.update(b"\n");
+    mac.update(expires.to_string().as_bytes());
+    mac
+}
+
+/// Returns `path` with `expires` and `signature` query parameters appended.
+pub fn sign(path: &str, expires_at: DateTime<Utc>) -> String {
+    let expires = expires_at.timestamp();
+    let signature = hex::encode(mac_for(path, expires).finalize().into_bytes());
+    format!("{}?expires={}&signature={}", path, expires, signature)
+}
+
+/// Checks a signature produced by `sign` for the same path and that it has not expired.
+pub fn verify(path: &str, expires: i64, signature: &str) -> bool {
+    if expires < Utc::now().timestamp() {
+        return false;
+    }
+
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+
+    mac_for(path, expires).verify_slice(&signature).is_ok()
+}
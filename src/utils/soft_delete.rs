@@ -0,0 +1,35 @@
+use sea_orm::{ ColumnTrait, EntityTrait, QueryFilter, Select };
+
+/// Query scope for entities with a nullable `deleted_at` column.
+///
+/// Handlers should start from `find_active()` rather than `find()` so that
+/// soft-deleted rows stay hidden unless a query explicitly asks for them.
+pub trait SoftDelete: EntityTrait {
+    fn deleted_at_column() -> Self::Column;
+
+    fn find_active() -> Select<Self> {
+        Self::find().filter(Self::deleted_at_column().is_null())
+    }
+
+    fn find_deleted() -> Select<Self> {
+        Self::find().filter(Self::deleted_at_column().is_not_null())
+    }
+}
+
+impl SoftDelete for entity::user::Entity {
+    fn deleted_at_column() -> Self::Column {
+        entity::user::Column::DeletedAt
+    }
+}
+
+impl SoftDelete for entity::post::Entity {
+    fn deleted_at_column() -> Self::Column {
+        entity::post::Column::DeletedAt
+    }
+}
+
+impl SoftDelete for entity::comment::Entity {
+    fn deleted_at_column() -> Self::Column {
+        entity::comment::Column::DeletedAt
+    }
+}
@@ -0,0 +1,60 @@
+use sea_orm::{
+    sea_query::{ Query, SelectStatement, UnionType },
+    ColumnTrait,
+    QueryFilter,
+    Select,
+};
+
+use crate::utils::soft_delete::SoftDelete;
+
+/// Query scope for user-authored content. Every post and comment query made on
+/// behalf of a user should start from `find_visible_to` so blocks and mutes are
+/// honoured in one place rather than per handler.
+pub trait Visibility: SoftDelete {
+    fn author_column() -> Self::Column;
+
+    /// Active rows, minus those by authors the viewer muted or blocked, or who
+    /// blocked the viewer.
+    fn find_visible_to(viewer_id: i32) -> Select<Self> {
+        Self::find_active().filter(Self::author_column().not_in_subquery(hidden_authors(viewer_id)))
+    }
+}
+
+impl Visibility for entity::post::Entity {
+    fn author_column() -> Self::Column {
+        entity::post::Column::UserId
+    }
+}
+
+impl Visibility for entity::comment::Entity {
+    fn author_column() -> Self::Column {
+        entity::comment::Column::UserId
+    }
+}
+
+/// Ids of users whose content is hidden from `viewer_id`.
+pub fn hidden_authors(viewer_id: i32) -> SelectStatement {
+    use entity::{ block, mute };
+
+    Query::select()
+        .column(mute::Column::MutedId)
+        .from(mute::Entity)
+        .and_where(mute::Column::MuterId.eq(viewer_id))
+        .union(
+            UnionType::All,
+            Query::select()
+                .column(block::Column::BlockedId)
+                .from(block::Entity)
+                .and_where(block::Column::BlockerId.eq(viewer_id))
+                .to_owned()
+        )
+        .union(
+            UnionType::All,
+            Query::select()
+                .column(block::Column::BlockerId)
+                .from(block::Entity)
+                .and_where(block::Column::BlockedId.eq(viewer_id))
+                .to_owned()
+        )
+        .to_owned()
+}